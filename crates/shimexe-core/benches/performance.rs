@@ -1,6 +1,7 @@
-use criterion::{black_box, criterion_group, criterion_main, Criterion};
+use criterion::{criterion_group, criterion_main, Criterion};
 use shimexe_core::{ShimConfig, ShimRunner};
 use std::collections::HashMap;
+use std::hint::black_box;
 use std::io::Write;
 use tempfile::NamedTempFile;
 
@@ -14,6 +15,7 @@ fn create_test_config() -> ShimConfig {
             download_url: None,
             source_type: shimexe_core::config::SourceType::File,
             extracted_executables: Vec::new(),
+            ..Default::default()
         },
         args: Default::default(),
         env: {
@@ -24,6 +26,7 @@ fn create_test_config() -> ShimConfig {
         },
         metadata: Default::default(),
         auto_update: None,
+        ..Default::default()
     }
 }
 
@@ -103,7 +106,7 @@ fn bench_validation(c: &mut Criterion) {
     c.bench_function("validate_executable", |b| {
         b.iter(|| {
             let result = runner.validate();
-            black_box(result);
+            let _ = black_box(result);
         })
     });
 }
@@ -118,7 +121,7 @@ fn bench_config_expansion(c: &mut Criterion) {
         b.iter(|| {
             let mut config_copy = config.clone();
             let result = config_copy.expand_env_vars();
-            black_box(result);
+            let _ = black_box(result);
         })
     });
 }
@@ -152,7 +155,7 @@ fn bench_cache_performance(c: &mut Criterion) {
     c.bench_function("cached_validation", |b| {
         b.iter(|| {
             let result = runner.validate();
-            black_box(result);
+            let _ = black_box(result);
         })
     });
 }
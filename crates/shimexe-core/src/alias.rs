@@ -0,0 +1,180 @@
+//! Alias shims (`shim.target_shim`).
+//!
+//! An alias runs another shim with extra arguments and environment instead of
+//! duplicating its whole configuration: `ll` can be the `ls` shim plus `-la`.
+//! The target is looked up as `<name>.shim.toml` in the alias's directory and
+//! may itself be an alias; cycles are reported as errors.
+
+use std::path::Path;
+use tracing::debug;
+
+use crate::config::ShimConfig;
+use crate::error::{Result, ShimError};
+
+/// Resolve `config` against the shims in `shim_dir` if it is an alias
+///
+/// Returns the target's configuration with the alias layered on top; other
+/// configurations are returned unchanged.
+pub fn resolve(config: ShimConfig, shim_dir: &Path) -> Result<ShimConfig> {
+    let mut chain = vec![config.shim.name.clone()];
+    resolve_chain(config, shim_dir, &mut chain)
+}
+
+fn resolve_chain(
+    config: ShimConfig,
+    shim_dir: &Path,
+    chain: &mut Vec<String>,
+) -> Result<ShimConfig> {
+    let Some(target) = config.shim.target_shim.clone() else {
+        return Ok(config);
+    };
+
+    if chain.contains(&target) {
+        chain.push(target);
+        return Err(ShimError::Config(format!(
+            "Alias cycle: {}",
+            chain.join(" -> ")
+        )));
+    }
+    chain.push(target.clone());
+
+    let target_file = shim_dir.join(format!("{}.shim.toml", target));
+    if !target_file.exists() {
+        return Err(ShimError::Config(format!(
+            "Shim '{}' targets '{}', which does not exist ({})",
+            config.shim.name,
+            target,
+            target_file.display()
+        )));
+    }
+    debug!("Shim '{}' is an alias of '{}'", config.shim.name, target);
+
+    let target_config = resolve_chain(ShimConfig::from_file(&target_file)?, shim_dir, chain)?;
+    Ok(layer(target_config, config))
+}
+
+/// Layer the alias `alias` on top of `target`
+///
+/// Args are appended, env entries and profiles override the target's, and
+/// sections the alias sets replace the target's. The result runs under the
+/// alias's name, while downloads, data and `.tool-versions` lookups keep
+/// using the target's.
+fn layer(mut target: ShimConfig, alias: ShimConfig) -> ShimConfig {
+    target
+        .shim
+        .data_name
+        .get_or_insert_with(|| target.shim.name.clone());
+    if let Some(ref mut versions) = target.versions {
+        versions
+            .tool_name
+            .get_or_insert_with(|| target.shim.name.clone());
+    }
+    target.shim.name = alias.shim.name;
+    target.shim.args.extend(alias.shim.args);
+    if alias.shim.cwd.is_some() {
+        target.shim.cwd = alias.shim.cwd;
+    }
+
+    if alias.args.template.is_some() || alias.args.inline.is_some() {
+        target.args = alias.args;
+    } else {
+        target.args.prefix.extend(alias.args.prefix);
+        target.args.suffix.extend(alias.args.suffix);
+        target.args.rewrite.extend(alias.args.rewrite);
+    }
+
+    target.env.extend(alias.env);
+    target.env_files.extend(alias.env_files);
+
+    replace_if_set(&mut target.env_from, alias.env_from);
+    replace_if_set(&mut target.hooks, alias.hooks);
+    replace_if_set(&mut target.timeout, alias.timeout);
+    replace_if_set(&mut target.retry, alias.retry);
+    replace_if_set(&mut target.logging, alias.logging);
+    replace_if_set(&mut target.history, alias.history);
+    replace_if_set(&mut target.concurrency, alias.concurrency);
+    replace_if_set(&mut target.exit, alias.exit);
+    replace_if_set(&mut target.limits, alias.limits);
+    replace_if_set(&mut target.sandbox, alias.sandbox);
+    replace_if_set(&mut target.wasm, alias.wasm);
+    replace_if_set(&mut target.dispatch, alias.dispatch);
+    replace_if_set(&mut target.project, alias.project);
+    replace_if_set(&mut target.versions, alias.versions);
+    target.profiles.extend(alias.profiles);
+
+    // Updates belong to the target shim, not to its aliases
+    target.auto_update = None;
+    target
+}
+
+/// Replace `target` with `value` when the alias sets the section
+fn replace_if_set<T>(target: &mut Option<T>, value: Option<T>) {
+    if value.is_some() {
+        *target = value;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn alias(name: &str, target: &str) -> ShimConfig {
+        let mut config = ShimConfig::new(name, "");
+        config.shim.target_shim = Some(target.to_string());
+        config
+    }
+
+    #[test]
+    fn test_alias_layers_args_and_env() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut ls = ShimConfig::new("ls", "/bin/ls");
+        ls.shim.args = vec!["--color=auto".to_string()];
+        ls.env.insert("LS_COLORS".to_string(), "di=34".to_string());
+        ls.to_file(dir.path().join("ls.shim.toml")).unwrap();
+
+        let mut ll = alias("ll", "ls");
+        ll.shim.args = vec!["-la".to_string()];
+        ll.env.insert("LS_COLORS".to_string(), "di=32".to_string());
+
+        let resolved = resolve(ll, dir.path()).unwrap();
+        assert_eq!(resolved.shim.name, "ll");
+        assert_eq!(resolved.shim.data_name(), "ls");
+        assert_eq!(resolved.shim.path, "/bin/ls");
+        assert_eq!(resolved.shim.args, vec!["--color=auto", "-la"]);
+        assert_eq!(resolved.env["LS_COLORS"], "di=32");
+        assert!(resolved.shim.target_shim.is_none());
+    }
+
+    #[test]
+    fn test_alias_keeps_target_tool_name_for_version_files() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut node = ShimConfig::new("node", "/opt/node/bin/node");
+        node.versions = Some(Default::default());
+        node.to_file(dir.path().join("node.shim.toml")).unwrap();
+
+        let resolved = resolve(alias("n", "node"), dir.path()).unwrap();
+        assert_eq!(resolved.shim.name, "n");
+        let versions = resolved.versions.unwrap();
+        assert_eq!(versions.tool_name.as_deref(), Some("node"));
+    }
+
+    #[test]
+    fn test_alias_cycle_is_detected() {
+        let dir = tempfile::tempdir().unwrap();
+        alias("a", "b")
+            .to_file(dir.path().join("a.shim.toml"))
+            .unwrap();
+        alias("b", "a")
+            .to_file(dir.path().join("b.shim.toml"))
+            .unwrap();
+
+        let err = resolve(alias("a", "b"), dir.path()).unwrap_err();
+        assert!(err.to_string().contains("a -> b -> a"), "{}", err);
+    }
+
+    #[test]
+    fn test_missing_target() {
+        let dir = tempfile::tempdir().unwrap();
+        assert!(resolve(alias("ll", "ls"), dir.path()).is_err());
+    }
+}
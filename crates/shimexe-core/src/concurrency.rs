@@ -0,0 +1,270 @@
+//! Per-shim concurrency limit (`[concurrency]`).
+//!
+//! Each running instance holds an advisory lock on one of `max_instances`
+//! slot files under `<data dir>/locks`. The operating system releases the
+//! lock when its holder exits, so a crashed shim never blocks the slot. The
+//! holder's PID is written into the slot so that waiting shims can report who
+//! they are waiting for and leftovers of dead processes can be told apart.
+
+use serde::{Deserialize, Serialize};
+use std::fs::{File, OpenOptions, TryLockError};
+use std::io::{Read, Seek, SeekFrom, Write};
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
+use tracing::{debug, info};
+
+use crate::error::{Result, ShimError};
+
+/// How often a waiting shim checks for a free slot
+const POLL_INTERVAL: Duration = Duration::from_millis(100);
+
+/// Limit on concurrently running instances of a shim
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ConcurrencyConfig {
+    /// Maximum number of instances running at the same time
+    #[serde(default = "default_max_instances")]
+    pub max_instances: u32,
+    /// What to do when all instances are busy
+    #[serde(default)]
+    pub lock_wait: LockWait,
+    /// Give up waiting after this many seconds (only with `lock_wait = "wait"`)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub wait_timeout_secs: Option<u64>,
+}
+
+/// Behavior when no instance slot is free
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum LockWait {
+    /// Wait until a slot frees up (optionally bounded by `wait_timeout_secs`)
+    #[default]
+    Wait,
+    /// Fail immediately
+    Fail,
+}
+
+fn default_max_instances() -> u32 {
+    1
+}
+
+impl ConcurrencyConfig {
+    /// Allow at most `max_instances`, waiting for a free slot
+    pub fn new(max_instances: u32) -> Self {
+        Self {
+            max_instances,
+            lock_wait: LockWait::default(),
+            wait_timeout_secs: None,
+        }
+    }
+
+    /// Validate the concurrency settings
+    pub fn validate(&self) -> Result<()> {
+        if self.max_instances == 0 {
+            return Err(ShimError::Config(
+                "concurrency.max_instances must be at least 1".to_string(),
+            ));
+        }
+        if self.lock_wait == LockWait::Fail && self.wait_timeout_secs.is_some() {
+            return Err(ShimError::Config(
+                "concurrency.wait_timeout_secs requires lock_wait = \"wait\"".to_string(),
+            ));
+        }
+        Ok(())
+    }
+
+    /// Take an instance slot in `lock_dir`, waiting according to the policy
+    pub fn acquire(&self, lock_dir: &Path) -> Result<InstanceGuard> {
+        std::fs::create_dir_all(lock_dir)?;
+        let deadline = self
+            .wait_timeout_secs
+            .map(|secs| Instant::now() + Duration::from_secs(secs));
+        let mut announced = false;
+
+        loop {
+            for slot in 0..self.max_instances {
+                if let Some(guard) = InstanceGuard::try_acquire(&slot_path(lock_dir, slot))? {
+                    return Ok(guard);
+                }
+            }
+
+            let holders = holders(lock_dir, self.max_instances);
+            let waited_out = deadline.is_some_and(|deadline| Instant::now() >= deadline);
+            if self.lock_wait == LockWait::Fail || waited_out {
+                return Err(ShimError::InstanceLimit(format!(
+                    "{} instance(s) already running{}",
+                    self.max_instances,
+                    describe_holders(&holders)
+                )));
+            }
+
+            if !announced {
+                info!(
+                    "Waiting for a free instance slot{}",
+                    describe_holders(&holders)
+                );
+                announced = true;
+            }
+            std::thread::sleep(POLL_INTERVAL);
+        }
+    }
+}
+
+/// A held instance slot, released when dropped
+#[derive(Debug)]
+pub struct InstanceGuard {
+    file: File,
+    path: PathBuf,
+}
+
+impl InstanceGuard {
+    /// Lock the slot at `path` if nobody else holds it
+    fn try_acquire(path: &Path) -> Result<Option<Self>> {
+        let mut file = OpenOptions::new()
+            .create(true)
+            .truncate(false)
+            .read(true)
+            .write(true)
+            .open(path)?;
+
+        match file.try_lock() {
+            Ok(()) => {}
+            Err(TryLockError::WouldBlock) => return Ok(None),
+            Err(TryLockError::Error(e)) => return Err(e.into()),
+        }
+
+        if let Some(pid) = read_pid(&mut file) {
+            if pid != std::process::id() && !process_alive(pid) {
+                debug!(
+                    "Reclaiming stale instance lock {} left by process {}",
+                    path.display(),
+                    pid
+                );
+            }
+        }
+
+        file.set_len(0)?;
+        file.seek(SeekFrom::Start(0))?;
+        write!(file, "{}", std::process::id())?;
+        debug!("Acquired instance lock {}", path.display());
+
+        Ok(Some(Self {
+            file,
+            path: path.to_path_buf(),
+        }))
+    }
+
+    /// Slot file held by this guard
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+}
+
+impl Drop for InstanceGuard {
+    fn drop(&mut self) {
+        // Clear the PID so the slot does not look stale; closing the file unlocks it
+        let _ = self.file.set_len(0);
+        let _ = self.file.unlock();
+    }
+}
+
+fn slot_path(lock_dir: &Path, slot: u32) -> PathBuf {
+    lock_dir.join(format!("instance-{}.lock", slot))
+}
+
+/// PIDs recorded in the busy slots of `lock_dir`
+fn holders(lock_dir: &Path, max_instances: u32) -> Vec<u32> {
+    (0..max_instances)
+        .filter_map(|slot| {
+            let mut file = File::open(slot_path(lock_dir, slot)).ok()?;
+            read_pid(&mut file)
+        })
+        .filter(|pid| process_alive(*pid))
+        .collect()
+}
+
+fn describe_holders(holders: &[u32]) -> String {
+    if holders.is_empty() {
+        return String::new();
+    }
+    let pids: Vec<String> = holders.iter().map(u32::to_string).collect();
+    format!(" (pid {})", pids.join(", "))
+}
+
+fn read_pid(file: &mut File) -> Option<u32> {
+    let mut content = String::new();
+    file.seek(SeekFrom::Start(0)).ok()?;
+    file.read_to_string(&mut content).ok()?;
+    content.trim().parse().ok()
+}
+
+/// Whether a process with `pid` still exists
+#[cfg(unix)]
+fn process_alive(pid: u32) -> bool {
+    // SAFETY: kill(2) with signal 0 only checks for existence
+    let result = unsafe { libc::kill(pid as libc::pid_t, 0) };
+    result == 0 || std::io::Error::last_os_error().raw_os_error() == Some(libc::EPERM)
+}
+
+#[cfg(not(unix))]
+fn process_alive(_pid: u32) -> bool {
+    true
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_validate() {
+        assert!(ConcurrencyConfig::new(1).validate().is_ok());
+        assert!(ConcurrencyConfig::new(0).validate().is_err());
+
+        let mut config = ConcurrencyConfig::new(1);
+        config.lock_wait = LockWait::Fail;
+        config.wait_timeout_secs = Some(5);
+        assert!(config.validate().is_err());
+    }
+
+    #[test]
+    fn test_slots_are_exclusive() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut config = ConcurrencyConfig::new(2);
+        config.lock_wait = LockWait::Fail;
+
+        let first = config.acquire(dir.path()).unwrap();
+        let second = config.acquire(dir.path()).unwrap();
+        assert_ne!(first.path(), second.path());
+
+        let err = config.acquire(dir.path()).unwrap_err();
+        assert!(matches!(err, ShimError::InstanceLimit(_)));
+        assert!(err.to_string().contains(&std::process::id().to_string()));
+
+        drop(first);
+        assert!(config.acquire(dir.path()).is_ok());
+    }
+
+    #[test]
+    fn test_wait_timeout() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut config = ConcurrencyConfig::new(1);
+        config.wait_timeout_secs = Some(0);
+
+        let _held = config.acquire(dir.path()).unwrap();
+        assert!(matches!(
+            config.acquire(dir.path()),
+            Err(ShimError::InstanceLimit(_))
+        ));
+    }
+
+    #[test]
+    fn test_stale_pid_is_reclaimed() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(slot_path(dir.path(), 0), "999999999").unwrap();
+
+        let mut config = ConcurrencyConfig::new(1);
+        config.lock_wait = LockWait::Fail;
+        let guard = config.acquire(dir.path()).unwrap();
+        let content = std::fs::read_to_string(guard.path()).unwrap();
+        assert_eq!(content, std::process::id().to_string());
+    }
+}
@@ -1,12 +1,29 @@
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
 use std::path::{Path, PathBuf};
 use std::sync::{Arc, Mutex};
 use std::time::{Duration, SystemTime};
 
+use crate::concurrency::ConcurrencyConfig;
+use crate::dispatch::DispatchConfig;
+use crate::dotenv::EnvFile;
+use crate::env_from::EnvFrom;
 use crate::error::{Result, ShimError};
+use crate::exit::ExitConfig;
+use crate::history::HistoryConfig;
+use crate::hooks::HooksConfig;
+use crate::interpreter::Interpreter;
+use crate::limits::LimitsConfig;
+use crate::logging::LoggingConfig;
+use crate::process::TimeoutConfig;
+use crate::profiles::{self, Profile};
+use crate::project::ProjectConfig;
+use crate::retry::RetryConfig;
+use crate::sandbox::SandboxConfig;
 use crate::template::ArgsConfig;
 use crate::utils::expand_env_vars;
+use crate::versions::VersionsConfig;
+use crate::wasm::WasmConfig;
 
 /// Configuration cache entry
 #[derive(Debug, Clone)]
@@ -115,7 +132,7 @@ impl Default for ConfigCache {
 }
 
 /// Main shim configuration structure
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct ShimConfig {
     /// Core shim configuration
     pub shim: ShimCore,
@@ -131,14 +148,63 @@ pub struct ShimConfig {
     /// Auto-update configuration
     #[serde(default)]
     pub auto_update: Option<AutoUpdate>,
+    /// Dotenv files loaded before `[env]` (later files override earlier ones)
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub env_files: Vec<EnvFile>,
+    /// Environment produced by an activation script or command
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub env_from: Option<EnvFrom>,
+    /// Commands run before and after the target executable
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub hooks: Option<HooksConfig>,
+    /// Execution timeout and kill policy for the target
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub timeout: Option<TimeoutConfig>,
+    /// Re-run the target when it exits with selected codes
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub retry: Option<RetryConfig>,
+    /// Tee the target's output into a rotating log file
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub logging: Option<LoggingConfig>,
+    /// Record invocations in the local execution history
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub history: Option<HistoryConfig>,
+    /// Limit how many instances of the shim may run at once
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub concurrency: Option<ConcurrencyConfig>,
+    /// Normalize the target's exit status
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub exit: Option<ExitConfig>,
+    /// Resource limits for the target (Unix only)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub limits: Option<LimitsConfig>,
+    /// Restrict the target's filesystem and network access (Linux only)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub sandbox: Option<SandboxConfig>,
+    /// WASI settings for `.wasm` targets
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub wasm: Option<WasmConfig>,
+    /// Route subcommands to other executables
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub dispatch: Option<DispatchConfig>,
+    /// Discovery of project-local overrides (`.shimexe.toml`)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub project: Option<ProjectConfig>,
+    /// Installed versions of the target and the default one
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub versions: Option<VersionsConfig>,
+    /// Named variants of the shim, selected at run time
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub profiles: BTreeMap<String, Profile>,
 }
 
 /// Core shim configuration
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct ShimCore {
     /// Name of the shim
     pub name: String,
-    /// Path to the target executable
+    /// Path to the target executable (empty for aliases)
+    #[serde(default, skip_serializing_if = "String::is_empty")]
     pub path: String,
     /// Default arguments to pass to the executable
     #[serde(default)]
@@ -155,6 +221,23 @@ pub struct ShimCore {
     /// For archives: list of extracted executables
     #[serde(default, skip_serializing_if = "Vec::is_empty")]
     pub extracted_executables: Vec<ExtractedExecutable>,
+    /// Interpreter for script targets (`"auto"`, `"python3 -u"` or `["java", "-jar"]`)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub interpreter: Option<Interpreter>,
+    /// Run another shim (by name) with this shim's args and env layered on top
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub target_shim: Option<String>,
+    /// Name whose data directory holds downloads, locks and caches, when it
+    /// differs from `name` (set to the target's name when resolving an alias)
+    #[serde(skip)]
+    pub data_name: Option<String>,
+}
+
+impl ShimCore {
+    /// Name of the shim's data directory (`<shim dir>/<data name>`)
+    pub fn data_name(&self) -> &str {
+        self.data_name.as_deref().unwrap_or(&self.name)
+    }
 }
 
 /// Source type for the shim
@@ -219,6 +302,16 @@ pub struct AutoUpdate {
     pub post_update_command: Option<String>,
 }
 
+impl AutoUpdate {
+    /// Whether updating runs commands of its own, which requires a trusted shim file
+    pub fn runs_commands(&self) -> bool {
+        self.pre_update_command.is_some()
+            || self.post_update_command.is_some()
+            || matches!(self.provider, UpdateProvider::Custom { .. })
+            || matches!(self.version_check, VersionCheck::Command { .. })
+    }
+}
+
 /// Update provider types
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "lowercase")]
@@ -298,11 +391,13 @@ impl ShimConfig {
                 download_url: None,
                 source_type: SourceType::File,
                 extracted_executables: Vec::new(),
+                ..Default::default()
             },
             args: Default::default(),
             env: HashMap::new(),
             metadata: ShimMetadata::default(),
             auto_update: None,
+            ..Default::default()
         }
     }
 
@@ -396,10 +491,72 @@ impl ShimConfig {
             return Err(ShimError::Config("Shim name cannot be empty".to_string()));
         }
 
-        if self.shim.path.is_empty() {
-            return Err(ShimError::Config("Shim path cannot be empty".to_string()));
+        match self.shim.target_shim {
+            Some(ref target) if target.trim().is_empty() => {
+                return Err(ShimError::Config(
+                    "shim.target_shim cannot be empty".to_string(),
+                ));
+            }
+            Some(_) if !self.shim.path.is_empty() => {
+                return Err(ShimError::Config(
+                    "An alias shim sets either shim.path or shim.target_shim, not both".to_string(),
+                ));
+            }
+            Some(_) => {}
+            None if self.shim.path.is_empty() => {
+                return Err(ShimError::Config("Shim path cannot be empty".to_string()));
+            }
+            None => {}
+        }
+
+        if let Some(ref interpreter) = self.shim.interpreter {
+            interpreter.validate()?;
         }
 
+        self.args.validate()?;
+
+        if let Some(ref env_from) = self.env_from {
+            env_from.validate()?;
+        }
+
+        if let Some(ref retry) = self.retry {
+            retry.validate()?;
+        }
+
+        if let Some(ref concurrency) = self.concurrency {
+            concurrency.validate()?;
+        }
+
+        if let Some(ref exit) = self.exit {
+            exit.validate()?;
+        }
+
+        if let Some(ref limits) = self.limits {
+            limits.validate()?;
+        }
+
+        if let Some(ref sandbox) = self.sandbox {
+            sandbox.validate()?;
+        }
+
+        if let Some(ref wasm) = self.wasm {
+            wasm.validate()?;
+        }
+
+        if let Some(ref dispatch) = self.dispatch {
+            dispatch.validate()?;
+        }
+
+        if let Some(ref project) = self.project {
+            project.validate()?;
+        }
+
+        if let Some(ref versions) = self.versions {
+            versions.validate()?;
+        }
+
+        profiles::validate(self)?;
+
         Ok(())
     }
 
@@ -413,6 +570,10 @@ impl ShimConfig {
             *arg = expand_env_vars(arg)?;
         }
 
+        if let Some(ref mut interpreter) = self.shim.interpreter {
+            interpreter.expand_env_vars()?;
+        }
+
         // Expand cwd if present
         if let Some(ref mut cwd) = self.shim.cwd {
             *cwd = expand_env_vars(cwd)?;
@@ -423,6 +584,14 @@ impl ShimConfig {
             *value = expand_env_vars(value)?;
         }
 
+        if let Some(ref mut dispatch) = self.dispatch {
+            dispatch.expand_env_vars()?;
+        }
+
+        for profile in self.profiles.values_mut() {
+            profile.expand_env_vars()?;
+        }
+
         Ok(())
     }
 
@@ -473,7 +642,7 @@ impl ShimConfig {
                     if let Some(home_dir) = dirs::home_dir() {
                         let download_path = home_dir
                             .join(".shimexe")
-                            .join(&self.shim.name)
+                            .join(self.shim.data_name())
                             .join("bin")
                             .join(&filename);
 
@@ -502,7 +671,7 @@ impl ShimConfig {
                     if let Some(home_dir) = dirs::home_dir() {
                         let download_path = home_dir
                             .join(".shimexe")
-                            .join(&self.shim.name)
+                            .join(self.shim.data_name())
                             .join("bin")
                             .join(&filename);
 
@@ -614,11 +783,13 @@ description = "Test shim"
                 download_url: None,
                 source_type: SourceType::File,
                 extracted_executables: vec![],
+                ..Default::default()
             },
             args: Default::default(),
             env: HashMap::new(),
             metadata: Default::default(),
             auto_update: None,
+            ..Default::default()
         };
         assert!(config.validate().is_ok());
 
@@ -632,11 +803,13 @@ description = "Test shim"
                 download_url: None,
                 source_type: SourceType::File,
                 extracted_executables: vec![],
+                ..Default::default()
             },
             args: Default::default(),
             env: HashMap::new(),
             metadata: Default::default(),
             auto_update: None,
+            ..Default::default()
         };
         assert!(invalid_config.validate().is_err());
 
@@ -650,11 +823,13 @@ description = "Test shim"
                 download_url: None,
                 source_type: SourceType::File,
                 extracted_executables: vec![],
+                ..Default::default()
             },
             args: Default::default(),
             env: HashMap::new(),
             metadata: Default::default(),
             auto_update: None,
+            ..Default::default()
         };
         assert!(invalid_config.validate().is_err());
     }
@@ -670,6 +845,7 @@ description = "Test shim"
                 download_url: None,
                 source_type: SourceType::File,
                 extracted_executables: vec![],
+                ..Default::default()
             },
             args: Default::default(),
             env: {
@@ -684,6 +860,7 @@ description = "Test shim"
                 tags: vec![],
             },
             auto_update: None,
+            ..Default::default()
         };
 
         let temp_file = NamedTempFile::new().unwrap();
@@ -710,6 +887,7 @@ description = "Test shim"
                 download_url: None,
                 source_type: SourceType::File,
                 extracted_executables: vec![],
+                ..Default::default()
             },
             args: Default::default(),
             env: {
@@ -719,6 +897,7 @@ description = "Test shim"
             },
             metadata: Default::default(),
             auto_update: None,
+            ..Default::default()
         };
 
         config.expand_env_vars().unwrap();
@@ -877,4 +1056,28 @@ description = "Test shim without download URL"
         assert_eq!(config.shim.download_url, None);
         assert_eq!(config.get_download_url(), None);
     }
+
+    #[test]
+    fn test_auto_update_runs_commands() {
+        let mut auto_update = AutoUpdate {
+            enabled: true,
+            provider: UpdateProvider::Github {
+                repo: "owner/tool".to_string(),
+                asset_pattern: "tool-{version}".to_string(),
+                include_prerelease: false,
+            },
+            download_url: "https://example.com/tool-{version}".to_string(),
+            version_check: VersionCheck::GithubLatest {
+                repo: "owner/tool".to_string(),
+                include_prerelease: false,
+            },
+            check_interval_hours: 24,
+            pre_update_command: None,
+            post_update_command: None,
+        };
+        assert!(!auto_update.runs_commands());
+
+        auto_update.post_update_command = Some("tool --init".to_string());
+        assert!(auto_update.runs_commands());
+    }
 }
@@ -0,0 +1,369 @@
+//! Subcommand dispatch (`[dispatch]`).
+//!
+//! One shim can front a family of executables such as `tool-build` and
+//! `tool-test`: the first argument selects a route, which runs its own target
+//! with its own default args and environment. Routes are matched by name, then
+//! by alias, then by glob pattern; arguments that match no route go to
+//! `shim.path` as usual.
+
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+use std::fmt::Write;
+
+use crate::config::{ShimConfig, SourceType};
+use crate::error::{Result, ShimError};
+use crate::utils::expand_env_vars;
+
+/// Placeholder in a route's `path` replaced by the subcommand
+pub const SUBCOMMAND_PLACEHOLDER: &str = "{subcommand}";
+
+/// Arguments answered with the list of routes
+const HELP_ARGS: &[&str] = &["--help", "-h"];
+
+/// Routes selected by the shim's first argument
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DispatchConfig {
+    /// Remove the subcommand from the arguments passed to the route
+    #[serde(default = "default_true")]
+    pub strip: bool,
+    /// Answer `--help` and `-h` with the list of routes
+    #[serde(default = "default_true")]
+    pub help: bool,
+    /// Routes keyed by subcommand name or glob pattern (`*`, `?`)
+    #[serde(default)]
+    pub routes: BTreeMap<String, Route>,
+}
+
+fn default_true() -> bool {
+    true
+}
+
+impl Default for DispatchConfig {
+    fn default() -> Self {
+        Self {
+            strip: true,
+            help: true,
+            routes: BTreeMap::new(),
+        }
+    }
+}
+
+/// Target of one subcommand
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct Route {
+    /// Executable to run, resolved like `shim.path` (may contain `{subcommand}`)
+    pub path: String,
+    /// Other names for the subcommand
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub aliases: Vec<String>,
+    /// Default args replacing `shim.args`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub args: Option<Vec<String>>,
+    /// Environment variables layered over `[env]`
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub env: HashMap<String, String>,
+    /// Override `dispatch.strip` for this route
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub strip: Option<bool>,
+    /// One-line summary shown by `--help`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+}
+
+/// A route selected for a particular invocation
+#[derive(Debug, Clone, PartialEq)]
+pub struct Dispatch<'a> {
+    /// Key of the route in `dispatch.routes`
+    pub name: &'a str,
+    /// Subcommand given on the command line
+    pub subcommand: &'a str,
+    pub route: &'a Route,
+    strip: bool,
+}
+
+fn is_glob(name: &str) -> bool {
+    name.contains(['*', '?'])
+}
+
+fn glob_regex(pattern: &str) -> Result<Regex> {
+    let regex = regex::escape(pattern)
+        .replace(r"\*", ".*")
+        .replace(r"\?", ".");
+    Regex::new(&format!("^{}$", regex))
+        .map_err(|e| ShimError::Config(format!("Invalid dispatch route '{}': {}", pattern, e)))
+}
+
+impl DispatchConfig {
+    /// Validate route names, aliases and paths
+    pub fn validate(&self) -> Result<()> {
+        let mut names = HashMap::new();
+        for (name, route) in &self.routes {
+            if name.trim().is_empty() || route.path.trim().is_empty() {
+                return Err(ShimError::Config(format!(
+                    "dispatch route '{}' needs a name and a path",
+                    name
+                )));
+            }
+            if is_glob(name) {
+                glob_regex(name)?;
+            }
+            for alias in std::iter::once(name).chain(&route.aliases) {
+                if let Some(other) = names.insert(alias.as_str(), name.as_str()) {
+                    return Err(ShimError::Config(format!(
+                        "dispatch subcommand '{}' is used by routes '{}' and '{}'",
+                        alias, other, name
+                    )));
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Expand environment variables in the routes
+    pub fn expand_env_vars(&mut self) -> Result<()> {
+        for route in self.routes.values_mut() {
+            route.path = expand_env_vars(&route.path)?;
+            for arg in route.args.iter_mut().flatten() {
+                *arg = expand_env_vars(arg)?;
+            }
+            for value in route.env.values_mut() {
+                *value = expand_env_vars(value)?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Whether `args` asks for the list of routes
+    pub fn is_help(&self, args: &[String]) -> bool {
+        self.help
+            && args
+                .first()
+                .is_some_and(|arg| HELP_ARGS.contains(&arg.as_str()))
+    }
+
+    /// Route selected by the first of `args`, if any
+    ///
+    /// Exact names win over aliases, and aliases over glob patterns; longer
+    /// patterns are tried first.
+    pub fn route<'a>(&'a self, args: &'a [String]) -> Result<Option<Dispatch<'a>>> {
+        let Some(subcommand) = args.first() else {
+            return Ok(None);
+        };
+
+        let found = self
+            .routes
+            .get_key_value(subcommand)
+            .filter(|(name, _)| !is_glob(name))
+            .or_else(|| {
+                self.routes
+                    .iter()
+                    .find(|(_, route)| route.aliases.contains(subcommand))
+            });
+        let found = match found {
+            Some(found) => Some(found),
+            None => {
+                let mut globs: Vec<_> = self
+                    .routes
+                    .iter()
+                    .filter(|(name, _)| is_glob(name))
+                    .collect();
+                globs.sort_by_key(|(name, _)| std::cmp::Reverse(name.len()));
+                let mut matched = None;
+                for (name, route) in globs {
+                    if glob_regex(name)?.is_match(subcommand) {
+                        matched = Some((name, route));
+                        break;
+                    }
+                }
+                matched
+            }
+        };
+
+        Ok(found.map(|(name, route)| Dispatch {
+            name,
+            subcommand,
+            route,
+            strip: route.strip.unwrap_or(self.strip),
+        }))
+    }
+
+    /// Usage text listing the routes of the shim `shim_name`
+    pub fn help(&self, shim_name: &str) -> String {
+        let mut help = format!(
+            "Usage: {} <subcommand> [args...]\n\nSubcommands:\n",
+            shim_name
+        );
+        let entries: Vec<(String, String)> = self
+            .routes
+            .iter()
+            .map(|(name, route)| {
+                let names = std::iter::once(name)
+                    .chain(&route.aliases)
+                    .map(String::as_str)
+                    .collect::<Vec<_>>()
+                    .join(", ");
+                let summary = route
+                    .description
+                    .clone()
+                    .unwrap_or_else(|| format!("runs {}", route.path));
+                (names, summary)
+            })
+            .collect();
+
+        let width = entries.iter().map(|(names, _)| names.len()).max();
+        for (names, summary) in &entries {
+            let _ = writeln!(
+                help,
+                "  {:width$}  {}",
+                names,
+                summary,
+                width = width.unwrap_or(0)
+            );
+        }
+        help
+    }
+}
+
+impl Dispatch<'_> {
+    /// Configuration running this route in place of `shim.path`
+    ///
+    /// A subcommand substituted into the path must be a plain name, so that
+    /// `tool ../../bin/sh` cannot reach executables outside the route.
+    pub fn config(&self, base: &ShimConfig) -> Result<ShimConfig> {
+        if self.route.path.contains(SUBCOMMAND_PLACEHOLDER)
+            && (self.subcommand.contains(['/', '\\']) || self.subcommand.contains(".."))
+        {
+            return Err(ShimError::Config(format!(
+                "dispatch subcommand '{}' must not contain path separators or '..'",
+                self.subcommand
+            )));
+        }
+
+        let mut config = base.clone();
+        config.shim.path = self
+            .route
+            .path
+            .replace(SUBCOMMAND_PLACEHOLDER, self.subcommand);
+        config.shim.source_type = SourceType::File;
+        config.shim.download_url = None;
+        config.shim.extracted_executables.clear();
+        config.shim.interpreter = None;
+        if let Some(ref args) = self.route.args {
+            config.shim.args = args.clone();
+        }
+        config
+            .env
+            .extend(self.route.env.iter().map(|(k, v)| (k.clone(), v.clone())));
+        config.auto_update = None;
+        config.dispatch = None;
+        Ok(config)
+    }
+
+    /// Arguments passed on to the route
+    pub fn args(&self, args: &[String]) -> Vec<String> {
+        match self.strip {
+            true => args[1..].to_vec(),
+            false => args.to_vec(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn config() -> DispatchConfig {
+        let config: DispatchConfig = toml::from_str(
+            r#"
+strip = true
+
+[routes.build]
+path = "tool-build"
+aliases = ["b"]
+description = "Build the project"
+
+[routes."test-*"]
+path = "tool-{subcommand}"
+strip = false
+
+[routes."*"]
+path = "tool-plugin"
+"#,
+        )
+        .unwrap();
+        config.validate().unwrap();
+        config
+    }
+
+    fn args(args: &[&str]) -> Vec<String> {
+        args.iter().map(|s| s.to_string()).collect()
+    }
+
+    #[test]
+    fn test_route_matching() {
+        let config = config();
+
+        let argv = args(&["b", "--release"]);
+        let dispatch = config.route(&argv).unwrap().unwrap();
+        assert_eq!(dispatch.name, "build");
+        assert_eq!(dispatch.args(&argv), args(&["--release"]));
+
+        let argv = args(&["test-unit"]);
+        let dispatch = config.route(&argv).unwrap().unwrap();
+        assert_eq!(dispatch.name, "test-*");
+        assert_eq!(dispatch.args(&argv), args(&["test-unit"]));
+        let base = ShimConfig::new("tool", "tool");
+        assert_eq!(dispatch.config(&base).unwrap().shim.path, "tool-test-unit");
+
+        let argv = args(&["other"]);
+        assert_eq!(config.route(&argv).unwrap().unwrap().name, "*");
+        assert!(config.route(&[]).unwrap().is_none());
+    }
+
+    #[test]
+    fn test_help_lists_routes() {
+        let config = config();
+        assert!(config.is_help(&args(&["--help"])));
+        assert!(!config.is_help(&args(&["build", "--help"])));
+
+        let help = config.help("tool");
+        assert!(help.starts_with("Usage: tool <subcommand>"));
+        assert!(help.contains("build, b  Build the project"));
+        assert!(help.contains("runs tool-{subcommand}"));
+    }
+
+    #[test]
+    fn test_validate_rejects_duplicate_aliases() {
+        let config: DispatchConfig = toml::from_str(
+            r#"
+[routes.build]
+path = "tool-build"
+aliases = ["test"]
+
+[routes.test]
+path = "tool-test"
+"#,
+        )
+        .unwrap();
+        assert!(config.validate().is_err());
+    }
+
+    #[test]
+    fn test_subcommand_cannot_escape_route_path() {
+        let config = config();
+        let base = ShimConfig::new("tool", "tool");
+
+        for subcommand in ["test-/../../bin/sh", "test-..", "test-a\\b"] {
+            let argv = args(&[subcommand]);
+            let dispatch = config.route(&argv).unwrap().unwrap();
+            assert_eq!(dispatch.name, "test-*");
+            assert!(dispatch.config(&base).is_err(), "{}", subcommand);
+        }
+
+        // Routes without the placeholder never see the subcommand
+        let argv = args(&["../other"]);
+        let dispatch = config.route(&argv).unwrap().unwrap();
+        assert_eq!(dispatch.config(&base).unwrap().shim.path, "tool-plugin");
+    }
+}
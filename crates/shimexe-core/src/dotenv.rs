@@ -0,0 +1,468 @@
+//! Dotenv file support for the `env_files` shim setting.
+//!
+//! The parser understands the common dotenv dialect:
+//! - `KEY=value` lines, optionally prefixed with `export`
+//! - `#` comments, both on their own line and after unquoted values
+//! - single-quoted values, taken literally
+//! - double-quoted values with `\n`, `\r`, `\t`, `\\`, `\"` and `\$` escapes
+//! - `${VAR}`, `${VAR:-default}` and `$VAR` interpolation in unquoted and
+//!   double-quoted values
+//! - quoted values spanning multiple lines
+
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::env;
+use std::path::{Path, PathBuf};
+use tracing::debug;
+
+use crate::error::{Result, ShimError};
+use crate::utils::expand_env_vars;
+
+/// A dotenv file referenced from `env_files`
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum EnvFile {
+    /// Plain path; the file must exist
+    Path(String),
+    /// Path with loading options
+    Detailed {
+        /// Path to the dotenv file (supports environment variable expansion)
+        path: String,
+        /// Silently skip the file when it does not exist
+        #[serde(default)]
+        optional: bool,
+        /// Base directory for relative paths
+        #[serde(default)]
+        relative_to: RelativeTo,
+    },
+}
+
+/// Base directory used to resolve relative `env_files` paths
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum RelativeTo {
+    /// Directory containing the shim file (current directory if there is none)
+    #[default]
+    Shim,
+    /// Current working directory of the shim invocation
+    Cwd,
+}
+
+impl EnvFile {
+    /// Get the configured path
+    pub fn path(&self) -> &str {
+        match self {
+            EnvFile::Path(path) => path,
+            EnvFile::Detailed { path, .. } => path,
+        }
+    }
+
+    /// Whether a missing file should be ignored
+    pub fn is_optional(&self) -> bool {
+        match self {
+            EnvFile::Path(_) => false,
+            EnvFile::Detailed { optional, .. } => *optional,
+        }
+    }
+
+    /// Base directory for a relative path
+    pub fn relative_to(&self) -> RelativeTo {
+        match self {
+            EnvFile::Path(_) => RelativeTo::Shim,
+            EnvFile::Detailed { relative_to, .. } => *relative_to,
+        }
+    }
+
+    /// Resolve the file location, expanding environment variables
+    pub fn resolve(&self, shim_dir: Option<&Path>) -> Result<PathBuf> {
+        let path = PathBuf::from(expand_env_vars(self.path())?);
+        if path.is_absolute() {
+            return Ok(path);
+        }
+
+        let base = match (self.relative_to(), shim_dir) {
+            (RelativeTo::Shim, Some(dir)) => dir.to_path_buf(),
+            _ => env::current_dir().map_err(ShimError::Io)?,
+        };
+        Ok(base.join(path))
+    }
+}
+
+/// Load all `env_files` in order, later files overriding earlier ones
+///
+/// Relative paths are resolved against `shim_dir` (or the current directory, see
+/// [`RelativeTo`]). Interpolation sees variables defined by earlier files first
+/// and falls back to the process environment.
+pub fn load_env_files(
+    files: &[EnvFile],
+    shim_dir: Option<&Path>,
+) -> Result<HashMap<String, String>> {
+    let mut vars = HashMap::new();
+
+    for file in files {
+        let path = file.resolve(shim_dir)?;
+        let content = match std::fs::read_to_string(&path) {
+            Ok(content) => content,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound && file.is_optional() => {
+                debug!("Skipping missing optional env file: {}", path.display());
+                continue;
+            }
+            Err(e) => {
+                return Err(ShimError::Config(format!(
+                    "Failed to read env file {}: {}",
+                    path.display(),
+                    e
+                )))
+            }
+        };
+
+        let parsed = parse_dotenv(&content, &vars)
+            .map_err(|e| ShimError::Config(format!("{}: {}", path.display(), e)))?;
+        debug!("Loaded {} variables from {}", parsed.len(), path.display());
+        vars.extend(parsed);
+    }
+
+    Ok(vars)
+}
+
+/// Parse dotenv content into ordered `(key, value)` pairs
+///
+/// `defined` holds variables visible to interpolation in addition to the ones
+/// defined earlier in `content` and the process environment.
+pub fn parse_dotenv(
+    content: &str,
+    defined: &HashMap<String, String>,
+) -> Result<Vec<(String, String)>> {
+    let mut parser = Parser {
+        chars: content.chars().collect(),
+        pos: 0,
+        line: 1,
+    };
+    let mut local: HashMap<String, String> = HashMap::new();
+    let mut result = Vec::new();
+
+    loop {
+        parser.skip_blank_and_comments();
+        if parser.at_end() {
+            break;
+        }
+
+        let line = parser.line;
+        let key = parser.parse_key()?;
+        parser.skip_inline_space();
+        if parser.peek() != Some('=') {
+            return Err(ShimError::Config(format!(
+                "line {}: expected '=' after '{}'",
+                line, key
+            )));
+        }
+        parser.pos += 1;
+        parser.skip_inline_space();
+
+        let lookup = |name: &str| {
+            local
+                .get(name)
+                .or_else(|| defined.get(name))
+                .cloned()
+                .or_else(|| env::var(name).ok())
+        };
+        let value = parser.parse_value(&lookup)?;
+
+        local.insert(key.clone(), value.clone());
+        result.push((key, value));
+    }
+
+    Ok(result)
+}
+
+struct Parser {
+    chars: Vec<char>,
+    pos: usize,
+    line: usize,
+}
+
+impl Parser {
+    fn at_end(&self) -> bool {
+        self.pos >= self.chars.len()
+    }
+
+    fn peek(&self) -> Option<char> {
+        self.chars.get(self.pos).copied()
+    }
+
+    fn bump(&mut self) -> Option<char> {
+        let c = self.peek()?;
+        self.pos += 1;
+        if c == '\n' {
+            self.line += 1;
+        }
+        Some(c)
+    }
+
+    fn error(&self, message: &str) -> ShimError {
+        ShimError::Config(format!("line {}: {}", self.line, message))
+    }
+
+    fn skip_inline_space(&mut self) {
+        while matches!(self.peek(), Some(' ') | Some('\t')) {
+            self.pos += 1;
+        }
+    }
+
+    fn skip_to_line_end(&mut self) {
+        while let Some(c) = self.peek() {
+            if c == '\n' {
+                break;
+            }
+            self.pos += 1;
+        }
+    }
+
+    fn skip_blank_and_comments(&mut self) {
+        loop {
+            match self.peek() {
+                Some(c) if c.is_whitespace() => {
+                    self.bump();
+                }
+                Some('#') => self.skip_to_line_end(),
+                _ => break,
+            }
+        }
+    }
+
+    fn parse_key(&mut self) -> Result<String> {
+        let mut key = self.read_identifier();
+        if key == "export" && matches!(self.peek(), Some(' ') | Some('\t')) {
+            self.skip_inline_space();
+            key = self.read_identifier();
+        }
+
+        let valid_start = key
+            .chars()
+            .next()
+            .map(|c| c.is_ascii_alphabetic() || c == '_')
+            .unwrap_or(false);
+        if !valid_start {
+            return Err(self.error("expected a variable name"));
+        }
+        Ok(key)
+    }
+
+    fn read_identifier(&mut self) -> String {
+        let mut ident = String::new();
+        while let Some(c) = self.peek() {
+            if c.is_ascii_alphanumeric() || c == '_' || c == '.' {
+                ident.push(c);
+                self.pos += 1;
+            } else {
+                break;
+            }
+        }
+        ident
+    }
+
+    fn parse_value(&mut self, lookup: &dyn Fn(&str) -> Option<String>) -> Result<String> {
+        let value = match self.peek() {
+            Some('\'') => {
+                self.pos += 1;
+                self.parse_single_quoted()?
+            }
+            Some('"') => {
+                self.pos += 1;
+                self.parse_double_quoted(lookup)?
+            }
+            _ => return Ok(self.parse_unquoted(lookup)),
+        };
+
+        // Only whitespace or a comment may follow a closing quote
+        self.skip_inline_space();
+        match self.peek() {
+            None | Some('\n') | Some('\r') | Some('#') => {
+                self.skip_to_line_end();
+                Ok(value)
+            }
+            Some(_) => Err(self.error("unexpected characters after closing quote")),
+        }
+    }
+
+    fn parse_single_quoted(&mut self) -> Result<String> {
+        let mut value = String::new();
+        loop {
+            match self.bump() {
+                Some('\'') => return Ok(value),
+                Some(c) => value.push(c),
+                None => return Err(self.error("unterminated single-quoted value")),
+            }
+        }
+    }
+
+    fn parse_double_quoted(&mut self, lookup: &dyn Fn(&str) -> Option<String>) -> Result<String> {
+        let mut value = String::new();
+        loop {
+            match self.bump() {
+                Some('"') => return Ok(value),
+                Some('\\') => match self.bump() {
+                    Some('n') => value.push('\n'),
+                    Some('r') => value.push('\r'),
+                    Some('t') => value.push('\t'),
+                    Some('\\') => value.push('\\'),
+                    Some('"') => value.push('"'),
+                    Some('$') => value.push('$'),
+                    Some(other) => {
+                        value.push('\\');
+                        value.push(other);
+                    }
+                    None => return Err(self.error("unterminated double-quoted value")),
+                },
+                Some('$') => self.interpolate(&mut value, lookup)?,
+                Some(c) => value.push(c),
+                None => return Err(self.error("unterminated double-quoted value")),
+            }
+        }
+    }
+
+    fn parse_unquoted(&mut self, lookup: &dyn Fn(&str) -> Option<String>) -> String {
+        let mut value = String::new();
+        while let Some(c) = self.peek() {
+            match c {
+                '\n' => break,
+                // Leading whitespace is already skipped, so `FOO= # note` is empty
+                '#' if value.is_empty() || value.ends_with([' ', '\t']) => {
+                    self.skip_to_line_end();
+                    break;
+                }
+                '$' => {
+                    self.pos += 1;
+                    // Unquoted values never span lines, so a bad reference is kept literally
+                    if self.interpolate(&mut value, lookup).is_err() {
+                        value.push('$');
+                    }
+                }
+                _ => {
+                    value.push(c);
+                    self.pos += 1;
+                }
+            }
+        }
+        value.trim().to_string()
+    }
+
+    /// Expand a variable reference; the leading `$` has already been consumed
+    fn interpolate(
+        &mut self,
+        value: &mut String,
+        lookup: &dyn Fn(&str) -> Option<String>,
+    ) -> Result<()> {
+        if self.peek() == Some('{') {
+            let start = self.pos;
+            self.pos += 1;
+            let mut expr = String::new();
+            loop {
+                match self.peek() {
+                    Some('}') => {
+                        self.pos += 1;
+                        break;
+                    }
+                    Some('\n') | None => {
+                        self.pos = start;
+                        return Err(self.error("unterminated '${'"));
+                    }
+                    Some(c) => {
+                        expr.push(c);
+                        self.pos += 1;
+                    }
+                }
+            }
+
+            let (name, default) = match expr.split_once(":-") {
+                Some((name, default)) => (name, Some(default)),
+                None => (expr.as_str(), None),
+            };
+            match lookup(name).filter(|v| !v.is_empty()) {
+                Some(resolved) => value.push_str(&resolved),
+                None => value.push_str(default.unwrap_or("")),
+            }
+            return Ok(());
+        }
+
+        let name = self.read_identifier();
+        if name.is_empty() {
+            value.push('$');
+        } else {
+            value.push_str(&lookup(&name).unwrap_or_default());
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(content: &str) -> Vec<(String, String)> {
+        parse_dotenv(content, &HashMap::new()).unwrap()
+    }
+
+    fn pair(key: &str, value: &str) -> (String, String) {
+        (key.to_string(), value.to_string())
+    }
+
+    #[test]
+    fn test_parse_basic_and_comments() {
+        let vars = parse(
+            "# leading comment\nFOO=bar\n\nexport BAZ = qux # trailing comment\nURL=http://x/#anchor\nEMPTY= # note\n",
+        );
+        assert_eq!(
+            vars,
+            vec![
+                pair("FOO", "bar"),
+                pair("BAZ", "qux"),
+                pair("URL", "http://x/#anchor"),
+                pair("EMPTY", "")
+            ]
+        );
+    }
+
+    #[test]
+    fn test_parse_quotes_and_escapes() {
+        let vars =
+            parse("A='lit $HOME \\n'\nB=\"line1\\nline2 \\\"q\\\" \\$X\"\nC=\"multi\nline\"\n");
+        assert_eq!(
+            vars,
+            vec![
+                pair("A", "lit $HOME \\n"),
+                pair("B", "line1\nline2 \"q\" $X"),
+                pair("C", "multi\nline")
+            ]
+        );
+    }
+
+    #[test]
+    fn test_parse_interpolation() {
+        let mut defined = HashMap::new();
+        defined.insert("BASE".to_string(), "/opt".to_string());
+
+        let vars = parse_dotenv(
+            "ROOT=${BASE}/tool\nBIN=\"$ROOT/bin\"\nLEVEL=${DOTENV_TEST_UNSET_VAR:-info}\nRAW='${ROOT}'\n",
+            &defined,
+        )
+        .unwrap();
+        assert_eq!(
+            vars,
+            vec![
+                pair("ROOT", "/opt/tool"),
+                pair("BIN", "/opt/tool/bin"),
+                pair("LEVEL", "info"),
+                pair("RAW", "${ROOT}")
+            ]
+        );
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        assert!(parse_dotenv("NOEQUALS\n", &HashMap::new()).is_err());
+        assert!(parse_dotenv("A=\"unterminated\n", &HashMap::new()).is_err());
+        assert!(parse_dotenv("A='x' trailing\n", &HashMap::new()).is_err());
+        assert!(parse_dotenv("1BAD=x\n", &HashMap::new()).is_err());
+    }
+}
@@ -0,0 +1,458 @@
+//! Environment computed by an activation script or command (`[env_from]`).
+//!
+//! A script such as a Python venv `activate` is sourced in a shell and the
+//! resulting environment is compared with the one before sourcing. A command
+//! can either be run the same way (`capture = "diff"`) or print `KEY=VALUE`
+//! lines on stdout (`capture = "output"`). The result is cached on disk and
+//! reused until the script changes or the TTL expires.
+
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+use std::time::{SystemTime, UNIX_EPOCH};
+use tracing::{debug, warn};
+
+use crate::dotenv::parse_dotenv;
+use crate::error::{Result, ShimError};
+use crate::process::{default_shell, shell_command};
+use crate::utils::expand_env_vars;
+
+/// Name of the cache file inside the shim data directory
+const CACHE_FILE: &str = "env_from.json";
+
+/// Marker separating the environment dumps taken before and after the script
+const MARKER: &str = "__SHIMEXE_ENV_MARKER__";
+
+/// Variables that shells change on their own and that never belong in a diff
+const IGNORED_VARS: &[&str] = &["_", "SHLVL", "PWD", "OLDPWD"];
+
+/// Environment produced by a script or command
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct EnvFrom {
+    /// Script to source (supports environment variable expansion)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub script: Option<String>,
+    /// Shell command to run instead of sourcing a script
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub command: Option<String>,
+    /// How the environment is captured
+    #[serde(default)]
+    pub capture: EnvCapture,
+    /// Shell used to run the script or command (defaults to `sh`, or `cmd` on Windows)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub shell: Option<String>,
+    /// How long a cached result stays valid, in seconds (0 = never cache)
+    #[serde(default = "default_ttl_secs")]
+    pub ttl_secs: u64,
+}
+
+/// Capture mode for `[env_from]`
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum EnvCapture {
+    /// Diff the environment before and after running the script or command
+    #[default]
+    Diff,
+    /// Parse `KEY=VALUE` lines printed on stdout
+    Output,
+}
+
+/// Environment changes to apply to the target process
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct EnvDiff {
+    /// Variables that were added or changed
+    pub set: BTreeMap<String, String>,
+    /// Variables that were removed
+    pub unset: Vec<String>,
+}
+
+impl EnvDiff {
+    /// Set a variable, cancelling an earlier removal
+    pub fn insert(&mut self, key: impl Into<String>, value: impl Into<String>) {
+        let key = key.into();
+        self.unset.retain(|k| *k != key);
+        self.set.insert(key, value.into());
+    }
+
+    /// Remove a variable, dropping an earlier value
+    pub fn remove(&mut self, key: &str) {
+        self.set.remove(key);
+        if !self.unset.iter().any(|k| k == key) {
+            self.unset.push(key.to_string());
+        }
+    }
+
+    /// Layer `other` on top of this diff
+    pub fn extend(&mut self, other: EnvDiff) {
+        for key in &other.unset {
+            self.remove(key);
+        }
+        for (key, value) in other.set {
+            self.insert(key, value);
+        }
+    }
+
+    /// Apply the changes to a command
+    pub fn apply(&self, cmd: &mut Command) {
+        for key in &self.unset {
+            cmd.env_remove(key);
+        }
+        cmd.envs(&self.set);
+    }
+}
+
+/// Script or command an `[env_from]` result is computed from
+struct Source {
+    script: Option<PathBuf>,
+    /// Script path or command, as stored in the cache
+    text: String,
+    shell: String,
+    script_mtime: Option<u128>,
+}
+
+/// On-disk cache entry
+#[derive(Debug, Serialize, Deserialize)]
+struct CacheEntry {
+    source: String,
+    shell: String,
+    script_mtime: Option<u128>,
+    created_at: u64,
+    diff: EnvDiff,
+}
+
+fn default_ttl_secs() -> u64 {
+    3600
+}
+
+impl EnvFrom {
+    /// Validate the section
+    pub fn validate(&self) -> Result<()> {
+        match (&self.script, &self.command) {
+            (Some(_), Some(_)) => Err(ShimError::Config(
+                "[env_from] accepts either 'script' or 'command', not both".to_string(),
+            )),
+            (None, None) => Err(ShimError::Config(
+                "[env_from] requires 'script' or 'command'".to_string(),
+            )),
+            _ => Ok(()),
+        }
+    }
+
+    /// Compute the environment diff, using the cache in `cache_dir` when possible
+    pub fn resolve(&self, shim_dir: Option<&Path>, cache_dir: Option<&Path>) -> Result<EnvDiff> {
+        let source = self.source(shim_dir)?;
+        let cache_file = self.cache_file(cache_dir);
+        if let Some(diff) = self.read_cache(cache_file.as_deref(), &source) {
+            return Ok(diff);
+        }
+
+        let diff = self.run(&source.shell, source.script.as_deref())?;
+
+        if let Some(ref cache_file) = cache_file {
+            let entry = CacheEntry {
+                source: source.text,
+                shell: source.shell,
+                script_mtime: source.script_mtime,
+                created_at: now_secs(),
+                diff: diff.clone(),
+            };
+            if let Err(e) = write_cache(cache_file, &entry) {
+                warn!("Failed to cache [env_from] result: {}", e);
+            }
+        }
+
+        Ok(diff)
+    }
+
+    /// The cached environment diff, without running the script or command
+    ///
+    /// Returns `None` when there is no valid cached result.
+    pub fn cached(
+        &self,
+        shim_dir: Option<&Path>,
+        cache_dir: Option<&Path>,
+    ) -> Result<Option<EnvDiff>> {
+        let source = self.source(shim_dir)?;
+        Ok(self.read_cache(self.cache_file(cache_dir).as_deref(), &source))
+    }
+
+    /// Shell command line that computing the environment runs
+    pub fn command_line(&self, shim_dir: Option<&Path>) -> Result<String> {
+        let source = self.source(shim_dir)?;
+        let body = match source.script {
+            Some(ref path) => source_statement(path),
+            None => source.text,
+        };
+        Ok(format!("{}: {}", source.shell, body))
+    }
+
+    /// What the environment is computed from
+    fn source(&self, shim_dir: Option<&Path>) -> Result<Source> {
+        self.validate()?;
+
+        let script = self.script_path(shim_dir)?;
+        let text = match (&script, &self.command) {
+            (Some(path), _) => path.to_string_lossy().to_string(),
+            (None, Some(command)) => command.clone(),
+            (None, None) => unreachable!("validated above"),
+        };
+        let script_mtime = match script {
+            Some(ref path) => Some(modified_nanos(path)?),
+            None => None,
+        };
+
+        Ok(Source {
+            script,
+            text,
+            shell: self.shell_program(),
+            script_mtime,
+        })
+    }
+
+    fn cache_file(&self, cache_dir: Option<&Path>) -> Option<PathBuf> {
+        cache_dir
+            .filter(|_| self.ttl_secs > 0)
+            .map(|dir| dir.join(CACHE_FILE))
+    }
+
+    /// Resolve the script path relative to the shim directory
+    fn script_path(&self, shim_dir: Option<&Path>) -> Result<Option<PathBuf>> {
+        let Some(ref script) = self.script else {
+            return Ok(None);
+        };
+
+        let path = PathBuf::from(expand_env_vars(script)?);
+        let path = match shim_dir {
+            Some(dir) if path.is_relative() => dir.join(path),
+            _ => path,
+        };
+        Ok(Some(path))
+    }
+
+    fn shell_program(&self) -> String {
+        self.shell
+            .clone()
+            .unwrap_or_else(|| default_shell().to_string())
+    }
+
+    fn read_cache(&self, cache_file: Option<&Path>, source: &Source) -> Option<EnvDiff> {
+        let cache_file = cache_file?;
+        let content = std::fs::read_to_string(cache_file).ok()?;
+        let entry: CacheEntry = serde_json::from_str(&content).ok()?;
+
+        let fresh = now_secs().saturating_sub(entry.created_at) < self.ttl_secs;
+        if fresh
+            && entry.source == source.text
+            && entry.shell == source.shell
+            && entry.script_mtime == source.script_mtime
+        {
+            debug!(
+                "Using cached [env_from] result from {}",
+                cache_file.display()
+            );
+            Some(entry.diff)
+        } else {
+            None
+        }
+    }
+
+    /// Run the script or command and capture its environment
+    fn run(&self, shell: &str, script: Option<&Path>) -> Result<EnvDiff> {
+        let body = match (script, &self.command) {
+            (Some(path), _) => source_statement(path),
+            (None, Some(command)) => command.clone(),
+            (None, None) => unreachable!("validated before running"),
+        };
+
+        debug!("Computing [env_from] environment with {}: {}", shell, body);
+
+        let output = match self.capture {
+            EnvCapture::Diff => shell_command(shell, &diff_script(&body)),
+            EnvCapture::Output => shell_command(shell, &body),
+        }
+        .stdin(Stdio::null())
+        .output()
+        .map_err(|e| {
+            ShimError::ProcessExecution(format!(
+                "Failed to run [env_from] shell '{}': {}",
+                shell, e
+            ))
+        })?;
+
+        if !output.status.success() {
+            return Err(ShimError::ProcessExecution(format!(
+                "[env_from] '{}' failed with {}: {}",
+                body,
+                output.status,
+                String::from_utf8_lossy(&output.stderr).trim()
+            )));
+        }
+
+        let stdout = String::from_utf8_lossy(&output.stdout);
+        match self.capture {
+            EnvCapture::Diff => parse_env_dumps(&stdout),
+            EnvCapture::Output => {
+                let set = parse_dotenv(&stdout, &HashMap::new())?
+                    .into_iter()
+                    .collect();
+                Ok(EnvDiff {
+                    set,
+                    unset: Vec::new(),
+                })
+            }
+        }
+    }
+}
+
+/// Statement that sources `path` in the target shell
+fn source_statement(path: &Path) -> String {
+    if cfg!(windows) {
+        format!("call \"{}\"", path.display())
+    } else {
+        format!(". '{}'", path.display().to_string().replace('\'', "'\\''"))
+    }
+}
+
+/// Wrap `body` so the environment is dumped before and after running it
+fn diff_script(body: &str) -> String {
+    if cfg!(windows) {
+        format!("set & echo {MARKER} & {body} >nul & set")
+    } else {
+        format!("env -0; printf '{MARKER}\\0'; {{ {body}\n}} >&2; env -0")
+    }
+}
+
+/// Parse the output of [`diff_script`] into an [`EnvDiff`]
+fn parse_env_dumps(output: &str) -> Result<EnvDiff> {
+    let separator = if cfg!(windows) { '\n' } else { '\0' };
+    let mut before = HashMap::new();
+    let mut after = HashMap::new();
+    let mut seen_marker = false;
+
+    for entry in output.split(separator) {
+        let entry = entry.trim_end_matches('\r');
+        if entry.trim() == MARKER {
+            seen_marker = true;
+            continue;
+        }
+        if let Some((key, value)) = entry.split_once('=') {
+            if key.is_empty() || IGNORED_VARS.contains(&key) {
+                continue;
+            }
+            let target = if seen_marker { &mut after } else { &mut before };
+            target.insert(key.to_string(), value.to_string());
+        }
+    }
+
+    if !seen_marker {
+        return Err(ShimError::ProcessExecution(
+            "[env_from] produced no environment dump".to_string(),
+        ));
+    }
+
+    let set = after
+        .iter()
+        .filter(|(key, value)| before.get(*key) != Some(*value))
+        .map(|(key, value)| (key.clone(), value.clone()))
+        .collect();
+    let mut unset: Vec<String> = before
+        .keys()
+        .filter(|key| !after.contains_key(*key))
+        .cloned()
+        .collect();
+    unset.sort();
+
+    Ok(EnvDiff { set, unset })
+}
+
+fn write_cache(cache_file: &Path, entry: &CacheEntry) -> Result<()> {
+    if let Some(parent) = cache_file.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    let content = serde_json::to_string(entry)
+        .map_err(|e| ShimError::Config(format!("Failed to serialize cache: {}", e)))?;
+    std::fs::write(cache_file, content)?;
+    Ok(())
+}
+
+fn modified_nanos(path: &Path) -> Result<u128> {
+    let modified = std::fs::metadata(path)
+        .and_then(|m| m.modified())
+        .map_err(|e| {
+            ShimError::Config(format!(
+                "[env_from] script {} is not accessible: {}",
+                path.display(),
+                e
+            ))
+        })?;
+    Ok(modified
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_nanos())
+        .unwrap_or_default())
+}
+
+fn now_secs() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_validate_requires_exactly_one_source() {
+        let mut env_from = EnvFrom {
+            script: None,
+            command: None,
+            capture: EnvCapture::Diff,
+            shell: None,
+            ttl_secs: 0,
+        };
+        assert!(env_from.validate().is_err());
+
+        env_from.script = Some("activate".to_string());
+        assert!(env_from.validate().is_ok());
+
+        env_from.command = Some("true".to_string());
+        assert!(env_from.validate().is_err());
+    }
+
+    #[test]
+    fn test_parse_env_dumps() {
+        let sep = if cfg!(windows) { "\n" } else { "\0" };
+        let output = [
+            "KEEP=same",
+            "CHANGED=old",
+            "GONE=x",
+            "_=/bin/sh",
+            MARKER,
+            "KEEP=same",
+            "CHANGED=new",
+            "ADDED=multi\nline",
+            "_=/usr/bin/env",
+            "",
+        ]
+        .join(sep);
+
+        let diff = parse_env_dumps(&output).unwrap();
+        assert_eq!(diff.set.get("CHANGED").map(String::as_str), Some("new"));
+        assert!(!diff.set.contains_key("KEEP"));
+        assert!(!diff.set.contains_key("_"));
+        assert_eq!(diff.unset, vec!["GONE".to_string()]);
+        if !cfg!(windows) {
+            assert_eq!(
+                diff.set.get("ADDED").map(String::as_str),
+                Some("multi\nline")
+            );
+        }
+    }
+
+    #[test]
+    fn test_parse_env_dumps_without_marker() {
+        assert!(parse_env_dumps("A=1").is_err());
+    }
+}
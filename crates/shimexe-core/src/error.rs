@@ -39,6 +39,15 @@ pub enum ShimError {
     #[error("Template processing error: {0}")]
     TemplateError(String),
 
+    #[error("Hook failed: {0}")]
+    HookFailed(String),
+
+    #[error("Instance limit reached: {0}")]
+    InstanceLimit(String),
+
+    #[error("Untrusted configuration: {0}")]
+    Untrusted(String),
+
     #[error("Download error: {0}")]
     Download(#[from] anyhow::Error),
 }
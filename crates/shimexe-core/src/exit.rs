@@ -0,0 +1,209 @@
+//! Exit status normalization (`[exit]`).
+//!
+//! Some tools exit 0 on failure or use unusual exit codes. `map` rewrites the
+//! target's exit code, and `fail_on_stderr_regex` / `fail_on_stdout_regex`
+//! turn a successful run into a failure when a line of output matches.
+
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::sync::atomic::{AtomicBool, Ordering};
+use tracing::debug;
+
+use crate::error::{Result, ShimError};
+use crate::logging::Stream;
+
+/// Exit status rules for the target executable
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ExitConfig {
+    /// Exit code rewrites, e.g. `{ 3 = 0 }`
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub map: BTreeMap<String, i32>,
+    /// Fail when a line written to stderr matches
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub fail_on_stderr_regex: Option<String>,
+    /// Fail when a line written to stdout matches
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub fail_on_stdout_regex: Option<String>,
+    /// Exit code used when an output pattern turns a success into a failure
+    #[serde(default = "default_failure_code")]
+    pub failure_code: i32,
+}
+
+fn default_failure_code() -> i32 {
+    1
+}
+
+impl Default for ExitConfig {
+    fn default() -> Self {
+        Self {
+            map: BTreeMap::new(),
+            fail_on_stderr_regex: None,
+            fail_on_stdout_regex: None,
+            failure_code: default_failure_code(),
+        }
+    }
+}
+
+impl ExitConfig {
+    /// Validate the exit code map and output patterns
+    pub fn validate(&self) -> Result<()> {
+        for key in self.map.keys() {
+            if key.trim().parse::<i32>().is_err() {
+                return Err(ShimError::Config(format!(
+                    "exit.map key '{}' is not an exit code",
+                    key
+                )));
+            }
+        }
+        if self.failure_code == 0 {
+            return Err(ShimError::Config(
+                "exit.failure_code must not be 0".to_string(),
+            ));
+        }
+        self.watch().map(|_| ())
+    }
+
+    /// Output watch for the configured patterns, `None` when there are none
+    pub fn watch(&self) -> Result<Option<OutputWatch>> {
+        let compile = |pattern: &Option<String>, field: &str| {
+            pattern
+                .as_deref()
+                .map(|p| {
+                    Regex::new(p).map_err(|e| {
+                        ShimError::Config(format!("Invalid exit.{} '{}': {}", field, p, e))
+                    })
+                })
+                .transpose()
+        };
+        let stdout = compile(&self.fail_on_stdout_regex, "fail_on_stdout_regex")?;
+        let stderr = compile(&self.fail_on_stderr_regex, "fail_on_stderr_regex")?;
+
+        if stdout.is_none() && stderr.is_none() {
+            return Ok(None);
+        }
+        Ok(Some(OutputWatch {
+            stdout,
+            stderr,
+            matched: AtomicBool::new(false),
+        }))
+    }
+
+    /// Exit code reported for a target that exited with `code`
+    ///
+    /// `output_matched` is whether a failure pattern matched the run's output.
+    pub fn normalize(&self, code: i32, output_matched: bool) -> i32 {
+        let mapped = self
+            .map
+            .iter()
+            .find(|(from, _)| from.trim().parse() == Ok(code))
+            .map_or(code, |(_, to)| *to);
+        if mapped != code {
+            debug!("Mapped exit code {} to {}", code, mapped);
+        }
+
+        if output_matched && mapped == 0 {
+            debug!(
+                "Output matched a failure pattern, exiting with {}",
+                self.failure_code
+            );
+            return self.failure_code;
+        }
+        mapped
+    }
+}
+
+/// Scans output lines for the failure patterns of an [`ExitConfig`]
+#[derive(Debug)]
+pub struct OutputWatch {
+    stdout: Option<Regex>,
+    stderr: Option<Regex>,
+    matched: AtomicBool,
+}
+
+impl OutputWatch {
+    /// Streams that need to be captured
+    pub fn streams(&self) -> Vec<Stream> {
+        let mut streams = Vec::new();
+        if self.stdout.is_some() {
+            streams.push(Stream::Stdout);
+        }
+        if self.stderr.is_some() {
+            streams.push(Stream::Stderr);
+        }
+        streams
+    }
+
+    /// Check one line of output from `stream`
+    pub fn check(&self, stream: Stream, line: &[u8]) {
+        let pattern = match stream {
+            Stream::Stdout => &self.stdout,
+            Stream::Stderr => &self.stderr,
+        };
+        let Some(pattern) = pattern else {
+            return;
+        };
+        let line = String::from_utf8_lossy(line);
+        if pattern.is_match(line.trim_end_matches(['\r', '\n'])) {
+            self.matched.store(true, Ordering::Relaxed);
+        }
+    }
+
+    /// Whether a pattern matched since the last call, resetting the flag
+    pub fn take_matched(&self) -> bool {
+        self.matched.swap(false, Ordering::Relaxed)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn config(toml_str: &str) -> ExitConfig {
+        let config: ExitConfig = toml::from_str(toml_str).unwrap();
+        config.validate().unwrap();
+        config
+    }
+
+    #[test]
+    fn test_exit_code_map() {
+        let config = config("map = { 3 = 0, 0 = 2 }");
+        assert_eq!(config.normalize(3, false), 0);
+        assert_eq!(config.normalize(0, false), 2);
+        assert_eq!(config.normalize(1, false), 1);
+    }
+
+    #[test]
+    fn test_output_match_fails_success() {
+        let config = config("map = { 3 = 0 }\nfail_on_stderr_regex = '^ERROR'");
+        let watch = config.watch().unwrap().unwrap();
+        assert_eq!(watch.streams(), vec![Stream::Stderr]);
+
+        watch.check(Stream::Stdout, b"ERROR on stdout\n");
+        watch.check(Stream::Stderr, b"warning: fine\n");
+        assert!(!watch.take_matched());
+
+        watch.check(Stream::Stderr, b"ERROR: license expired\n");
+        assert!(watch.take_matched());
+        assert!(!watch.take_matched());
+
+        assert_eq!(config.normalize(3, true), 1);
+        assert_eq!(config.normalize(7, true), 7);
+    }
+
+    #[test]
+    fn test_default_matches_serde_default() {
+        let default = ExitConfig::default();
+        assert_eq!(default, config(""));
+        default.validate().unwrap();
+    }
+
+    #[test]
+    fn test_validate_rejects_bad_config() {
+        let bad_key: ExitConfig = toml::from_str("map = { x = 0 }").unwrap();
+        assert!(bad_key.validate().is_err());
+
+        let bad_regex: ExitConfig = toml::from_str("fail_on_stdout_regex = '('").unwrap();
+        assert!(bad_regex.validate().is_err());
+    }
+}
@@ -0,0 +1,272 @@
+//! Dry-run reports describing what a shim would execute.
+//!
+//! Set `SHIMEXE_EXPLAIN=1` (or `json`) when invoking a shim, or use
+//! `shimexe run --dry-run`, to print the resolved invocation instead of running it.
+
+use serde::Serialize;
+use std::fmt;
+use std::path::PathBuf;
+
+use crate::env_from::EnvDiff;
+use crate::limits::LimitsConfig;
+use crate::process::TimeoutConfig;
+
+/// Environment variable enabling explain mode (`1`/`text` or `json`)
+pub const EXPLAIN_ENV_VAR: &str = "SHIMEXE_EXPLAIN";
+
+/// Output format of an explanation
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ExplainFormat {
+    Text,
+    Json,
+}
+
+impl ExplainFormat {
+    /// Format requested through `SHIMEXE_EXPLAIN`, if any
+    pub fn from_env() -> Option<Self> {
+        let value = std::env::var(EXPLAIN_ENV_VAR).ok()?;
+        match value.trim().to_ascii_lowercase().as_str() {
+            "" | "0" | "false" | "off" => None,
+            "json" => Some(ExplainFormat::Json),
+            _ => Some(ExplainFormat::Text),
+        }
+    }
+}
+
+/// Fully resolved invocation of a shim
+#[derive(Debug, Clone, Serialize)]
+pub struct Explanation {
+    /// Name of the shim
+    pub shim: String,
+    /// Configuration file the shim was loaded from
+    pub config_file: Option<PathBuf>,
+    /// Why that configuration file was chosen
+    pub config_origin: Option<String>,
+    /// Trusted project file whose overrides were applied
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub project_file: Option<PathBuf>,
+    /// Selected version of a multi-version shim and what selected it
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub version: Option<String>,
+    /// Profile applied to the invocation and what selected it
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub profile: Option<String>,
+    /// Resolved target executable, if it could be resolved
+    pub executable: Option<PathBuf>,
+    /// Interpreter command the executable is run with, for scripts
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub interpreter: Option<Vec<String>>,
+    /// How the executable was (or will be) found
+    pub resolution: String,
+    /// Final arguments passed to the target
+    pub argv: Vec<String>,
+    /// Environment changes relative to the current process
+    pub env: Vec<EnvChange>,
+    /// Working directory of the target
+    pub cwd: PathBuf,
+    /// Effective execution timeout
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub timeout: Option<TimeoutConfig>,
+    /// Resource limits applied to the target
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub limits: Option<LimitsConfig>,
+    /// Work that would happen before the target starts (downloads, updates)
+    pub pending: Vec<String>,
+}
+
+/// A single environment change
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct EnvChange {
+    pub name: String,
+    /// New value, `None` when the variable is removed
+    pub value: Option<String>,
+    /// Value in the current process, if any
+    pub previous: Option<String>,
+}
+
+impl EnvChange {
+    /// Changes `diff` makes to the current process environment, skipping no-ops
+    pub fn from_diff(diff: &EnvDiff) -> Vec<EnvChange> {
+        Self::against(diff, |name| std::env::var(name).ok())
+    }
+
+    /// Changes `diff` makes to the environment seen through `current`
+    fn against(diff: &EnvDiff, current: impl Fn(&str) -> Option<String>) -> Vec<EnvChange> {
+        let mut changes: Vec<EnvChange> = diff
+            .set
+            .iter()
+            .filter_map(|(name, value)| {
+                let previous = current(name);
+                (previous.as_deref() != Some(value.as_str())).then(|| EnvChange {
+                    name: name.clone(),
+                    value: Some(value.clone()),
+                    previous,
+                })
+            })
+            .collect();
+
+        changes.extend(diff.unset.iter().filter_map(|name| {
+            current(name).map(|previous| EnvChange {
+                name: name.clone(),
+                value: None,
+                previous: Some(previous),
+            })
+        }));
+        changes
+    }
+}
+
+impl Explanation {
+    /// Render in the requested format
+    pub fn render(&self, format: ExplainFormat) -> String {
+        match format {
+            ExplainFormat::Text => self.to_string(),
+            ExplainFormat::Json => serde_json::to_string_pretty(self)
+                .unwrap_or_else(|e| format!("{{\"error\": \"{}\"}}", e)),
+        }
+    }
+}
+
+impl fmt::Display for Explanation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "Shim: {}", self.shim)?;
+        match self.config_file {
+            Some(ref file) => write!(f, "Config: {}", file.display())?,
+            None => write!(f, "Config: <in memory>")?,
+        }
+        match self.config_origin {
+            Some(ref origin) => writeln!(f, " ({})", origin)?,
+            None => writeln!(f)?,
+        }
+        if let Some(ref project_file) = self.project_file {
+            writeln!(f, "Project: {}", project_file.display())?;
+        }
+        if let Some(ref version) = self.version {
+            writeln!(f, "Version: {}", version)?;
+        }
+        if let Some(ref profile) = self.profile {
+            writeln!(f, "Profile: {}", profile)?;
+        }
+
+        match self.executable {
+            Some(ref exe) => writeln!(f, "Executable: {}", exe.display())?,
+            None => writeln!(f, "Executable: <unresolved>")?,
+        }
+        writeln!(f, "  resolved via: {}", self.resolution)?;
+        if let Some(ref interpreter) = self.interpreter {
+            writeln!(f, "Interpreter: {}", interpreter.join(" "))?;
+        }
+
+        writeln!(f, "Argv:")?;
+        if self.argv.is_empty() {
+            writeln!(f, "  (none)")?;
+        }
+        for (i, arg) in self.argv.iter().enumerate() {
+            writeln!(f, "  [{}] {}", i, arg)?;
+        }
+
+        writeln!(f, "Cwd: {}", self.cwd.display())?;
+
+        writeln!(f, "Environment changes:")?;
+        if self.env.is_empty() {
+            writeln!(f, "  (none)")?;
+        }
+        for change in &self.env {
+            match (&change.value, &change.previous) {
+                (Some(value), None) => writeln!(f, "  + {}={}", change.name, value)?,
+                (Some(value), Some(previous)) => {
+                    writeln!(f, "  ~ {}={} (was {})", change.name, value, previous)?
+                }
+                (None, _) => writeln!(f, "  - {}", change.name)?,
+            }
+        }
+
+        if let Some(ref timeout) = self.timeout {
+            writeln!(
+                f,
+                "Timeout: {}s ({:?}, {}s grace)",
+                timeout.secs, timeout.signal, timeout.grace_secs
+            )?;
+        }
+
+        if let Some(ref limits) = self.limits {
+            writeln!(f, "Limits: {}", limits)?;
+        }
+
+        if !self.pending.is_empty() {
+            writeln!(f, "Pending:")?;
+            for action in &self.pending {
+                writeln!(f, "  - {}", action)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+
+    #[test]
+    fn test_env_changes_skip_unchanged() {
+        let current: HashMap<&str, &str> = [
+            ("EXPLAIN_TEST_SAME", "1"),
+            ("EXPLAIN_TEST_CHANGED", "old"),
+            ("EXPLAIN_TEST_REMOVED", "x"),
+        ]
+        .into();
+
+        let mut diff = EnvDiff::default();
+        diff.insert("EXPLAIN_TEST_SAME", "1");
+        diff.insert("EXPLAIN_TEST_CHANGED", "new");
+        diff.insert("EXPLAIN_TEST_ADDED", "value");
+        diff.remove("EXPLAIN_TEST_REMOVED");
+        diff.remove("EXPLAIN_TEST_NEVER_SET");
+
+        let changes = EnvChange::against(&diff, |name| current.get(name).map(|v| v.to_string()));
+        let names: Vec<&str> = changes.iter().map(|c| c.name.as_str()).collect();
+        assert_eq!(
+            names,
+            vec![
+                "EXPLAIN_TEST_ADDED",
+                "EXPLAIN_TEST_CHANGED",
+                "EXPLAIN_TEST_REMOVED"
+            ]
+        );
+        assert_eq!(changes[1].previous.as_deref(), Some("old"));
+        assert_eq!(changes[2].value, None);
+    }
+
+    #[test]
+    fn test_text_rendering() {
+        let explanation = Explanation {
+            shim: "node".to_string(),
+            config_file: Some(PathBuf::from("/shims/node.shim.toml")),
+            config_origin: Some("next to the shim executable".to_string()),
+            project_file: None,
+            version: None,
+            profile: None,
+            executable: None,
+            interpreter: None,
+            resolution: "download from https://example.com/node".to_string(),
+            argv: vec!["--version".to_string()],
+            env: vec![],
+            cwd: PathBuf::from("/work"),
+            timeout: None,
+            limits: None,
+            pending: vec!["download https://example.com/node".to_string()],
+        };
+
+        let text = explanation.render(ExplainFormat::Text);
+        assert!(text.contains("Config: /shims/node.shim.toml (next to the shim executable)"));
+        assert!(text.contains("Executable: <unresolved>"));
+        assert!(text.contains("  [0] --version"));
+        assert!(text.contains("Pending:\n  - download"));
+
+        let json: serde_json::Value =
+            serde_json::from_str(&explanation.render(ExplainFormat::Json)).unwrap();
+        assert_eq!(json["argv"][0], "--version");
+        assert!(json["executable"].is_null());
+    }
+}
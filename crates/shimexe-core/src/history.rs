@@ -0,0 +1,397 @@
+//! Local execution history.
+//!
+//! Recording is opt-in: with `SHIMEXE_HISTORY=1`, or `[history] enabled = true`
+//! in a shim file, each invocation appends one JSON line to
+//! `~/.shimexe/history.jsonl` (or `SHIMEXE_HISTORY_FILE`). `SHIMEXE_NO_HISTORY=1`
+//! turns recording off regardless. The file is pruned by age and size as it grows.
+
+use chrono::{DateTime, Duration as ChronoDuration, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashSet};
+use std::ffi::OsString;
+use std::fs::{self, File, OpenOptions};
+use std::io::{BufRead, BufReader, Read, Seek, SeekFrom, Write};
+use std::path::{Path, PathBuf};
+use tracing::debug;
+
+use crate::error::{Result, ShimError};
+
+/// Environment variable enabling history recording for every shim
+pub const HISTORY_ENV_VAR: &str = "SHIMEXE_HISTORY";
+
+/// Environment variable disabling history recording, overriding everything else
+pub const NO_HISTORY_ENV_VAR: &str = "SHIMEXE_NO_HISTORY";
+
+/// Environment variable overriding the history file location
+pub const HISTORY_FILE_ENV_VAR: &str = "SHIMEXE_HISTORY_FILE";
+
+/// `[history]` section of a shim file
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct HistoryConfig {
+    /// Record invocations of this shim
+    #[serde(default)]
+    pub enabled: bool,
+}
+
+/// One recorded shim invocation
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct HistoryRecord {
+    pub timestamp: DateTime<Utc>,
+    pub shim: String,
+    pub executable: String,
+    pub argc: usize,
+    pub cwd: String,
+    pub duration_ms: u64,
+    /// Exit code of the target, `None` when it could not be started
+    pub exit_code: Option<i32>,
+}
+
+impl HistoryRecord {
+    /// Whether the invocation failed
+    pub fn is_failure(&self) -> bool {
+        self.exit_code != Some(0)
+    }
+}
+
+/// Append-only JSON-lines history file with retention limits
+#[derive(Debug, Clone)]
+pub struct History {
+    path: PathBuf,
+    max_age_days: u32,
+    max_bytes: u64,
+}
+
+impl History {
+    /// Default number of days records are kept
+    pub const DEFAULT_MAX_AGE_DAYS: u32 = 90;
+    /// Default size the history file may grow to before it is pruned
+    pub const DEFAULT_MAX_BYTES: u64 = 4 * 1024 * 1024;
+
+    /// Create a history stored at `path`
+    pub fn new<P: Into<PathBuf>>(path: P) -> Self {
+        Self {
+            path: path.into(),
+            max_age_days: Self::DEFAULT_MAX_AGE_DAYS,
+            max_bytes: Self::DEFAULT_MAX_BYTES,
+        }
+    }
+
+    /// History to record a shim's invocations in, or `None` unless recording is enabled
+    ///
+    /// Recording is enabled by `SHIMEXE_HISTORY` or by `config`, and
+    /// `SHIMEXE_NO_HISTORY` disables it for every shim.
+    pub fn from_env(config: Option<&HistoryConfig>) -> Option<Self> {
+        Self::from_vars(config, |name| std::env::var_os(name))
+    }
+
+    /// History at the configured location, whether or not recording is enabled
+    pub fn location() -> Option<Self> {
+        Self::location_from_vars(|name| std::env::var_os(name))
+    }
+
+    fn from_vars(
+        config: Option<&HistoryConfig>,
+        var: impl Fn(&str) -> Option<OsString>,
+    ) -> Option<Self> {
+        let is_set = |name| var(name).is_some_and(|v| !v.is_empty() && v != "0");
+        if is_set(NO_HISTORY_ENV_VAR) {
+            return None;
+        }
+        if !is_set(HISTORY_ENV_VAR) && !config.is_some_and(|config| config.enabled) {
+            return None;
+        }
+        Self::location_from_vars(var)
+    }
+
+    fn location_from_vars(var: impl Fn(&str) -> Option<OsString>) -> Option<Self> {
+        if let Some(path) = var(HISTORY_FILE_ENV_VAR) {
+            return Some(Self::new(path));
+        }
+
+        dirs::home_dir().map(|home| Self::new(home.join(".shimexe").join("history.jsonl")))
+    }
+
+    /// Keep records for at most `days` days
+    pub fn with_max_age_days(mut self, days: u32) -> Self {
+        self.max_age_days = days;
+        self
+    }
+
+    /// Prune the file once it grows beyond `bytes`
+    pub fn with_max_bytes(mut self, bytes: u64) -> Self {
+        self.max_bytes = bytes;
+        self
+    }
+
+    /// Path of the history file
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Append a record, pruning the file when it is over its size limit
+    pub fn append(&self, record: &HistoryRecord) -> Result<()> {
+        if let Some(parent) = self.path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+
+        let mut line = serde_json::to_vec(record)
+            .map_err(|e| ShimError::Config(format!("Failed to serialize history: {}", e)))?;
+        line.push(b'\n');
+
+        let mut file = OpenOptions::new()
+            .create(true)
+            .read(true)
+            .append(true)
+            .open(&self.path)?;
+        file.lock()?;
+        file.write_all(&line)?;
+
+        if file.metadata()?.len() > self.max_bytes {
+            self.prune_locked(&mut file)?;
+        }
+        Ok(())
+    }
+
+    /// Read all records within the retention window, oldest first
+    pub fn read(&self) -> Result<Vec<HistoryRecord>> {
+        let file = match File::open(&self.path) {
+            Ok(file) => file,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+            Err(e) => return Err(e.into()),
+        };
+
+        let cutoff = self.cutoff();
+        let mut records = Vec::new();
+        for line in BufReader::new(file).lines() {
+            let line = line?;
+            // Skip lines that are torn or written by an incompatible version
+            if let Ok(record) = serde_json::from_str::<HistoryRecord>(&line) {
+                if record.timestamp >= cutoff {
+                    records.push(record);
+                }
+            }
+        }
+        Ok(records)
+    }
+
+    /// Drop expired records and shrink the file to half its size limit
+    fn prune_locked(&self, file: &mut File) -> Result<()> {
+        let mut content = String::new();
+        file.seek(SeekFrom::Start(0))?;
+        file.read_to_string(&mut content)?;
+
+        let cutoff = self.cutoff();
+        let budget = (self.max_bytes / 2) as usize;
+        let mut kept: Vec<&str> = Vec::new();
+        let mut size = 0;
+        for line in content.lines().rev() {
+            let Ok(record) = serde_json::from_str::<HistoryRecord>(line) else {
+                continue;
+            };
+            if record.timestamp < cutoff || size + line.len() + 1 > budget {
+                break;
+            }
+            size += line.len() + 1;
+            kept.push(line);
+        }
+
+        debug!(
+            "Pruning history {}: keeping {} records",
+            self.path.display(),
+            kept.len()
+        );
+
+        file.set_len(0)?;
+        let mut output = String::with_capacity(size);
+        for line in kept.iter().rev() {
+            output.push_str(line);
+            output.push('\n');
+        }
+        file.write_all(output.as_bytes())?;
+        Ok(())
+    }
+
+    fn cutoff(&self) -> DateTime<Utc> {
+        Utc::now() - ChronoDuration::days(i64::from(self.max_age_days))
+    }
+}
+
+/// Aggregated usage of a single shim
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ShimUsage {
+    pub shim: String,
+    pub runs: usize,
+    pub failures: usize,
+    pub p50_ms: u64,
+    pub p95_ms: u64,
+    pub last_used: DateTime<Utc>,
+}
+
+impl ShimUsage {
+    /// Share of failed runs, between 0 and 1
+    pub fn failure_rate(&self) -> f64 {
+        if self.runs == 0 {
+            0.0
+        } else {
+            self.failures as f64 / self.runs as f64
+        }
+    }
+}
+
+/// Aggregate records per shim, most used first
+pub fn summarize(records: &[HistoryRecord]) -> Vec<ShimUsage> {
+    let mut by_shim: BTreeMap<&str, Vec<&HistoryRecord>> = BTreeMap::new();
+    for record in records {
+        by_shim.entry(&record.shim).or_default().push(record);
+    }
+
+    let mut usage: Vec<ShimUsage> = by_shim
+        .into_iter()
+        .map(|(shim, records)| {
+            let mut durations: Vec<u64> = records.iter().map(|r| r.duration_ms).collect();
+            durations.sort_unstable();
+
+            ShimUsage {
+                shim: shim.to_string(),
+                runs: records.len(),
+                failures: records.iter().filter(|r| r.is_failure()).count(),
+                p50_ms: percentile(&durations, 50),
+                p95_ms: percentile(&durations, 95),
+                last_used: records
+                    .iter()
+                    .map(|r| r.timestamp)
+                    .max()
+                    .unwrap_or_default(),
+            }
+        })
+        .collect();
+
+    usage.sort_by(|a, b| b.runs.cmp(&a.runs).then_with(|| a.shim.cmp(&b.shim)));
+    usage
+}
+
+/// Names from `known` that never show up in `records`
+pub fn never_used<'a>(known: &'a [String], records: &[HistoryRecord]) -> Vec<&'a str> {
+    let used: HashSet<&str> = records.iter().map(|r| r.shim.as_str()).collect();
+    known
+        .iter()
+        .map(String::as_str)
+        .filter(|name| !used.contains(name))
+        .collect()
+}
+
+/// Nearest-rank percentile of sorted values
+fn percentile(sorted: &[u64], p: usize) -> u64 {
+    if sorted.is_empty() {
+        return 0;
+    }
+    let rank = (p * sorted.len()).div_ceil(100).max(1);
+    sorted[rank - 1]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn record(shim: &str, duration_ms: u64, exit_code: Option<i32>) -> HistoryRecord {
+        HistoryRecord {
+            timestamp: Utc::now(),
+            shim: shim.to_string(),
+            executable: format!("/usr/bin/{}", shim),
+            argc: 1,
+            cwd: "/tmp".to_string(),
+            duration_ms,
+            exit_code,
+        }
+    }
+
+    #[test]
+    fn test_append_and_read() {
+        let dir = tempfile::tempdir().unwrap();
+        let history = History::new(dir.path().join("history.jsonl"));
+        assert!(history.read().unwrap().is_empty());
+
+        history.append(&record("node", 10, Some(0))).unwrap();
+        history.append(&record("git", 20, Some(1))).unwrap();
+
+        let records = history.read().unwrap();
+        assert_eq!(records.len(), 2);
+        assert_eq!(records[1].shim, "git");
+    }
+
+    #[test]
+    fn test_prune_by_size_and_age() {
+        let dir = tempfile::tempdir().unwrap();
+        let history = History::new(dir.path().join("history.jsonl")).with_max_bytes(2048);
+
+        let mut old = record("old", 1, Some(0));
+        old.timestamp = Utc::now() - ChronoDuration::days(365);
+        history.append(&old).unwrap();
+        for i in 0..50 {
+            history.append(&record("node", i, Some(0))).unwrap();
+        }
+
+        let size = fs::metadata(history.path()).unwrap().len();
+        assert!(size <= 2048);
+        let records = history.read().unwrap();
+        assert!(!records.is_empty());
+        assert!(records.iter().all(|r| r.shim == "node"));
+        assert_eq!(records.last().unwrap().duration_ms, 49);
+    }
+
+    /// `from_vars` with the history file in /tmp and the other variables from `vars`
+    fn resolve(config: Option<&HistoryConfig>, vars: &[(&str, &str)]) -> Option<History> {
+        History::from_vars(config, |name| {
+            if name == HISTORY_FILE_ENV_VAR {
+                return Some(OsString::from("/tmp/history.jsonl"));
+            }
+            vars.iter()
+                .find(|(key, _)| *key == name)
+                .map(|(_, value)| OsString::from(value))
+        })
+    }
+
+    #[test]
+    fn test_recording_is_opt_in() {
+        let enabled = HistoryConfig { enabled: true };
+
+        assert!(resolve(None, &[]).is_none());
+        assert!(resolve(Some(&HistoryConfig::default()), &[]).is_none());
+        assert!(resolve(None, &[(HISTORY_ENV_VAR, "0")]).is_none());
+
+        let history = resolve(Some(&enabled), &[]).unwrap();
+        assert_eq!(history.path(), Path::new("/tmp/history.jsonl"));
+        assert!(resolve(None, &[(HISTORY_ENV_VAR, "1")]).is_some());
+    }
+
+    #[test]
+    fn test_no_history_overrides_opt_in() {
+        let enabled = HistoryConfig { enabled: true };
+        let disabled = [(HISTORY_ENV_VAR, "1"), (NO_HISTORY_ENV_VAR, "1")];
+        assert!(resolve(Some(&enabled), &disabled).is_none());
+
+        let not_disabled = [(HISTORY_ENV_VAR, "1"), (NO_HISTORY_ENV_VAR, "0")];
+        assert!(resolve(None, &not_disabled).is_some());
+    }
+
+    #[test]
+    fn test_summarize() {
+        let records = vec![
+            record("node", 10, Some(0)),
+            record("node", 30, Some(1)),
+            record("node", 20, None),
+            record("git", 5, Some(0)),
+        ];
+
+        let usage = summarize(&records);
+        assert_eq!(usage[0].shim, "node");
+        assert_eq!(usage[0].runs, 3);
+        assert_eq!(usage[0].failures, 2);
+        assert_eq!(usage[0].p50_ms, 20);
+        assert_eq!(usage[0].p95_ms, 30);
+        assert_eq!(usage[1].shim, "git");
+
+        let known = vec!["node".to_string(), "python".to_string()];
+        assert_eq!(never_used(&known, &records), vec!["python"]);
+    }
+}
@@ -0,0 +1,221 @@
+//! Declarative pre/post execution hooks (`[hooks]`).
+//!
+//! Hooks are shell commands run before and after the target executable. They
+//! receive the shim context through environment variables:
+//! - `SHIMEXE_HOOK`: `pre_exec` or `post_exec`
+//! - `SHIMEXE_SHIM_NAME`: name of the shim
+//! - `SHIMEXE_EXECUTABLE`: resolved target executable
+//! - `SHIMEXE_ARGC`, `SHIMEXE_ARG_<n>`: resolved arguments passed to the target
+//! - `SHIMEXE_ARGV`: the same arguments joined with spaces (for display only)
+//! - `SHIMEXE_EXIT_CODE`: exit code of the target (`post_exec` only)
+
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+use std::process::Stdio;
+use std::time::Duration;
+use tracing::{debug, warn};
+
+use crate::env_from::EnvDiff;
+use crate::error::{Result, ShimError};
+use crate::process::{default_shell, shell_command, stderr_stdio, wait_timeout};
+
+/// Hooks run around the target executable
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct HooksConfig {
+    /// Commands run before the target starts
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub pre_exec: Vec<Hook>,
+    /// Commands run after the target exits
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub post_exec: Vec<Hook>,
+}
+
+/// A single hook command
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum Hook {
+    /// Plain command; failures abort the shim
+    Command(String),
+    /// Command with execution settings
+    Detailed {
+        /// Shell command to run
+        command: String,
+        /// What to do when the hook fails or times out
+        #[serde(default)]
+        on_failure: HookFailure,
+        /// Kill the hook and treat it as failed after this many seconds
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        timeout_secs: Option<u64>,
+        /// Start the hook without waiting for it
+        #[serde(default)]
+        background: bool,
+    },
+}
+
+/// Failure policy for a hook
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum HookFailure {
+    /// Stop and report an error
+    #[default]
+    Abort,
+    /// Log a warning and carry on
+    Warn,
+}
+
+/// Hook stage
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum HookStage {
+    PreExec,
+    PostExec,
+}
+
+/// Information about the invocation exposed to hooks
+pub struct HookContext<'a> {
+    pub shim_name: &'a str,
+    pub executable: &'a Path,
+    pub argv: &'a [String],
+    pub env: &'a EnvDiff,
+    pub exit_code: Option<i32>,
+}
+
+impl Hook {
+    /// Get the command string
+    pub fn command(&self) -> &str {
+        match self {
+            Hook::Command(command) => command,
+            Hook::Detailed { command, .. } => command,
+        }
+    }
+
+    /// Get the failure policy
+    pub fn on_failure(&self) -> HookFailure {
+        match self {
+            Hook::Command(_) => HookFailure::Abort,
+            Hook::Detailed { on_failure, .. } => *on_failure,
+        }
+    }
+
+    /// Get the timeout, if any
+    pub fn timeout(&self) -> Option<Duration> {
+        match self {
+            Hook::Command(_) => None,
+            Hook::Detailed { timeout_secs, .. } => timeout_secs.map(Duration::from_secs),
+        }
+    }
+
+    /// Whether the hook runs in the background
+    pub fn is_background(&self) -> bool {
+        match self {
+            Hook::Command(_) => false,
+            Hook::Detailed { background, .. } => *background,
+        }
+    }
+
+    /// Run the hook, applying its failure policy
+    pub fn run(&self, stage: HookStage, context: &HookContext<'_>) -> Result<()> {
+        match self.try_run(stage, context) {
+            Ok(()) => Ok(()),
+            Err(message) => match self.on_failure() {
+                HookFailure::Abort => Err(ShimError::HookFailed(message)),
+                HookFailure::Warn => {
+                    warn!("{}", message);
+                    Ok(())
+                }
+            },
+        }
+    }
+
+    fn try_run(
+        &self,
+        stage: HookStage,
+        context: &HookContext<'_>,
+    ) -> std::result::Result<(), String> {
+        let stage_name = stage.name();
+        let command = self.command();
+        debug!("Running {} hook: {}", stage_name, command);
+
+        let mut cmd = shell_command(default_shell(), command);
+        context.env.apply(&mut cmd);
+        cmd.env("SHIMEXE_HOOK", stage_name)
+            .env("SHIMEXE_SHIM_NAME", context.shim_name)
+            .env("SHIMEXE_EXECUTABLE", context.executable)
+            .env("SHIMEXE_ARGC", context.argv.len().to_string())
+            .env("SHIMEXE_ARGV", context.argv.join(" "));
+        for (i, arg) in context.argv.iter().enumerate() {
+            cmd.env(format!("SHIMEXE_ARG_{}", i), arg);
+        }
+        if let Some(exit_code) = context.exit_code {
+            cmd.env("SHIMEXE_EXIT_CODE", exit_code.to_string());
+        }
+
+        // Hook output goes to stderr so it never mixes with the target's stdout
+        cmd.stdout(stderr_stdio());
+
+        if self.is_background() {
+            cmd.stdin(Stdio::null());
+            return cmd
+                .spawn()
+                .map(|_| ())
+                .map_err(|e| format!("{} hook '{}' could not start: {}", stage_name, command, e));
+        }
+
+        let mut child = cmd
+            .spawn()
+            .map_err(|e| format!("{} hook '{}' could not start: {}", stage_name, command, e))?;
+
+        let status = match self.timeout() {
+            Some(timeout) => match wait_timeout(&mut child, timeout) {
+                Ok(Some(status)) => status,
+                Ok(None) => {
+                    let _ = child.kill();
+                    let _ = child.wait();
+                    return Err(format!(
+                        "{} hook '{}' timed out after {}s",
+                        stage_name,
+                        command,
+                        timeout.as_secs()
+                    ));
+                }
+                Err(e) => return Err(format!("{} hook '{}' failed: {}", stage_name, command, e)),
+            },
+            None => child
+                .wait()
+                .map_err(|e| format!("{} hook '{}' failed: {}", stage_name, command, e))?,
+        };
+
+        if status.success() {
+            Ok(())
+        } else {
+            Err(format!(
+                "{} hook '{}' failed with {}",
+                stage_name, command, status
+            ))
+        }
+    }
+}
+
+impl HookStage {
+    /// Name used in logs and `SHIMEXE_HOOK`
+    pub fn name(&self) -> &'static str {
+        match self {
+            HookStage::PreExec => "pre_exec",
+            HookStage::PostExec => "post_exec",
+        }
+    }
+}
+
+impl HooksConfig {
+    /// Run all hooks of a stage in order
+    pub fn run(&self, stage: HookStage, context: &HookContext<'_>) -> Result<()> {
+        let hooks = match stage {
+            HookStage::PreExec => &self.pre_exec,
+            HookStage::PostExec => &self.post_exec,
+        };
+
+        for hook in hooks {
+            hook.run(stage, context)?;
+        }
+        Ok(())
+    }
+}
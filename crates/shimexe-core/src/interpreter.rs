@@ -0,0 +1,227 @@
+//! Interpreters for script targets (`shim.interpreter`).
+//!
+//! A shim can point at a script instead of an executable. The interpreter is
+//! either given explicitly (`"python3 -u"` or `["java", "-jar"]`) or, with
+//! `"auto"`, inferred from the script's extension or shebang line. The
+//! interpreter command is resolved like `shim.path`: absolute paths are used
+//! as they are, anything else is looked up in `PATH`.
+
+use serde::{Deserialize, Serialize};
+use std::fs::File;
+use std::io::{BufRead, BufReader, Read};
+use std::path::{Path, PathBuf};
+
+use crate::error::{Result, ShimError};
+use crate::utils::expand_env_vars;
+
+/// Value selecting inference from the extension or shebang
+pub const AUTO: &str = "auto";
+
+/// How a script target is run
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum Interpreter {
+    /// `"auto"`, or a command line split on whitespace (e.g. `"python3 -u"`)
+    Command(String),
+    /// Program followed by its arguments (e.g. `["java", "-jar"]`)
+    Args(Vec<String>),
+}
+
+/// Interpreter resolved for a particular script
+#[derive(Debug, Clone, PartialEq)]
+pub struct ResolvedInterpreter {
+    /// Interpreter executable
+    pub program: PathBuf,
+    /// Arguments placed before the script path
+    pub args: Vec<String>,
+}
+
+impl Interpreter {
+    /// Validate the interpreter setting
+    pub fn validate(&self) -> Result<()> {
+        let empty = match self {
+            Interpreter::Command(command) => command.trim().is_empty(),
+            Interpreter::Args(args) => args.first().is_none_or(|p| p.trim().is_empty()),
+        };
+        if empty {
+            return Err(ShimError::Config(
+                "shim.interpreter must not be empty".to_string(),
+            ));
+        }
+        Ok(())
+    }
+
+    /// Expand environment variables in the interpreter command
+    pub fn expand_env_vars(&mut self) -> Result<()> {
+        match self {
+            Interpreter::Command(command) => *command = expand_env_vars(command)?,
+            Interpreter::Args(args) => {
+                for arg in args {
+                    *arg = expand_env_vars(arg)?;
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Command line (program first) used to run `script`
+    ///
+    /// Returns `None` when `"auto"` cannot infer an interpreter, in which case
+    /// the script is executed directly.
+    pub fn command_for(&self, script: &Path) -> Option<Vec<String>> {
+        match self {
+            Interpreter::Command(command) if command.trim() == AUTO => infer(script),
+            Interpreter::Command(command) => {
+                Some(command.split_whitespace().map(String::from).collect())
+            }
+            Interpreter::Args(args) => Some(args.clone()),
+        }
+    }
+
+    /// Resolve the interpreter executable for `script`
+    pub fn resolve(&self, script: &Path) -> Result<Option<ResolvedInterpreter>> {
+        let Some(mut command) = self.command_for(script) else {
+            return Ok(None);
+        };
+        let program = command.remove(0);
+        let path = PathBuf::from(&program);
+        let program = if path.is_absolute() {
+            path
+        } else {
+            which::which(&path)
+                .map_err(|_| ShimError::ExecutableNotFound(format!("interpreter '{}'", program)))?
+        };
+
+        Ok(Some(ResolvedInterpreter {
+            program,
+            args: command,
+        }))
+    }
+}
+
+/// Infer the interpreter of `script` from its extension, then its shebang
+pub fn infer(script: &Path) -> Option<Vec<String>> {
+    let extension = script
+        .extension()
+        .and_then(|e| e.to_str())
+        .map(str::to_ascii_lowercase);
+    if let Some(command) = extension.as_deref().and_then(for_extension) {
+        return Some(command.iter().map(|s| s.to_string()).collect());
+    }
+    shebang(script)
+}
+
+/// Interpreter for a file extension
+fn for_extension(extension: &str) -> Option<&'static [&'static str]> {
+    let command: &[&str] = match extension {
+        #[cfg(windows)]
+        "py" => &["python"],
+        #[cfg(not(windows))]
+        "py" => &["python3"],
+        "js" | "mjs" | "cjs" => &["node"],
+        "jar" => &["java", "-jar"],
+        "sh" => &["sh"],
+        "bash" => &["bash"],
+        "rb" => &["ruby"],
+        "pl" => &["perl"],
+        "ps1" => &["pwsh", "-File"],
+        "wasm" => &["wasmtime", "run"],
+        _ => return None,
+    };
+    Some(command)
+}
+
+/// Interpreter named by the shebang line of `script`
+///
+/// `#!/usr/bin/env python3` becomes `python3`, so the interpreter is looked up
+/// in `PATH` like `env` would; `env -S` is supported for multiple arguments.
+fn shebang(script: &Path) -> Option<Vec<String>> {
+    // Only the start of the file is read, so binaries without newlines stay cheap
+    let mut first_line = Vec::new();
+    BufReader::new(File::open(script).ok()?.take(256))
+        .read_until(b'\n', &mut first_line)
+        .ok()?;
+    let line = String::from_utf8_lossy(&first_line);
+    let mut words = line.strip_prefix("#!")?.split_whitespace().peekable();
+
+    let program = words.next()?;
+    if Path::new(program)
+        .file_name()
+        .is_some_and(|name| name == "env")
+    {
+        if words.peek() == Some(&"-S") {
+            words.next();
+        }
+        let command: Vec<String> = words.map(String::from).collect();
+        return (!command.is_empty()).then_some(command);
+    }
+
+    let mut command = vec![program.to_string()];
+    command.extend(words.map(String::from));
+    Some(command)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(toml_str: &str) -> Interpreter {
+        #[derive(Deserialize)]
+        struct Wrapper {
+            interpreter: Interpreter,
+        }
+        toml::from_str::<Wrapper>(toml_str).unwrap().interpreter
+    }
+
+    #[test]
+    fn test_explicit_commands() {
+        let script = Path::new("tool.py");
+        assert_eq!(
+            parse("interpreter = 'python3 -u'").command_for(script),
+            Some(vec!["python3".to_string(), "-u".to_string()])
+        );
+        assert_eq!(
+            parse("interpreter = ['java', '-jar']").command_for(script),
+            Some(vec!["java".to_string(), "-jar".to_string()])
+        );
+        assert!(parse("interpreter = ' '").validate().is_err());
+    }
+
+    #[test]
+    fn test_infer_from_extension() {
+        let auto = parse("interpreter = 'auto'");
+        assert_eq!(
+            auto.command_for(Path::new("/opt/app/server.jar")),
+            Some(vec!["java".to_string(), "-jar".to_string()])
+        );
+        assert_eq!(
+            auto.command_for(Path::new("build.SH")),
+            Some(vec!["sh".to_string()])
+        );
+    }
+
+    #[test]
+    fn test_infer_from_shebang() {
+        let dir = tempfile::tempdir().unwrap();
+        let cases = [
+            ("#!/usr/bin/env python3\nprint(1)\n", vec!["python3"]),
+            (
+                "#!/usr/bin/env -S node --no-warnings\n",
+                vec!["node", "--no-warnings"],
+            ),
+            ("#!/bin/bash -e\n", vec!["/bin/bash", "-e"]),
+        ];
+        for (i, (content, expected)) in cases.iter().enumerate() {
+            let script = dir.path().join(format!("script{}", i));
+            std::fs::write(&script, content).unwrap();
+            assert_eq!(
+                infer(&script),
+                Some(expected.iter().map(|s| s.to_string()).collect())
+            );
+        }
+
+        let binary = dir.path().join("binary");
+        std::fs::write(&binary, [0x7f, b'E', b'L', b'F', 0, 1]).unwrap();
+        assert_eq!(infer(&binary), None);
+    }
+}
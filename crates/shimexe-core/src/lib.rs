@@ -3,29 +3,73 @@
 //! Core library for shimexe - a modern, cross-platform executable shim manager
 //! with environment variable expansion and TOML configuration support.
 
+pub mod alias;
 pub mod archive;
+pub mod concurrency;
 pub mod config;
+pub mod dispatch;
+pub mod dotenv;
 pub mod downloader;
+pub mod env_from;
 pub mod error;
+pub mod exit;
+pub mod explain;
+pub mod history;
+pub mod hooks;
+pub mod interpreter;
+pub mod limits;
+pub mod logging;
 pub mod manager;
+pub mod process;
+pub mod profiles;
+pub mod project;
+pub mod resolution_cache;
+pub mod retry;
+pub mod rewrite;
 pub mod runner;
+pub mod sandbox;
 pub mod template;
+pub mod timing;
 pub mod traits;
+pub mod trust;
 pub mod updater;
 pub mod utils;
+pub mod versions;
+pub mod wasm;
 
 pub use archive::ArchiveExtractor;
+pub use concurrency::{ConcurrencyConfig, InstanceGuard, LockWait};
 pub use config::{
     AutoUpdate, ExtractedExecutable, ShimConfig, ShimCore, ShimMetadata, SourceType,
     UpdateProvider, VersionCheck,
 };
+pub use dispatch::{DispatchConfig, Route};
+pub use dotenv::EnvFile;
 pub use downloader::Downloader;
+pub use env_from::{EnvCapture, EnvDiff, EnvFrom};
 pub use error::{Result, ShimError};
-pub use manager::{ShimBuilder, ShimInfo, ShimManager};
+pub use exit::{ExitConfig, OutputWatch};
+pub use explain::{ExplainFormat, Explanation};
+pub use history::{History, HistoryConfig, HistoryRecord, ShimUsage};
+pub use hooks::{Hook, HookFailure, HooksConfig};
+pub use interpreter::Interpreter;
+pub use limits::LimitsConfig;
+pub use logging::{LogFormat, LoggingConfig};
+pub use manager::{InstalledVersion, ShimBuilder, ShimInfo, ShimManager};
+pub use process::{KillSignal, TimeoutConfig};
+pub use profiles::{ActiveProfile, Profile, ProfileSource};
+pub use project::{ProjectConfig, ProjectFile, ProjectMatch, ProjectOverride};
+pub use resolution_cache::ResolutionCache;
+pub use retry::{Backoff, RetryConfig};
+pub use rewrite::RewriteRule;
 pub use runner::ShimRunner;
+pub use sandbox::SandboxConfig;
 pub use template::{ArgsConfig, ArgsMode, TemplateEngine};
 pub use traits::{CustomizableShimRunner, ShimConfigLoader, ShimRunnerBuilder, ShimRunnerTrait};
+pub use trust::TrustStore;
 pub use updater::ShimUpdater;
+pub use versions::{SelectedVersion, VersionSource, VersionsConfig};
+pub use wasm::WasmConfig;
 
 /// Re-export commonly used types
 pub mod prelude {
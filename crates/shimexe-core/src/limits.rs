@@ -0,0 +1,295 @@
+//! Resource limits for the target process (`[limits]`, Unix only).
+//!
+//! Limits are applied in the child between `fork` and `exec` with
+//! `setrlimit`, `setpriority` and `umask`, so they never affect the shim
+//! itself. Both the soft and the hard limit are lowered, so the target cannot
+//! raise them again.
+
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::process::Command;
+
+use crate::error::{Result, ShimError};
+
+/// Resource limits for the target executable
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct LimitsConfig {
+    /// CPU time in seconds (`RLIMIT_CPU`)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cpu_seconds: Option<u64>,
+    /// Address space in bytes (`RLIMIT_AS`)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub memory_bytes: Option<u64>,
+    /// Number of open file descriptors (`RLIMIT_NOFILE`)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub open_files: Option<u64>,
+    /// Scheduling priority, from -20 (highest) to 19 (lowest)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub nice: Option<i32>,
+    /// File mode creation mask, e.g. `0o022`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub umask: Option<u32>,
+}
+
+impl LimitsConfig {
+    /// Validate the configured values
+    pub fn validate(&self) -> Result<()> {
+        for (name, value) in [
+            ("cpu_seconds", self.cpu_seconds),
+            ("memory_bytes", self.memory_bytes),
+            ("open_files", self.open_files),
+        ] {
+            if value == Some(0) {
+                return Err(ShimError::Config(format!(
+                    "limits.{} must be greater than 0",
+                    name
+                )));
+            }
+        }
+        if let Some(nice) = self.nice {
+            if !(-20..=19).contains(&nice) {
+                return Err(ShimError::Config(format!(
+                    "limits.nice must be between -20 and 19, got {}",
+                    nice
+                )));
+            }
+        }
+        if let Some(umask) = self.umask {
+            if umask > 0o777 {
+                return Err(ShimError::Config(format!(
+                    "limits.umask must be at most 0o777, got {:#o}",
+                    umask
+                )));
+            }
+        }
+        Ok(())
+    }
+
+    /// Whether any limit is set
+    pub fn is_empty(&self) -> bool {
+        *self == Self::default()
+    }
+
+    /// Check that the limits can be applied by the current process
+    ///
+    /// Unprivileged processes cannot raise a hard limit or lower their nice
+    /// value, so this fails up front instead of after the fork.
+    #[cfg(unix)]
+    pub fn check(&self) -> Result<()> {
+        self.validate()?;
+        // SAFETY: geteuid has no memory safety requirements
+        if unsafe { libc::geteuid() } == 0 {
+            return Ok(());
+        }
+
+        for (name, resource, value) in self.rlimits() {
+            let hard = sys::hard_limit(resource)?;
+            if hard != libc::RLIM_INFINITY && value > hard {
+                return Err(ShimError::Config(format!(
+                    "limits.{} ({}) exceeds the hard limit ({})",
+                    name, value, hard
+                )));
+            }
+        }
+
+        if let Some(nice) = self.nice {
+            let current = sys::current_nice()?;
+            if nice < current {
+                return Err(ShimError::PermissionDenied(format!(
+                    "limits.nice ({}) is below the current nice value ({})",
+                    nice, current
+                )));
+            }
+        }
+        Ok(())
+    }
+
+    #[cfg(not(unix))]
+    pub fn check(&self) -> Result<()> {
+        self.validate()?;
+        if !self.is_empty() {
+            tracing::warn!("Resource limits are only supported on Unix, ignoring [limits]");
+        }
+        Ok(())
+    }
+
+    /// Apply the limits to the child started by `cmd`
+    pub fn configure(&self, cmd: &mut Command) {
+        #[cfg(unix)]
+        {
+            use std::os::unix::process::CommandExt;
+
+            let rlimits = self.rlimits();
+            let nice = self.nice;
+            let umask = self.umask;
+            // SAFETY: the closure only makes async-signal-safe system calls
+            // and does not allocate
+            unsafe {
+                cmd.pre_exec(move || sys::apply(&rlimits, nice, umask));
+            }
+        }
+
+        #[cfg(not(unix))]
+        let _ = cmd;
+    }
+
+    #[cfg(unix)]
+    fn rlimits(&self) -> Vec<(&'static str, sys::Resource, libc::rlim_t)> {
+        [
+            ("cpu_seconds", libc::RLIMIT_CPU, self.cpu_seconds),
+            ("memory_bytes", libc::RLIMIT_AS, self.memory_bytes),
+            ("open_files", libc::RLIMIT_NOFILE, self.open_files),
+        ]
+        .into_iter()
+        .filter_map(|(name, resource, value)| {
+            value.map(|value| (name, resource, value as libc::rlim_t))
+        })
+        .collect()
+    }
+}
+
+impl fmt::Display for LimitsConfig {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut parts = Vec::new();
+        if let Some(secs) = self.cpu_seconds {
+            parts.push(format!("cpu {}s", secs));
+        }
+        if let Some(bytes) = self.memory_bytes {
+            parts.push(format!("memory {} bytes", bytes));
+        }
+        if let Some(files) = self.open_files {
+            parts.push(format!("open files {}", files));
+        }
+        if let Some(nice) = self.nice {
+            parts.push(format!("nice {}", nice));
+        }
+        if let Some(umask) = self.umask {
+            parts.push(format!("umask {:03o}", umask));
+        }
+        write!(f, "{}", parts.join(", "))
+    }
+}
+
+#[cfg(unix)]
+mod sys {
+    use std::io;
+
+    // glibc declares the RLIMIT_* constants with their own type, musl and the BSDs use c_int
+    #[cfg(all(target_os = "linux", target_env = "gnu"))]
+    pub(super) type Resource = libc::__rlimit_resource_t;
+    #[cfg(not(all(target_os = "linux", target_env = "gnu")))]
+    pub(super) type Resource = libc::c_int;
+
+    pub(super) fn hard_limit(resource: Resource) -> io::Result<libc::rlim_t> {
+        let mut limit = libc::rlimit {
+            rlim_cur: 0,
+            rlim_max: 0,
+        };
+        // SAFETY: `limit` is a valid, writable rlimit
+        if unsafe { libc::getrlimit(resource, &mut limit) } != 0 {
+            return Err(io::Error::last_os_error());
+        }
+        Ok(limit.rlim_max)
+    }
+
+    pub(super) fn current_nice() -> io::Result<i32> {
+        // getpriority can legitimately return -1, so errors are told apart via errno
+        errno_clear();
+        // SAFETY: getpriority has no memory safety requirements
+        let nice = unsafe { libc::getpriority(libc::PRIO_PROCESS, 0) };
+        let err = io::Error::last_os_error();
+        if nice == -1 && err.raw_os_error().is_some_and(|e| e != 0) {
+            return Err(err);
+        }
+        Ok(nice)
+    }
+
+    /// Apply the limits in the child; runs between fork and exec
+    pub(super) fn apply(
+        rlimits: &[(&'static str, Resource, libc::rlim_t)],
+        nice: Option<i32>,
+        umask: Option<u32>,
+    ) -> io::Result<()> {
+        for &(_, resource, value) in rlimits {
+            let limit = libc::rlimit {
+                rlim_cur: value,
+                rlim_max: value,
+            };
+            // SAFETY: `limit` is a valid rlimit
+            if unsafe { libc::setrlimit(resource, &limit) } != 0 {
+                return Err(io::Error::last_os_error());
+            }
+        }
+        if let Some(nice) = nice {
+            // SAFETY: setpriority has no memory safety requirements
+            if unsafe { libc::setpriority(libc::PRIO_PROCESS, 0, nice) } != 0 {
+                return Err(io::Error::last_os_error());
+            }
+        }
+        if let Some(umask) = umask {
+            // SAFETY: umask cannot fail
+            unsafe {
+                libc::umask(umask as libc::mode_t);
+            }
+        }
+        Ok(())
+    }
+
+    #[cfg(any(target_os = "linux", target_os = "android"))]
+    fn errno_clear() {
+        // SAFETY: __errno_location returns a valid thread-local pointer
+        unsafe { *libc::__errno_location() = 0 }
+    }
+
+    #[cfg(any(target_os = "macos", target_os = "ios", target_os = "freebsd"))]
+    fn errno_clear() {
+        // SAFETY: __error returns a valid thread-local pointer
+        unsafe { *libc::__error() = 0 }
+    }
+
+    #[cfg(not(any(
+        target_os = "linux",
+        target_os = "android",
+        target_os = "macos",
+        target_os = "ios",
+        target_os = "freebsd"
+    )))]
+    fn errno_clear() {}
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_validate() {
+        let limits: LimitsConfig =
+            toml::from_str("cpu_seconds = 60\nnice = 10\numask = 0o022").unwrap();
+        assert_eq!(limits.umask, Some(0o022));
+        assert!(limits.validate().is_ok());
+        assert_eq!(limits.to_string(), "cpu 60s, nice 10, umask 022");
+
+        for bad in ["open_files = 0", "nice = 20", "umask = 0o1000"] {
+            let limits: LimitsConfig = toml::from_str(bad).unwrap();
+            assert!(limits.validate().is_err(), "{}", bad);
+        }
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_check_rejects_raising_hard_limit() {
+        // SAFETY: geteuid has no memory safety requirements
+        if unsafe { libc::geteuid() } == 0 {
+            return;
+        }
+        let hard = sys::hard_limit(libc::RLIMIT_NOFILE).unwrap();
+        if hard == libc::RLIM_INFINITY {
+            return;
+        }
+        let limits = LimitsConfig {
+            open_files: Some(hard as u64 + 1),
+            ..Default::default()
+        };
+        assert!(limits.check().is_err());
+    }
+}
@@ -0,0 +1,414 @@
+//! Per-shim logging (`[logging]`).
+//!
+//! The target's stdout and/or stderr can be teed into a rotating log file under
+//! the shim's data directory while still being streamed to the terminal. The
+//! section also sets the level and format of shimexe's own diagnostics in shim
+//! mode.
+
+use serde::{Deserialize, Serialize};
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, IsTerminal, Read, Write};
+use std::path::{Path, PathBuf};
+use std::process::{Child, Command, Stdio};
+use std::sync::{Arc, Mutex};
+use std::thread::{self, JoinHandle};
+use tracing::warn;
+
+use crate::error::Result;
+use crate::exit::OutputWatch;
+
+/// Output logging settings
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct LoggingConfig {
+    /// Tee the target's stdout into the log
+    #[serde(default)]
+    pub stdout: bool,
+    /// Tee the target's stderr into the log
+    #[serde(default)]
+    pub stderr: bool,
+    /// Log file name inside `<data dir>/logs` (default: `<shim name>.log`)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub file: Option<String>,
+    /// Rotate the log once it grows beyond this size
+    #[serde(default = "default_max_size_bytes")]
+    pub max_size_bytes: u64,
+    /// Number of rotated files kept next to the active log
+    #[serde(default = "default_max_files")]
+    pub max_files: u32,
+    /// Prefix every logged line with a timestamp
+    #[serde(default)]
+    pub timestamps: bool,
+    /// Also tee streams connected to a terminal
+    ///
+    /// Teeing replaces the terminal with a pipe, which makes many programs turn
+    /// off colors and interactive output, so terminal streams are inherited
+    /// unless this is set.
+    #[serde(default)]
+    pub tee_terminal: bool,
+    /// Level (or filter directives) for shimexe diagnostics in shim mode
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub level: Option<String>,
+    /// Format of shimexe diagnostics in shim mode
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub format: Option<LogFormat>,
+}
+
+/// Diagnostic log format
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum LogFormat {
+    /// Human-readable lines
+    #[default]
+    Text,
+    /// One JSON object per event
+    Json,
+}
+
+fn default_max_size_bytes() -> u64 {
+    10 * 1024 * 1024
+}
+
+fn default_max_files() -> u32 {
+    5
+}
+
+impl Default for LoggingConfig {
+    fn default() -> Self {
+        Self {
+            stdout: false,
+            stderr: false,
+            file: None,
+            max_size_bytes: default_max_size_bytes(),
+            max_files: default_max_files(),
+            timestamps: false,
+            tee_terminal: false,
+            level: None,
+            format: None,
+        }
+    }
+}
+
+impl LoggingConfig {
+    /// Path of the active log file
+    pub fn log_path(&self, data_dir: &Path, shim_name: &str) -> PathBuf {
+        let file = self
+            .file
+            .clone()
+            .unwrap_or_else(|| format!("{}.log", shim_name));
+        data_dir.join("logs").join(file)
+    }
+}
+
+/// Target output stream
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Stream {
+    Stdout,
+    Stderr,
+}
+
+impl Stream {
+    fn name(&self) -> &'static str {
+        match self {
+            Stream::Stdout => "stdout",
+            Stream::Stderr => "stderr",
+        }
+    }
+
+    fn is_terminal(&self) -> bool {
+        match self {
+            Stream::Stdout => io::stdout().is_terminal(),
+            Stream::Stderr => io::stderr().is_terminal(),
+        }
+    }
+
+    fn write_through(&self, data: &[u8]) -> io::Result<()> {
+        match self {
+            Stream::Stdout => {
+                let mut out = io::stdout().lock();
+                out.write_all(data)?;
+                out.flush()
+            }
+            Stream::Stderr => {
+                let mut err = io::stderr().lock();
+                err.write_all(data)?;
+                err.flush()
+            }
+        }
+    }
+}
+
+/// Log file rotated by size, keeping `<file>.1` .. `<file>.<max_files>`
+pub struct RotatingLog {
+    path: PathBuf,
+    file: File,
+    size: u64,
+    max_size_bytes: u64,
+    max_files: u32,
+    timestamps: bool,
+}
+
+impl RotatingLog {
+    /// Open (or create) the log at `path`
+    pub fn open(path: PathBuf, config: &LoggingConfig) -> Result<Self> {
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        let file = OpenOptions::new().create(true).append(true).open(&path)?;
+        let size = file.metadata()?.len();
+
+        Ok(Self {
+            path,
+            file,
+            size,
+            max_size_bytes: config.max_size_bytes,
+            max_files: config.max_files,
+            timestamps: config.timestamps,
+        })
+    }
+
+    /// Append one line of output from `stream`
+    pub fn write_line(&mut self, stream: Stream, line: &[u8]) -> io::Result<()> {
+        let mut record = Vec::with_capacity(line.len() + 48);
+        if self.timestamps {
+            let now = chrono::Utc::now().format("%Y-%m-%dT%H:%M:%S%.3fZ");
+            write!(record, "{} ", now)?;
+        }
+        write!(record, "[{}] ", stream.name())?;
+        record.extend_from_slice(line);
+        if !line.ends_with(b"\n") {
+            record.push(b'\n');
+        }
+
+        if self.size > 0 && self.size + record.len() as u64 > self.max_size_bytes {
+            self.rotate()?;
+        }
+        self.file.write_all(&record)?;
+        self.size += record.len() as u64;
+        Ok(())
+    }
+
+    fn rotate(&mut self) -> io::Result<()> {
+        let rotated = |n: u32| {
+            let mut name = self.path.clone().into_os_string();
+            name.push(format!(".{}", n));
+            PathBuf::from(name)
+        };
+
+        if self.max_files == 0 {
+            fs::remove_file(&self.path)?;
+        } else {
+            let _ = fs::remove_file(rotated(self.max_files));
+            for n in (1..self.max_files).rev() {
+                let from = rotated(n);
+                if from.exists() {
+                    fs::rename(&from, rotated(n + 1))?;
+                }
+            }
+            fs::rename(&self.path, rotated(1))?;
+        }
+
+        self.file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&self.path)?;
+        self.size = 0;
+        Ok(())
+    }
+}
+
+/// Tees the target's output streams into a shared log and/or an [`OutputWatch`]
+pub struct OutputTee {
+    log: Option<Arc<Mutex<RotatingLog>>>,
+    logged: Vec<Stream>,
+    watch: Option<Arc<OutputWatch>>,
+    streams: Vec<Stream>,
+}
+
+impl OutputTee {
+    /// Set up teeing for the streams selected in `config`
+    ///
+    /// Returns `None` when no stream needs to be teed.
+    pub fn new(config: &LoggingConfig, log_path: PathBuf) -> Result<Option<Self>> {
+        let streams: Vec<Stream> = [
+            (Stream::Stdout, config.stdout),
+            (Stream::Stderr, config.stderr),
+        ]
+        .into_iter()
+        .filter(|(stream, enabled)| *enabled && (config.tee_terminal || !stream.is_terminal()))
+        .map(|(stream, _)| stream)
+        .collect();
+
+        if streams.is_empty() {
+            return Ok(None);
+        }
+
+        let log = RotatingLog::open(log_path, config)?;
+        Ok(Some(Self {
+            log: Some(Arc::new(Mutex::new(log))),
+            logged: streams.clone(),
+            watch: None,
+            streams,
+        }))
+    }
+
+    /// Tee only into `watch`, without a log file
+    pub fn for_watch(watch: Arc<OutputWatch>) -> Self {
+        Self {
+            log: None,
+            logged: Vec::new(),
+            watch: None,
+            streams: Vec::new(),
+        }
+        .with_watch(watch)
+    }
+
+    /// Also feed the streams `watch` needs into it
+    pub fn with_watch(mut self, watch: Arc<OutputWatch>) -> Self {
+        for stream in watch.streams() {
+            if !self.streams.contains(&stream) {
+                self.streams.push(stream);
+            }
+        }
+        self.watch = Some(watch);
+        self
+    }
+
+    /// Replace the teed streams of `cmd` with pipes
+    pub fn configure(&self, cmd: &mut Command) {
+        for stream in &self.streams {
+            match stream {
+                Stream::Stdout => cmd.stdout(Stdio::piped()),
+                Stream::Stderr => cmd.stderr(Stdio::piped()),
+            };
+        }
+    }
+
+    /// Start pumping the pipes of a spawned child
+    ///
+    /// Join the returned handles after the child exits so no output is lost.
+    pub fn attach(&self, child: &mut Child) -> Vec<JoinHandle<()>> {
+        let mut pumps = Vec::new();
+        if let Some(stdout) = child.stdout.take() {
+            pumps.push(self.pump(stdout, Stream::Stdout));
+        }
+        if let Some(stderr) = child.stderr.take() {
+            pumps.push(self.pump(stderr, Stream::Stderr));
+        }
+        pumps
+    }
+
+    fn pump<R: Read + Send + 'static>(&self, mut reader: R, stream: Stream) -> JoinHandle<()> {
+        let mut log = self.log.clone().filter(|_| self.logged.contains(&stream));
+        let watch = self.watch.clone();
+        thread::spawn(move || {
+            let mut buf = [0u8; 8192];
+            let mut line = Vec::new();
+
+            let consume = |line: &[u8], log: &mut Option<Arc<Mutex<RotatingLog>>>| {
+                if let Some(ref watch) = watch {
+                    watch.check(stream, line);
+                }
+                if log.as_ref().is_some_and(|l| !write_log(l, stream, line)) {
+                    *log = None;
+                }
+            };
+
+            loop {
+                let n = match reader.read(&mut buf) {
+                    Ok(0) => break,
+                    Ok(n) => n,
+                    Err(e) if e.kind() == io::ErrorKind::Interrupted => continue,
+                    Err(_) => break,
+                };
+
+                // The terminal always gets the output, even if logging fails
+                let _ = stream.write_through(&buf[..n]);
+
+                if log.is_none() && watch.is_none() {
+                    continue;
+                }
+                for chunk in buf[..n].split_inclusive(|b| *b == b'\n') {
+                    line.extend_from_slice(chunk);
+                    if line.ends_with(b"\n") {
+                        consume(&line, &mut log);
+                        line.clear();
+                    }
+                }
+            }
+
+            if !line.is_empty() {
+                consume(&line, &mut log);
+            }
+        })
+    }
+}
+
+fn write_log(log: &Mutex<RotatingLog>, stream: Stream, line: &[u8]) -> bool {
+    let mut log = match log.lock() {
+        Ok(log) => log,
+        Err(poisoned) => poisoned.into_inner(),
+    };
+    match log.write_line(stream, line) {
+        Ok(()) => true,
+        Err(e) => {
+            warn!("Failed to write output log {}: {}", log.path.display(), e);
+            false
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_rotating_log() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("logs").join("tool.log");
+        let config = LoggingConfig {
+            max_size_bytes: 20,
+            max_files: 2,
+            ..Default::default()
+        };
+
+        let mut log = RotatingLog::open(path.clone(), &config).unwrap();
+        for i in 0..4 {
+            log.write_line(Stream::Stdout, format!("line {}\n", i).as_bytes())
+                .unwrap();
+        }
+
+        assert_eq!(fs::read_to_string(&path).unwrap(), "[stdout] line 3\n");
+        assert_eq!(
+            fs::read_to_string(dir.path().join("logs/tool.log.1")).unwrap(),
+            "[stdout] line 2\n"
+        );
+        assert!(dir.path().join("logs/tool.log.2").exists());
+        assert!(!dir.path().join("logs/tool.log.3").exists());
+    }
+
+    #[test]
+    fn test_timestamps_and_partial_lines() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("tool.log");
+        let config = LoggingConfig {
+            timestamps: true,
+            ..Default::default()
+        };
+
+        let mut log = RotatingLog::open(path.clone(), &config).unwrap();
+        log.write_line(Stream::Stderr, b"no newline").unwrap();
+
+        let content = fs::read_to_string(&path).unwrap();
+        assert!(content.ends_with(" [stderr] no newline\n"));
+        assert!(content.starts_with("20"));
+    }
+
+    #[test]
+    fn test_log_path() {
+        let config = LoggingConfig::default();
+        assert_eq!(
+            config.log_path(Path::new("/data/tool"), "tool"),
+            Path::new("/data/tool/logs/tool.log")
+        );
+    }
+}
@@ -9,7 +9,9 @@ use tracing::info;
 use crate::config::{ShimConfig, ShimCore, ShimMetadata, SourceType};
 use crate::downloader::Downloader;
 use crate::error::{Result, ShimError};
+use crate::resolution_cache::ResolutionCache;
 use crate::runner::ShimRunner;
+use crate::versions::VersionsConfig;
 
 /// High-level shim manager for tool managers
 #[derive(Debug, Clone)]
@@ -33,6 +35,14 @@ pub struct ShimInfo {
     pub is_valid: bool,
 }
 
+/// One installed version of a multi-version shim
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct InstalledVersion {
+    pub version: String,
+    pub path: String,
+    pub is_default: bool,
+}
+
 /// Builder for creating shims with a fluent API
 #[derive(Debug, Clone)]
 pub struct ShimBuilder {
@@ -157,11 +167,13 @@ impl ShimBuilder {
                 download_url: self.download_url,
                 source_type: self.source_type,
                 extracted_executables: Vec::new(),
+                ..Default::default()
             },
             args: Default::default(),
             env: self.env,
             metadata: self.metadata,
             auto_update: None,
+            ..Default::default()
         };
 
         config.validate()?;
@@ -240,6 +252,7 @@ impl ShimManager {
         if config_path.exists() {
             fs::remove_file(&config_path)?;
         }
+        ResolutionCache::remove(&config_path);
         if shim_path.exists() {
             fs::remove_file(&shim_path)?;
         }
@@ -360,6 +373,178 @@ impl ShimManager {
         }
     }
 
+    /// Install `version` of the shim's target at `path`
+    ///
+    /// Creates the shim if it does not exist yet. The first installed version
+    /// becomes the default; a plain shim with `metadata.version` keeps its
+    /// current executable as an installed version, and a plain shim without
+    /// one is refused rather than losing that executable.
+    pub fn install_version(
+        &self,
+        name: &str,
+        version: &str,
+        path: impl Into<String>,
+    ) -> Result<PathBuf> {
+        let path = path.into();
+        let config_path = self.config_path(name);
+        if !config_path.exists() {
+            let mut config = self
+                .builder(name)
+                .path(path.clone())
+                .version(version)
+                .build()?;
+            config.versions = Some(VersionsConfig {
+                default: Some(version.to_string()),
+                installed: [(version.to_string(), path)].into(),
+                ..Default::default()
+            });
+            return self.create_shim(config);
+        }
+
+        let mut config = ShimConfig::from_file(&config_path)?;
+        if config.versions.is_none()
+            && config.metadata.version.is_none()
+            && !config.shim.path.is_empty()
+        {
+            return Err(ShimError::Config(format!(
+                "Shim '{}' runs {} without a recorded version; set metadata.version so it can be kept as an installed version",
+                name, config.shim.path
+            )));
+        }
+        let versions = config.versions.get_or_insert_with(|| {
+            let mut versions = VersionsConfig::default();
+            if let Some(ref current) = config.metadata.version {
+                if !config.shim.path.is_empty() {
+                    versions
+                        .installed
+                        .insert(current.clone(), config.shim.path.clone());
+                    versions.default = Some(current.clone());
+                }
+            }
+            versions
+        });
+        versions.installed.insert(version.to_string(), path);
+        if versions.default.is_none() {
+            versions.default = Some(version.to_string());
+            Self::point_at_default(&mut config);
+        }
+
+        self.save_config(&config)?;
+        info!("Installed version {} of shim '{}'", version, name);
+        Ok(self.get_shim_executable_path(name))
+    }
+
+    /// Installed versions of a shim, in version order
+    ///
+    /// Semver versions are ordered by precedence, anything else by name.
+    pub fn list_versions(&self, name: &str) -> Result<Vec<InstalledVersion>> {
+        let config = self.load_config(name)?;
+        let Some(versions) = config.versions else {
+            return Ok(Vec::new());
+        };
+        let mut installed: Vec<InstalledVersion> = versions
+            .installed
+            .iter()
+            .map(|(version, path)| InstalledVersion {
+                version: version.clone(),
+                path: path.clone(),
+                is_default: versions.default.as_ref() == Some(version),
+            })
+            .collect();
+        installed.sort_by(|a, b| crate::versions::compare(&a.version, &b.version));
+        Ok(installed)
+    }
+
+    /// Make `version` the default version of a shim
+    pub fn use_version(&self, name: &str, version: &str) -> Result<()> {
+        let mut config = self.load_config(name)?;
+        let versions = Self::installed_versions(&mut config, name, version)?;
+        versions.default = Some(version.to_string());
+        Self::point_at_default(&mut config);
+
+        self.save_config(&config)?;
+        info!("Shim '{}' now uses version {}", name, version);
+        Ok(())
+    }
+
+    /// Remove an installed version of a shim
+    ///
+    /// Removing the last version removes the shim. The default version can
+    /// only be removed once another version is in use.
+    pub fn uninstall_version(&self, name: &str, version: &str) -> Result<()> {
+        let mut config = self.load_config(name)?;
+        let versions = Self::installed_versions(&mut config, name, version)?;
+        if versions.installed.len() == 1 {
+            return self.remove_shim(name);
+        }
+        if versions.default.as_deref() == Some(version) {
+            return Err(ShimError::Config(format!(
+                "Version {} is the default of shim '{}'; use another version first",
+                version, name
+            )));
+        }
+        versions.installed.remove(version);
+
+        self.save_config(&config)?;
+        info!("Uninstalled version {} of shim '{}'", version, name);
+        Ok(())
+    }
+
+    /// Versions of `config`, failing unless `version` is installed
+    fn installed_versions<'a>(
+        config: &'a mut ShimConfig,
+        name: &str,
+        version: &str,
+    ) -> Result<&'a mut VersionsConfig> {
+        config
+            .versions
+            .as_mut()
+            .filter(|versions| versions.installed.contains_key(version))
+            .ok_or_else(|| {
+                ShimError::Config(format!(
+                    "Version {} of shim '{}' is not installed",
+                    version, name
+                ))
+            })
+    }
+
+    /// Point `shim.path` and `metadata.version` at the default version
+    fn point_at_default(config: &mut ShimConfig) {
+        let Some(ref versions) = config.versions else {
+            return;
+        };
+        let Some(ref default) = versions.default else {
+            return;
+        };
+        if let Some(path) = versions.installed.get(default) {
+            config.shim.path = path.clone();
+            config.metadata.version = Some(default.clone());
+        }
+    }
+
+    /// Configuration file of a shim
+    fn config_path(&self, name: &str) -> PathBuf {
+        self.shim_dir.join(format!("{}.shim.toml", name))
+    }
+
+    /// Load the configuration of an existing shim
+    fn load_config(&self, name: &str) -> Result<ShimConfig> {
+        let config_path = self.config_path(name);
+        if !config_path.exists() {
+            return Err(ShimError::Config(format!("Shim '{}' does not exist", name)));
+        }
+        ShimConfig::from_file(&config_path)
+    }
+
+    /// Write a shim's configuration in place, keeping its executable
+    fn save_config(&self, config: &ShimConfig) -> Result<()> {
+        config.validate()?;
+        let config_path = self.config_path(&config.shim.name);
+        config.to_file(&config_path)?;
+        ResolutionCache::remove(&config_path);
+        Ok(())
+    }
+
     /// Get the path to the shim executable
     fn get_shim_executable_path(&self, name: &str) -> PathBuf {
         if cfg!(windows) {
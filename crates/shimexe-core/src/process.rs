@@ -0,0 +1,383 @@
+//! Helpers for spawning and supervising processes.
+
+use serde::{Deserialize, Serialize};
+use std::io;
+use std::io::IsTerminal;
+use std::process::{Child, Command, ExitStatus, Stdio};
+use std::thread;
+use std::time::{Duration, Instant};
+use tracing::{debug, warn};
+
+use crate::error::{Result, ShimError};
+
+/// Exit code reported when the target is stopped by the shim timeout
+pub const TIMEOUT_EXIT_CODE: i32 = 124;
+
+/// Environment variable overriding the configured timeout (seconds, `0` disables)
+pub const TIMEOUT_ENV_VAR: &str = "SHIMEXE_TIMEOUT";
+
+/// Execution timeout for the target (`[timeout]`)
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TimeoutConfig {
+    /// Seconds the target may run before it is stopped
+    #[serde(alias = "timeout_secs")]
+    pub secs: u64,
+    /// Signal sent first when the timeout fires
+    #[serde(default)]
+    pub signal: KillSignal,
+    /// Seconds to wait after the first signal before sending SIGKILL
+    #[serde(default = "default_grace_secs")]
+    pub grace_secs: u64,
+    /// Run the target in its own process group and signal the whole group
+    /// (default: only when stdin is not a terminal)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub process_group: Option<bool>,
+}
+
+/// First signal sent to a timed-out target
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum KillSignal {
+    /// SIGTERM
+    #[default]
+    Term,
+    /// SIGINT
+    Int,
+    /// SIGKILL right away, without a grace period
+    Kill,
+}
+
+fn default_grace_secs() -> u64 {
+    5
+}
+
+impl TimeoutConfig {
+    /// Create a timeout with the default kill policy
+    pub fn new(secs: u64) -> Self {
+        Self {
+            secs,
+            signal: KillSignal::default(),
+            grace_secs: default_grace_secs(),
+            process_group: None,
+        }
+    }
+
+    /// Resolve the effective timeout, honoring the `SHIMEXE_TIMEOUT` override
+    pub fn effective(configured: Option<&TimeoutConfig>) -> Result<Option<TimeoutConfig>> {
+        Self::with_override(configured, std::env::var(TIMEOUT_ENV_VAR).ok().as_deref())
+    }
+
+    fn with_override(
+        configured: Option<&TimeoutConfig>,
+        value: Option<&str>,
+    ) -> Result<Option<TimeoutConfig>> {
+        let Some(value) = value else {
+            return Ok(configured.filter(|t| t.secs > 0).cloned());
+        };
+
+        let secs: u64 = value.trim().parse().map_err(|_| {
+            ShimError::Config(format!(
+                "{} must be a number of seconds, got '{}'",
+                TIMEOUT_ENV_VAR, value
+            ))
+        })?;
+        if secs == 0 {
+            return Ok(None);
+        }
+
+        let mut timeout = configured
+            .cloned()
+            .unwrap_or_else(|| TimeoutConfig::new(secs));
+        timeout.secs = secs;
+        Ok(Some(timeout))
+    }
+
+    /// Timeout as a duration
+    pub fn duration(&self) -> Duration {
+        Duration::from_secs(self.secs)
+    }
+
+    /// Whether the target gets its own process group
+    pub fn uses_process_group(&self) -> bool {
+        self.process_group
+            .unwrap_or_else(|| !io::stdin().is_terminal())
+    }
+
+    /// Prepare `cmd` so the kill policy can be applied to it
+    pub fn configure(&self, cmd: &mut Command) {
+        #[cfg(unix)]
+        {
+            use std::os::unix::process::CommandExt;
+            if self.uses_process_group() {
+                cmd.process_group(0);
+            }
+        }
+
+        #[cfg(not(unix))]
+        let _ = cmd;
+    }
+
+    /// Wait for `child`, stopping it when the timeout fires
+    ///
+    /// Returns `Ok(None)` when the child had to be stopped.
+    pub fn wait(&self, child: &mut Child) -> io::Result<Option<ExitStatus>> {
+        let group = cfg!(unix) && self.uses_process_group();
+
+        // A child in its own process group no longer receives terminal signals,
+        // so forward them while waiting
+        #[cfg(unix)]
+        let forwarder = if group {
+            Some(signals::Forwarder::register()?)
+        } else {
+            None
+        };
+
+        let deadline = Instant::now() + self.duration();
+        let mut interval = Duration::from_millis(5);
+        let result = loop {
+            if let Some(status) = child.try_wait()? {
+                break Some(status);
+            }
+
+            #[cfg(unix)]
+            if let Some(ref forwarder) = forwarder {
+                forwarder.forward(child.id());
+            }
+
+            let now = Instant::now();
+            if now >= deadline {
+                break None;
+            }
+            thread::sleep(interval.min(deadline - now));
+            interval = (interval * 2).min(Duration::from_millis(50));
+        };
+
+        #[cfg(unix)]
+        drop(forwarder);
+
+        match result {
+            Some(status) => Ok(Some(status)),
+            None => {
+                warn!("Target timed out after {}s, stopping it", self.secs);
+                self.kill(child, group)?;
+                Ok(None)
+            }
+        }
+    }
+
+    /// Apply the kill policy to a timed-out child
+    fn kill(&self, child: &mut Child, group: bool) -> io::Result<()> {
+        #[cfg(unix)]
+        {
+            let pid = child.id();
+            if self.signal != KillSignal::Kill {
+                let signal = match self.signal {
+                    KillSignal::Int => libc::SIGINT,
+                    _ => libc::SIGTERM,
+                };
+                signals::send(pid, group, signal);
+
+                let grace = Duration::from_secs(self.grace_secs);
+                if wait_timeout(child, grace)?.is_some() {
+                    if group {
+                        // Stragglers left in the group still get killed
+                        signals::send(pid, true, libc::SIGKILL);
+                    }
+                    return Ok(());
+                }
+                debug!("Target ignored the first signal, sending SIGKILL");
+            }
+            signals::send(pid, group, libc::SIGKILL);
+        }
+
+        #[cfg(not(unix))]
+        {
+            let _ = group;
+            child.kill()?;
+        }
+
+        child.wait()?;
+        Ok(())
+    }
+}
+
+/// Default shell for command strings (`sh`, or `cmd` on Windows)
+pub fn default_shell() -> &'static str {
+    if cfg!(windows) {
+        "cmd"
+    } else {
+        "sh"
+    }
+}
+
+/// Build a command running `script` through `shell`
+pub fn shell_command(shell: &str, script: &str) -> Command {
+    let mut cmd = Command::new(shell);
+    if cfg!(windows) && shell.eq_ignore_ascii_case("cmd") {
+        cmd.arg("/C").arg(script);
+    } else {
+        cmd.arg("-c").arg(script);
+    }
+    cmd
+}
+
+/// Wait for `child` to exit, giving up after `timeout`
+///
+/// Returns `Ok(None)` when the timeout elapsed; the child is left running.
+pub fn wait_timeout(child: &mut Child, timeout: Duration) -> io::Result<Option<ExitStatus>> {
+    let deadline = Instant::now() + timeout;
+    let mut interval = Duration::from_millis(5);
+
+    loop {
+        if let Some(status) = child.try_wait()? {
+            return Ok(Some(status));
+        }
+
+        let now = Instant::now();
+        if now >= deadline {
+            return Ok(None);
+        }
+
+        thread::sleep(interval.min(deadline - now));
+        interval = (interval * 2).min(Duration::from_millis(100));
+    }
+}
+
+/// A `Stdio` writing to this process's stderr, so helper output never ends up on stdout
+pub fn stderr_stdio() -> Stdio {
+    #[cfg(unix)]
+    {
+        use std::os::fd::AsFd;
+        if let Ok(fd) = io::stderr().as_fd().try_clone_to_owned() {
+            return Stdio::from(fd);
+        }
+    }
+
+    #[cfg(windows)]
+    {
+        use std::os::windows::io::AsHandle;
+        if let Ok(handle) = io::stderr().as_handle().try_clone_to_owned() {
+            return Stdio::from(handle);
+        }
+    }
+
+    Stdio::inherit()
+}
+
+#[cfg(unix)]
+mod signals {
+    use std::io;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Arc;
+
+    use signal_hook::consts::{SIGHUP, SIGINT, SIGQUIT, SIGTERM};
+    use signal_hook::SigId;
+
+    /// Signals forwarded to a target running in its own process group
+    const FORWARDED: &[libc::c_int] = &[SIGINT, SIGTERM, SIGHUP, SIGQUIT];
+
+    /// Send `signal` to a process, or to its whole process group
+    pub(super) fn send(pid: u32, group: bool, signal: libc::c_int) {
+        let pid = pid as libc::pid_t;
+        let target = if group { -pid } else { pid };
+        // SAFETY: kill(2) has no memory safety requirements
+        unsafe {
+            libc::kill(target, signal);
+        }
+    }
+
+    /// Records terminal signals received by the shim so they can be forwarded
+    pub(super) struct Forwarder {
+        pending: Arc<AtomicUsize>,
+        ids: Vec<SigId>,
+    }
+
+    impl Forwarder {
+        pub(super) fn register() -> io::Result<Self> {
+            let pending = Arc::new(AtomicUsize::new(0));
+            let mut ids = Vec::new();
+            for &signal in FORWARDED {
+                ids.push(signal_hook::flag::register_usize(
+                    signal,
+                    Arc::clone(&pending),
+                    signal as usize,
+                )?);
+            }
+            Ok(Self { pending, ids })
+        }
+
+        pub(super) fn forward(&self, pid: u32) {
+            let signal = self.pending.swap(0, Ordering::SeqCst);
+            if signal != 0 {
+                send(pid, true, signal as libc::c_int);
+            }
+        }
+    }
+
+    impl Drop for Forwarder {
+        fn drop(&mut self) {
+            for id in self.ids.drain(..) {
+                signal_hook::low_level::unregister(id);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_shell_command_runs_script() {
+        let status = shell_command(default_shell(), "exit 3").status().unwrap();
+        assert_eq!(status.code(), Some(3));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_wait_timeout() {
+        let mut child = shell_command("sh", "exit 0").spawn().unwrap();
+        let status = wait_timeout(&mut child, Duration::from_secs(10)).unwrap();
+        assert!(status.unwrap().success());
+
+        let mut child = shell_command("sh", "sleep 5").spawn().unwrap();
+        assert!(wait_timeout(&mut child, Duration::from_millis(50))
+            .unwrap()
+            .is_none());
+        child.kill().unwrap();
+        child.wait().unwrap();
+    }
+
+    #[test]
+    fn test_timeout_secs_alias() {
+        let timeout: TimeoutConfig = toml::from_str("timeout_secs = 30").unwrap();
+        assert_eq!(timeout.secs, 30);
+        let timeout: TimeoutConfig = toml::from_str("secs = 30").unwrap();
+        assert_eq!(timeout.secs, 30);
+    }
+
+    #[test]
+    fn test_timeout_env_override() {
+        let configured = TimeoutConfig::new(60);
+        let effective = TimeoutConfig::with_override(Some(&configured), Some("7")).unwrap();
+        assert_eq!(effective.map(|t| t.secs), Some(7));
+        assert_eq!(
+            TimeoutConfig::with_override(None, Some("7"))
+                .unwrap()
+                .map(|t| t.secs),
+            Some(7)
+        );
+
+        assert!(TimeoutConfig::with_override(Some(&configured), Some("0"))
+            .unwrap()
+            .is_none());
+        assert!(TimeoutConfig::with_override(Some(&configured), Some("soon")).is_err());
+
+        assert_eq!(
+            TimeoutConfig::with_override(Some(&configured), None)
+                .unwrap()
+                .map(|t| t.secs),
+            Some(60)
+        );
+    }
+}
@@ -0,0 +1,270 @@
+//! Named profiles (`[profiles.<name>]`).
+//!
+//! A profile is a variant of the same shim, e.g. a `debug` profile adding
+//! `RUST_LOG=trace` and `--verbose`. It is activated per invocation with the
+//! `--shimexe-profile <name>` flag (stripped before the target runs), by
+//! `SHIMEXE_PROFILE`, or by the `profile` of a trusted project file, in that
+//! order.
+
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fmt;
+use std::path::PathBuf;
+use tracing::debug;
+
+use crate::config::{ShimConfig, SourceType};
+use crate::error::{Result, ShimError};
+use crate::project::ProjectMatch;
+use crate::utils::expand_env_vars;
+
+/// Environment variable selecting a profile
+pub const PROFILE_ENV_VAR: &str = "SHIMEXE_PROFILE";
+
+/// Flag selecting a profile, removed from the target's arguments
+pub const PROFILE_FLAG: &str = "--shimexe-profile";
+
+/// Overrides applied when a profile is active
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct Profile {
+    /// Executable replacing `shim.path`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub path: Option<String>,
+    /// Default args replacing `shim.args`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub args: Option<Vec<String>>,
+    /// Args appended to the default args
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub extra_args: Vec<String>,
+    /// Environment variables layered over `[env]`
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub env: HashMap<String, String>,
+    /// Working directory replacing `shim.cwd`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cwd: Option<String>,
+    /// One-line summary of the profile
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+}
+
+/// What activated a profile
+#[derive(Debug, Clone, PartialEq)]
+pub enum ProfileSource {
+    /// The `--shimexe-profile` flag
+    Flag,
+    /// The `SHIMEXE_PROFILE` environment variable
+    Env,
+    /// A project file
+    Project(PathBuf),
+}
+
+impl fmt::Display for ProfileSource {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ProfileSource::Flag => write!(f, "{}", PROFILE_FLAG),
+            ProfileSource::Env => write!(f, "{}", PROFILE_ENV_VAR),
+            ProfileSource::Project(file) => write!(f, "{}", file.display()),
+        }
+    }
+}
+
+/// Profile active for an invocation
+#[derive(Debug, Clone, PartialEq)]
+pub struct ActiveProfile {
+    pub name: String,
+    pub source: ProfileSource,
+}
+
+impl fmt::Display for ActiveProfile {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} (from {})", self.name, self.source)
+    }
+}
+
+impl Profile {
+    /// Expand environment variables in the profile
+    pub fn expand_env_vars(&mut self) -> Result<()> {
+        if let Some(ref mut path) = self.path {
+            *path = expand_env_vars(path)?;
+        }
+        for arg in self.args.iter_mut().flatten().chain(&mut self.extra_args) {
+            *arg = expand_env_vars(arg)?;
+        }
+        for value in self.env.values_mut() {
+            *value = expand_env_vars(value)?;
+        }
+        if let Some(ref mut cwd) = self.cwd {
+            *cwd = expand_env_vars(cwd)?;
+        }
+        Ok(())
+    }
+
+    /// Configuration with the profile applied over `base`
+    pub fn config(&self, base: &ShimConfig) -> ShimConfig {
+        let mut config = base.clone();
+        if let Some(ref path) = self.path {
+            config.shim.path = path.clone();
+            config.shim.source_type = SourceType::File;
+            config.shim.download_url = None;
+            config.shim.extracted_executables.clear();
+        }
+        if let Some(ref args) = self.args {
+            config.shim.args = args.clone();
+        }
+        config.shim.args.extend(self.extra_args.iter().cloned());
+        config
+            .env
+            .extend(self.env.iter().map(|(k, v)| (k.clone(), v.clone())));
+        if self.cwd.is_some() {
+            config.shim.cwd = self.cwd.clone();
+        }
+        config
+    }
+}
+
+/// Validate the profile names of `config`
+pub fn validate(config: &ShimConfig) -> Result<()> {
+    for name in config.profiles.keys() {
+        if name.trim().is_empty() || name.contains(char::is_whitespace) {
+            return Err(ShimError::Config(format!(
+                "Invalid profile name '{}'",
+                name
+            )));
+        }
+    }
+    Ok(())
+}
+
+/// Split `--shimexe-profile <name>` (or `=<name>`) off `args`
+///
+/// Returns the remaining arguments and the last profile named.
+pub fn strip_flag(args: &[String]) -> Result<(Vec<String>, Option<String>)> {
+    let mut rest = Vec::with_capacity(args.len());
+    let mut profile = None;
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        if arg == PROFILE_FLAG {
+            let name = args.next().ok_or_else(|| {
+                ShimError::Config(format!("{} needs a profile name", PROFILE_FLAG))
+            })?;
+            profile = Some(name.clone());
+        } else if let Some(name) = arg
+            .strip_prefix(PROFILE_FLAG)
+            .and_then(|value| value.strip_prefix('='))
+        {
+            profile = Some(name.to_string());
+        } else {
+            rest.push(arg.clone());
+        }
+    }
+    Ok((rest, profile))
+}
+
+/// Profile of `config` selected by `flag`, `SHIMEXE_PROFILE` or the project
+///
+/// An unknown profile named by the flag is an error; one named by the
+/// environment or a project file is ignored, as it may target other shims.
+pub fn select(
+    config: &ShimConfig,
+    flag: Option<&str>,
+    project: Option<&ProjectMatch>,
+) -> Result<Option<ActiveProfile>> {
+    if let Some(name) = flag {
+        if !config.profiles.contains_key(name) {
+            let names: Vec<&str> = config.profiles.keys().map(String::as_str).collect();
+            return Err(ShimError::Config(format!(
+                "Shim '{}' has no profile '{}' (profiles: {})",
+                config.shim.name,
+                name,
+                if names.is_empty() {
+                    "none".to_string()
+                } else {
+                    names.join(", ")
+                }
+            )));
+        }
+        return Ok(Some(ActiveProfile {
+            name: name.to_string(),
+            source: ProfileSource::Flag,
+        }));
+    }
+
+    let requested = std::env::var(PROFILE_ENV_VAR)
+        .ok()
+        .filter(|name| !name.trim().is_empty())
+        .map(|name| ActiveProfile {
+            name: name.trim().to_string(),
+            source: ProfileSource::Env,
+        })
+        .or_else(|| {
+            let project = project?;
+            Some(ActiveProfile {
+                name: project.overrides.profile.clone()?,
+                source: ProfileSource::Project(project.file.clone()),
+            })
+        });
+
+    Ok(requested.filter(|active| {
+        let known = config.profiles.contains_key(&active.name);
+        if !known {
+            debug!(
+                "Shim '{}' has no profile '{}' requested by {}",
+                config.shim.name, active.name, active.source
+            );
+        }
+        known
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn args(args: &[&str]) -> Vec<String> {
+        args.iter().map(|s| s.to_string()).collect()
+    }
+
+    #[test]
+    fn test_strip_flag() {
+        let (rest, profile) =
+            strip_flag(&args(&["build", "--shimexe-profile", "debug", "-q"])).unwrap();
+        assert_eq!(rest, args(&["build", "-q"]));
+        assert_eq!(profile.as_deref(), Some("debug"));
+
+        let (rest, profile) = strip_flag(&args(&["--shimexe-profile=release"])).unwrap();
+        assert!(rest.is_empty());
+        assert_eq!(profile.as_deref(), Some("release"));
+
+        assert!(strip_flag(&args(&["--shimexe-profile"])).is_err());
+    }
+
+    #[test]
+    fn test_profile_config() {
+        let mut base = ShimConfig::new("tool", "tool");
+        base.shim.args = vec!["run".to_string()];
+        let profile = Profile {
+            extra_args: vec!["--verbose".to_string()],
+            env: HashMap::from([("RUST_LOG".to_string(), "trace".to_string())]),
+            cwd: Some("/tmp".to_string()),
+            ..Default::default()
+        };
+
+        let config = profile.config(&base);
+        assert_eq!(config.shim.args, vec!["run", "--verbose"]);
+        assert_eq!(config.env["RUST_LOG"], "trace");
+        assert_eq!(config.shim.cwd.as_deref(), Some("/tmp"));
+        assert_eq!(config.shim.path, "tool");
+    }
+
+    #[test]
+    fn test_unknown_flag_profile_is_an_error() {
+        let mut config = ShimConfig::new("tool", "tool");
+        config
+            .profiles
+            .insert("debug".to_string(), Profile::default());
+
+        let active = select(&config, Some("debug"), None).unwrap().unwrap();
+        assert_eq!(active.source, ProfileSource::Flag);
+        let err = select(&config, Some("fast"), None).unwrap_err();
+        assert!(err.to_string().contains("profiles: debug"), "{}", err);
+    }
+}
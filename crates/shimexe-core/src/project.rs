@@ -0,0 +1,294 @@
+//! Project-local shim overrides (`.shimexe.toml`).
+//!
+//! A repository can pin the tools its shims run: the runner walks up from the
+//! working directory to the nearest project file, and if that file has a
+//! section named after the running shim, its `path`, `version`, `args` and
+//! `env` are merged over the global configuration. For shims with several
+//! installed versions, `version` selects the one to run. Project files are only
+//! applied once they have been approved with `shimexe trust`.
+
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+use std::path::{Path, PathBuf};
+use tracing::{debug, warn};
+
+use crate::config::{ShimConfig, SourceType};
+use crate::error::{Result, ShimError};
+use crate::trust::{report_untrusted, TrustStore};
+use crate::utils::expand_env_vars;
+
+/// Default name of the project file
+pub const DEFAULT_PROJECT_FILE: &str = ".shimexe.toml";
+
+/// How the project file is discovered (`[project]`)
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ProjectConfig {
+    /// Look for a project file at all
+    #[serde(default = "default_true")]
+    pub enabled: bool,
+    /// Name of the project file
+    #[serde(default = "default_file_name")]
+    pub file_name: String,
+    /// Do not look above the home directory
+    #[serde(default = "default_true")]
+    pub stop_at_home: bool,
+    /// How many parent directories to search above the working directory
+    #[serde(default = "default_max_depth")]
+    pub max_depth: usize,
+}
+
+fn default_true() -> bool {
+    true
+}
+
+fn default_file_name() -> String {
+    DEFAULT_PROJECT_FILE.to_string()
+}
+
+fn default_max_depth() -> usize {
+    32
+}
+
+impl Default for ProjectConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            file_name: default_file_name(),
+            stop_at_home: true,
+            max_depth: default_max_depth(),
+        }
+    }
+}
+
+impl ProjectConfig {
+    /// Validate the project file name
+    pub fn validate(&self) -> Result<()> {
+        if self.file_name.trim().is_empty() || self.file_name.contains(['/', '\\']) {
+            return Err(ShimError::Config(format!(
+                "project.file_name must be a plain file name, got '{}'",
+                self.file_name
+            )));
+        }
+        Ok(())
+    }
+
+    /// Nearest project file at or above `start`
+    pub fn find(&self, start: &Path) -> Option<PathBuf> {
+        self.ancestors(start)
+            .map(|dir| dir.join(&self.file_name))
+            .find(|candidate| candidate.is_file())
+    }
+
+    /// Directories searched from `start`, nearest first
+    ///
+    /// Honors `max_depth` and `stop_at_home`.
+    pub fn ancestors<'a>(&self, start: &'a Path) -> impl Iterator<Item = &'a Path> {
+        let home = if self.stop_at_home {
+            dirs::home_dir()
+        } else {
+            None
+        };
+        let mut done = false;
+        start
+            .ancestors()
+            .take(self.max_depth + 1)
+            .take_while(move |dir| {
+                let searched = !done;
+                done = done || home.as_deref() == Some(*dir);
+                searched
+            })
+    }
+}
+
+/// Overrides for one shim in a project file
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct ProjectOverride {
+    /// Executable replacing `shim.path` (relative paths resolve against the project)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub path: Option<String>,
+    /// Version of the tool the project uses (selects from `[versions]` when present)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub version: Option<String>,
+    /// Default args replacing `shim.args`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub args: Option<Vec<String>>,
+    /// Environment variables layered over `[env]`
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub env: HashMap<String, String>,
+    /// Profile of the shim activated in the project
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub profile: Option<String>,
+}
+
+/// A parsed project file: one section per shim name
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct ProjectFile {
+    pub shims: BTreeMap<String, ProjectOverride>,
+}
+
+impl ProjectFile {
+    /// Parse the project file at `path`
+    pub fn from_file<P: AsRef<Path>>(path: P) -> Result<Self> {
+        let content = std::fs::read_to_string(path.as_ref())?;
+        toml::from_str(&content).map_err(|e| {
+            ShimError::Config(format!(
+                "Invalid project file {}: {}",
+                path.as_ref().display(),
+                e
+            ))
+        })
+    }
+}
+
+impl ProjectOverride {
+    /// Merge the overrides into `config`; `project_dir` anchors relative paths
+    pub fn apply(&self, config: &mut ShimConfig, project_dir: &Path) -> Result<()> {
+        if let Some(ref path) = self.path {
+            let path = expand_env_vars(path)?;
+            config.shim.path = if Path::new(&path).is_relative() && path.contains(['/', '\\']) {
+                project_dir.join(&path).to_string_lossy().to_string()
+            } else {
+                path
+            };
+            config.shim.source_type = SourceType::File;
+            config.shim.download_url = None;
+            config.shim.extracted_executables.clear();
+        }
+        // Multi-version shims select their executable from the version instead
+        if let Some(version) = self.version.as_ref().filter(|_| config.versions.is_none()) {
+            config.metadata.version = Some(version.clone());
+        }
+        if let Some(ref args) = self.args {
+            config.shim.args = args.clone();
+        }
+        config
+            .env
+            .extend(self.env.iter().map(|(k, v)| (k.clone(), v.clone())));
+        Ok(())
+    }
+}
+
+/// Section of a trusted project file for one shim
+#[derive(Debug, Clone, PartialEq)]
+pub struct ProjectMatch {
+    /// Project file the section was read from
+    pub file: PathBuf,
+    pub overrides: ProjectOverride,
+}
+
+impl ProjectMatch {
+    /// Version requested by the project, with the file requesting it
+    pub fn version(&self) -> Option<(&str, &Path)> {
+        self.overrides
+            .version
+            .as_deref()
+            .map(|version| (version, self.file.as_path()))
+    }
+
+    /// Merge the overrides into `config`
+    pub fn apply(&self, config: &mut ShimConfig) -> Result<()> {
+        debug!(
+            "Applying project overrides for '{}' from {}",
+            config.shim.name,
+            self.file.display()
+        );
+        let project_dir = self.file.parent().unwrap_or(Path::new("."));
+        self.overrides.apply(config, project_dir)
+    }
+}
+
+/// Section for `config`'s shim in the project file found from `start`
+///
+/// Untrusted project files are reported and ignored without being parsed, and
+/// trusted files that fail to parse are skipped with a warning.
+pub fn find(config: &ShimConfig, start: &Path) -> Result<Option<ProjectMatch>> {
+    let settings = config.project.clone().unwrap_or_default();
+    if !settings.enabled {
+        return Ok(None);
+    }
+    let Some(file) = settings.find(start) else {
+        return Ok(None);
+    };
+
+    if let Err(e) = TrustStore::load()?.require(&file, "may override shims") {
+        report_untrusted(&e);
+        return Ok(None);
+    }
+
+    let mut project = match ProjectFile::from_file(&file) {
+        Ok(project) => project,
+        Err(e) => {
+            warn!("Ignoring project file {}: {}", file.display(), e);
+            return Ok(None);
+        }
+    };
+    Ok(project
+        .shims
+        .remove(&config.shim.name)
+        .map(|overrides| ProjectMatch { file, overrides }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_find_walks_up_to_max_depth() {
+        let dir = tempfile::tempdir().unwrap();
+        let nested = dir.path().join("a").join("b");
+        std::fs::create_dir_all(&nested).unwrap();
+        let file = dir.path().join(DEFAULT_PROJECT_FILE);
+        std::fs::write(&file, "").unwrap();
+
+        let settings = ProjectConfig::default();
+        assert_eq!(settings.find(&nested), Some(file));
+
+        let shallow = ProjectConfig {
+            max_depth: 1,
+            ..Default::default()
+        };
+        assert_eq!(shallow.find(&nested), None);
+    }
+
+    #[test]
+    fn test_override_merges_over_config() {
+        let project: ProjectFile = toml::from_str(
+            r#"
+[node]
+path = "tools/node"
+version = "18.20.0"
+args = ["--no-warnings"]
+
+[node.env]
+NODE_ENV = "development"
+"#,
+        )
+        .unwrap();
+
+        let mut config = ShimConfig::new("node", "node");
+        config.shim.args = vec!["--global".to_string()];
+        config.env.insert("A".to_string(), "1".to_string());
+        project.shims["node"]
+            .apply(&mut config, Path::new("/work/app"))
+            .unwrap();
+
+        assert_eq!(
+            PathBuf::from(&config.shim.path),
+            Path::new("/work/app").join("tools/node")
+        );
+        assert_eq!(config.metadata.version.as_deref(), Some("18.20.0"));
+        assert_eq!(config.shim.args, vec!["--no-warnings"]);
+        assert_eq!(config.env["NODE_ENV"], "development");
+        assert_eq!(config.env["A"], "1");
+    }
+
+    #[test]
+    fn test_validate_file_name() {
+        let settings = ProjectConfig {
+            file_name: "../.shimexe.toml".to_string(),
+            ..Default::default()
+        };
+        assert!(settings.validate().is_err());
+    }
+}
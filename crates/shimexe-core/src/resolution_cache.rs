@@ -0,0 +1,249 @@
+//! Persistent resolution cache (`<name>.shim.cache`).
+//!
+//! Every shim invocation is a fresh process, so in-memory caches never hit.
+//! This cache stores the parsed configuration and the resolved executable next
+//! to the shim file. It is keyed on the shim file's mtime and size, the `PATH`
+//! and the shimexe version, and the cached executable is only used while it
+//! still exists. Set `SHIMEXE_NO_CACHE=1` to bypass it.
+
+use serde::{Deserialize, Serialize};
+use std::collections::hash_map::DefaultHasher;
+use std::hash::{Hash, Hasher};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::time::UNIX_EPOCH;
+use tracing::debug;
+
+use crate::config::ShimConfig;
+use crate::error::{Result, ShimError};
+
+/// Environment variable disabling the resolution cache
+pub const NO_CACHE_ENV_VAR: &str = "SHIMEXE_NO_CACHE";
+
+/// What a cache entry is valid for
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+struct CacheKey {
+    shimexe_version: String,
+    config_mtime_nanos: u128,
+    config_size: u64,
+    path_hash: u64,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct CacheFile {
+    key: CacheKey,
+    config: ShimConfig,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    executable: Option<CachedExecutable>,
+}
+
+/// Executable resolved for a given (expanded) `shim.path`
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+struct CachedExecutable {
+    shim_path: String,
+    resolved: PathBuf,
+}
+
+/// Resolution cache for one shim file
+#[derive(Debug, Clone)]
+pub struct ResolutionCache {
+    cache_file: PathBuf,
+    key: CacheKey,
+    /// Configuration as loaded from the shim file, before any runtime changes
+    config: ShimConfig,
+    executable: Option<CachedExecutable>,
+}
+
+impl ResolutionCache {
+    /// Cache file used for `shim_file`
+    pub fn cache_path(shim_file: &Path) -> PathBuf {
+        let name = shim_file
+            .file_name()
+            .map(|n| n.to_string_lossy())
+            .unwrap_or_default();
+        let stem = name.strip_suffix(".toml").unwrap_or(&name);
+        shim_file.with_file_name(format!("{}.cache", stem))
+    }
+
+    /// Whether the cache is disabled through the environment
+    pub fn is_disabled() -> bool {
+        std::env::var(NO_CACHE_ENV_VAR).is_ok_and(|v| !v.is_empty() && v != "0")
+    }
+
+    /// Load the configuration of `shim_file`, from the cache when it is fresh
+    pub fn load(shim_file: &Path) -> Result<(ShimConfig, Option<ResolutionCache>)> {
+        if Self::is_disabled() {
+            return Ok((ShimConfig::from_file(shim_file)?, None));
+        }
+
+        let key = match CacheKey::for_file(shim_file) {
+            Ok(key) => key,
+            Err(_) => return Ok((ShimConfig::from_file(shim_file)?, None)),
+        };
+        let cache_file = Self::cache_path(shim_file);
+
+        if let Some(entry) = read_entry(&cache_file).filter(|entry| entry.key == key) {
+            debug!("Using cached resolution {}", cache_file.display());
+            let cache = Self {
+                cache_file,
+                key,
+                config: entry.config.clone(),
+                executable: entry.executable,
+            };
+            return Ok((entry.config, Some(cache)));
+        }
+
+        let config = ShimConfig::from_file(shim_file)?;
+        let cache = Self {
+            cache_file,
+            key,
+            config: config.clone(),
+            executable: None,
+        };
+        cache.store();
+        Ok((config, Some(cache)))
+    }
+
+    /// Cached executable for the expanded `shim_path`, if it still exists
+    pub fn executable(&self, shim_path: &str) -> Option<&Path> {
+        let cached = self.executable.as_ref()?;
+        if cached.shim_path != shim_path {
+            return None;
+        }
+        if !cached.resolved.is_file() {
+            debug!(
+                "Cached executable {} is gone, resolving again",
+                cached.resolved.display()
+            );
+            return None;
+        }
+        Some(&cached.resolved)
+    }
+
+    /// Remember the executable resolved for `shim_path`
+    pub fn set_executable(&mut self, shim_path: &str, resolved: &Path) {
+        let executable = CachedExecutable {
+            shim_path: shim_path.to_string(),
+            resolved: resolved.to_path_buf(),
+        };
+        if self.executable.as_ref() == Some(&executable) {
+            return;
+        }
+        self.executable = Some(executable);
+        self.store();
+    }
+
+    /// Remove the cache file belonging to `shim_file`
+    pub fn remove(shim_file: &Path) {
+        let _ = std::fs::remove_file(Self::cache_path(shim_file));
+    }
+
+    /// Write the cache file, ignoring failures (the cache is an optimization)
+    fn store(&self) {
+        let entry = CacheFile {
+            key: self.key.clone(),
+            config: self.config.clone(),
+            executable: self.executable.clone(),
+        };
+        if let Err(e) = write_entry(&self.cache_file, &entry) {
+            debug!(
+                "Failed to write resolution cache {}: {}",
+                self.cache_file.display(),
+                e
+            );
+        }
+    }
+}
+
+impl CacheKey {
+    fn for_file(shim_file: &Path) -> std::io::Result<Self> {
+        let metadata = std::fs::metadata(shim_file)?;
+        let mtime = metadata
+            .modified()?
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_nanos())
+            .unwrap_or_default();
+
+        let mut hasher = DefaultHasher::new();
+        std::env::var_os("PATH").hash(&mut hasher);
+
+        Ok(Self {
+            shimexe_version: env!("CARGO_PKG_VERSION").to_string(),
+            config_mtime_nanos: mtime,
+            config_size: metadata.len(),
+            path_hash: hasher.finish(),
+        })
+    }
+}
+
+fn read_entry(cache_file: &Path) -> Option<CacheFile> {
+    let content = std::fs::read(cache_file).ok()?;
+    serde_json::from_slice(&content).ok()
+}
+
+fn write_entry(cache_file: &Path, entry: &CacheFile) -> Result<()> {
+    let content = serde_json::to_vec(entry)
+        .map_err(|e| ShimError::Config(format!("Failed to serialize cache: {}", e)))?;
+    let dir = cache_file.parent().unwrap_or_else(|| Path::new("."));
+
+    // Write atomically so concurrent shims never read a partial file
+    let mut temp = tempfile::NamedTempFile::new_in(dir)?;
+    temp.write_all(&content)?;
+    temp.persist(cache_file)
+        .map_err(|e| ShimError::Io(e.error))?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn write_shim(dir: &Path, path: &str) -> PathBuf {
+        let shim_file = dir.join("tool.shim.toml");
+        std::fs::write(
+            &shim_file,
+            format!("[shim]\nname = \"tool\"\npath = \"{}\"\n", path),
+        )
+        .unwrap();
+        shim_file
+    }
+
+    #[test]
+    fn test_cache_path() {
+        assert_eq!(
+            ResolutionCache::cache_path(Path::new("/shims/tool.shim.toml")),
+            Path::new("/shims/tool.shim.cache")
+        );
+    }
+
+    #[test]
+    fn test_cache_roundtrip_and_invalidation() {
+        let dir = tempfile::tempdir().unwrap();
+        let shim_file = write_shim(dir.path(), "tool");
+        let exe = dir.path().join("tool-bin");
+        std::fs::write(&exe, "").unwrap();
+
+        let (_, cache) = ResolutionCache::load(&shim_file).unwrap();
+        let mut cache = cache.unwrap();
+        assert!(ResolutionCache::cache_path(&shim_file).exists());
+        assert!(cache.executable("tool").is_none());
+        cache.set_executable("tool", &exe);
+
+        // A fresh load sees the executable
+        let (_, cache) = ResolutionCache::load(&shim_file).unwrap();
+        let cache = cache.unwrap();
+        assert_eq!(cache.executable("tool"), Some(exe.as_path()));
+        assert!(cache.executable("other").is_none());
+
+        // The target disappearing invalidates the executable
+        std::fs::remove_file(&exe).unwrap();
+        assert!(cache.executable("tool").is_none());
+
+        // Editing the shim file invalidates the whole entry
+        std::fs::write(&exe, "").unwrap();
+        write_shim(dir.path(), "tool-renamed");
+        let (config, cache) = ResolutionCache::load(&shim_file).unwrap();
+        assert_eq!(config.shim.path, "tool-renamed");
+        assert!(cache.unwrap().executable("tool").is_none());
+    }
+}
@@ -0,0 +1,144 @@
+//! Retry policy for flaky targets (`[retry]`).
+//!
+//! A target exiting with one of `on_exit_codes` is started again, up to
+//! `attempts` runs in total. Re-running is only safe when the first run could
+//! not have consumed input meant for it, so retries are disabled when stdin is
+//! not a terminal unless `allow_piped_stdin` is set.
+
+use serde::{Deserialize, Serialize};
+use std::io::IsTerminal;
+use std::time::Duration;
+
+use crate::error::{Result, ShimError};
+
+/// Retry settings for the target executable
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RetryConfig {
+    /// Total number of runs, including the first one
+    #[serde(default = "default_attempts")]
+    pub attempts: u32,
+    /// Exit codes that trigger another run
+    pub on_exit_codes: Vec<i32>,
+    /// How the delay grows between runs
+    #[serde(default)]
+    pub backoff: Backoff,
+    /// Delay before the first retry, in milliseconds
+    #[serde(default = "default_delay_ms")]
+    pub delay_ms: u64,
+    /// Upper bound for the delay, in milliseconds
+    #[serde(default = "default_max_delay_ms")]
+    pub max_delay_ms: u64,
+    /// Retry even when stdin is a pipe or file the target may have consumed
+    #[serde(default)]
+    pub allow_piped_stdin: bool,
+}
+
+/// Delay growth between retries
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Backoff {
+    /// Same delay before every retry
+    Fixed,
+    /// Delay doubles after every retry
+    #[default]
+    Exponential,
+}
+
+fn default_attempts() -> u32 {
+    3
+}
+
+fn default_delay_ms() -> u64 {
+    1000
+}
+
+fn default_max_delay_ms() -> u64 {
+    30_000
+}
+
+impl RetryConfig {
+    /// Create a retry policy with default timing
+    pub fn new(attempts: u32, on_exit_codes: Vec<i32>) -> Self {
+        Self {
+            attempts,
+            on_exit_codes,
+            backoff: Backoff::default(),
+            delay_ms: default_delay_ms(),
+            max_delay_ms: default_max_delay_ms(),
+            allow_piped_stdin: false,
+        }
+    }
+
+    /// Validate the retry settings
+    pub fn validate(&self) -> Result<()> {
+        if self.attempts == 0 {
+            return Err(ShimError::Config(
+                "retry.attempts must be at least 1".to_string(),
+            ));
+        }
+        if self.on_exit_codes.contains(&0) {
+            return Err(ShimError::Config(
+                "retry.on_exit_codes must not contain 0".to_string(),
+            ));
+        }
+        Ok(())
+    }
+
+    /// Whether retrying is safe for the current stdin
+    pub fn stdin_allows_retry(&self) -> bool {
+        self.allow_piped_stdin || std::io::stdin().is_terminal()
+    }
+
+    /// Whether `exit_code` of run number `attempt` (1-based) should be retried
+    pub fn should_retry(&self, attempt: u32, exit_code: i32) -> bool {
+        attempt < self.attempts && self.on_exit_codes.contains(&exit_code)
+    }
+
+    /// Delay before run number `attempt + 1`
+    pub fn delay(&self, attempt: u32) -> Duration {
+        let delay_ms = match self.backoff {
+            Backoff::Fixed => self.delay_ms,
+            Backoff::Exponential => {
+                let factor = 1u64 << attempt.saturating_sub(1).min(32);
+                self.delay_ms.saturating_mul(factor)
+            }
+        };
+        Duration::from_millis(delay_ms.min(self.max_delay_ms))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_should_retry() {
+        let retry = RetryConfig::new(3, vec![75, 1]);
+        assert!(retry.should_retry(1, 75));
+        assert!(retry.should_retry(2, 1));
+        assert!(!retry.should_retry(3, 75));
+        assert!(!retry.should_retry(1, 2));
+    }
+
+    #[test]
+    fn test_backoff_delays() {
+        let mut retry = RetryConfig::new(10, vec![1]);
+        retry.delay_ms = 100;
+        retry.max_delay_ms = 500;
+
+        assert_eq!(retry.delay(1), Duration::from_millis(100));
+        assert_eq!(retry.delay(2), Duration::from_millis(200));
+        assert_eq!(retry.delay(3), Duration::from_millis(400));
+        assert_eq!(retry.delay(4), Duration::from_millis(500));
+
+        retry.backoff = Backoff::Fixed;
+        assert_eq!(retry.delay(4), Duration::from_millis(100));
+    }
+
+    #[test]
+    fn test_validate() {
+        assert!(RetryConfig::new(3, vec![1]).validate().is_ok());
+        assert!(RetryConfig::new(0, vec![1]).validate().is_err());
+        assert!(RetryConfig::new(3, vec![0]).validate().is_err());
+    }
+}
@@ -0,0 +1,224 @@
+//! Argument rewriting rules (`[[args.rewrite]]`).
+//!
+//! Rules translate the user's arguments before they reach the target, e.g.
+//! renaming `--old-flag` to `--new-flag` after a tool upgrade, dropping flags
+//! the new version rejects, or injecting a flag next to another one. Rules are
+//! applied in order, each to the output of the previous one.
+
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use tracing::debug;
+
+use crate::error::{Result, ShimError};
+
+/// One rewrite rule, applied to every argument matching `match`
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct RewriteRule {
+    /// Regex matched against each argument
+    #[serde(rename = "match")]
+    pub pattern: String,
+    /// Replacement for the matched text (`$1` refers to capture groups)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub replace: Option<String>,
+    /// Remove matching arguments
+    #[serde(default)]
+    pub drop: bool,
+    /// Arguments inserted before each matching argument
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub insert_before: Vec<String>,
+    /// Arguments inserted after each matching argument
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub insert_after: Vec<String>,
+    /// Only apply the rule when some argument matches this regex
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub if_present: Option<String>,
+    /// Only apply the rule when no argument matches this regex
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub if_absent: Option<String>,
+}
+
+/// A rule with its patterns compiled
+struct CompiledRule<'a> {
+    rule: &'a RewriteRule,
+    pattern: Regex,
+    if_present: Option<Regex>,
+    if_absent: Option<Regex>,
+}
+
+fn compile(pattern: &str, field: &str) -> Result<Regex> {
+    Regex::new(pattern).map_err(|e| {
+        ShimError::Config(format!(
+            "Invalid args.rewrite {} '{}': {}",
+            field, pattern, e
+        ))
+    })
+}
+
+impl RewriteRule {
+    /// Validate the patterns and actions of the rule
+    pub fn validate(&self) -> Result<()> {
+        self.compile().map(|_| ())
+    }
+
+    fn compile(&self) -> Result<CompiledRule<'_>> {
+        if self.drop && (self.replace.is_some() || !self.insert_before.is_empty()) {
+            return Err(ShimError::Config(format!(
+                "args.rewrite rule '{}' cannot both drop and replace or insert before",
+                self.pattern
+            )));
+        }
+        if !self.drop
+            && self.replace.is_none()
+            && self.insert_before.is_empty()
+            && self.insert_after.is_empty()
+        {
+            return Err(ShimError::Config(format!(
+                "args.rewrite rule '{}' needs replace, drop, insert_before or insert_after",
+                self.pattern
+            )));
+        }
+
+        Ok(CompiledRule {
+            rule: self,
+            pattern: compile(&self.pattern, "match")?,
+            if_present: self
+                .if_present
+                .as_deref()
+                .map(|p| compile(p, "if_present"))
+                .transpose()?,
+            if_absent: self
+                .if_absent
+                .as_deref()
+                .map(|p| compile(p, "if_absent"))
+                .transpose()?,
+        })
+    }
+}
+
+impl CompiledRule<'_> {
+    fn applies_to(&self, args: &[String]) -> bool {
+        let any_match = |regex: &Regex| args.iter().any(|arg| regex.is_match(arg));
+        self.if_present.as_ref().is_none_or(any_match)
+            && !self.if_absent.as_ref().is_some_and(any_match)
+    }
+
+    fn apply(&self, args: Vec<String>) -> Vec<String> {
+        if !self.applies_to(&args) {
+            return args;
+        }
+
+        let mut result = Vec::with_capacity(args.len());
+        for arg in args {
+            if !self.pattern.is_match(&arg) {
+                result.push(arg);
+                continue;
+            }
+            // Dropping still honors insert_after, so a flag can be swapped for several
+            if !self.rule.drop {
+                result.extend(self.rule.insert_before.iter().cloned());
+                result.push(match self.rule.replace {
+                    Some(ref replace) => self.pattern.replace_all(&arg, replace).into_owned(),
+                    None => arg,
+                });
+            }
+            result.extend(self.rule.insert_after.iter().cloned());
+        }
+        result
+    }
+}
+
+/// Apply `rules` in order to `args`
+pub fn apply(rules: &[RewriteRule], args: &[String]) -> Result<Vec<String>> {
+    let mut result = args.to_vec();
+    for rule in rules {
+        result = rule.compile()?.apply(result);
+    }
+    if result != args {
+        debug!("Rewrote arguments {:?} to {:?}", args, result);
+    }
+    Ok(result)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rules(toml_str: &str) -> Vec<RewriteRule> {
+        #[derive(Deserialize)]
+        struct Wrapper {
+            rewrite: Vec<RewriteRule>,
+        }
+        let rules = toml::from_str::<Wrapper>(toml_str).unwrap().rewrite;
+        for rule in &rules {
+            rule.validate().unwrap();
+        }
+        rules
+    }
+
+    fn args(args: &[&str]) -> Vec<String> {
+        args.iter().map(|s| s.to_string()).collect()
+    }
+
+    #[test]
+    fn test_replace_drop_and_insert() {
+        let rules = rules(
+            r#"
+[[rewrite]]
+match = "^--old-(.*)"
+replace = "--new-$1"
+
+[[rewrite]]
+match = "^--legacy$"
+drop = true
+
+[[rewrite]]
+match = "^build$"
+insert_after = ["--locked"]
+"#,
+        );
+
+        assert_eq!(
+            apply(&rules, &args(&["build", "--old-flag", "--legacy", "x"])).unwrap(),
+            args(&["build", "--locked", "--new-flag", "x"])
+        );
+    }
+
+    #[test]
+    fn test_conditions() {
+        let rules = rules(
+            r#"
+[[rewrite]]
+match = "^run$"
+insert_before = ["--quiet"]
+if_present = "^--ci$"
+if_absent = "^--verbose$"
+"#,
+        );
+
+        assert_eq!(
+            apply(&rules, &args(&["run", "--ci"])).unwrap(),
+            args(&["--quiet", "run", "--ci"])
+        );
+        assert_eq!(
+            apply(&rules, &args(&["run", "--ci", "--verbose"])).unwrap(),
+            args(&["run", "--ci", "--verbose"])
+        );
+        assert_eq!(apply(&rules, &args(&["run"])).unwrap(), args(&["run"]));
+    }
+
+    #[test]
+    fn test_validate_rejects_bad_rules() {
+        let no_action = RewriteRule {
+            pattern: "x".to_string(),
+            ..Default::default()
+        };
+        assert!(no_action.validate().is_err());
+
+        let bad_regex = RewriteRule {
+            pattern: "(".to_string(),
+            drop: true,
+            ..Default::default()
+        };
+        assert!(bad_regex.validate().is_err());
+    }
+}
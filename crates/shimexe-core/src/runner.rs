@@ -4,11 +4,82 @@ use std::sync::{Arc, Mutex, OnceLock};
 use std::time::{Duration, SystemTime};
 use tracing::{debug, info, warn};
 
-use crate::config::ShimConfig;
+use crate::alias;
+use crate::concurrency::InstanceGuard;
+use crate::config::{ShimConfig, SourceType};
+use crate::dispatch::Dispatch;
+use crate::dotenv::load_env_files;
 use crate::downloader::Downloader;
+use crate::env_from::EnvDiff;
 use crate::error::{Result, ShimError};
+use crate::exit::OutputWatch;
+use crate::explain::{EnvChange, Explanation};
+use crate::history::{History, HistoryRecord};
+use crate::hooks::{HookContext, HookStage};
+use crate::interpreter::ResolvedInterpreter;
+use crate::logging::OutputTee;
+use crate::process::{TimeoutConfig, TIMEOUT_EXIT_CODE};
+use crate::profiles::{self, ActiveProfile};
+use crate::project::{self, ProjectMatch};
+use crate::resolution_cache::ResolutionCache;
+use crate::template::TemplateEngine;
+use crate::timing::{self, stage};
+use crate::trust::report_untrusted;
 use crate::updater::ShimUpdater;
-use crate::utils::get_builtin_env_vars;
+use crate::utils::{expand_env_vars, get_builtin_env_vars};
+use crate::versions::{self, SelectedVersion};
+use crate::wasm::{self, WasmInvocation, WasmModule};
+
+/// Default shim directory (`~/.shimexe`)
+fn default_shim_dir() -> Result<PathBuf> {
+    Ok(dirs::home_dir()
+        .ok_or_else(|| ShimError::Config("Could not determine home directory".to_string()))?
+        .join(".shimexe"))
+}
+
+/// Apply the trusted project overrides found from the working directory and
+/// select the version of multi-version shims
+///
+/// Returns the applied project section and the selected version.
+fn apply_overrides(
+    config: &mut ShimConfig,
+) -> Result<(Option<ProjectMatch>, Option<SelectedVersion>)> {
+    let cwd = std::env::current_dir().ok();
+    let project = match cwd {
+        Some(ref cwd) => project::find(config, cwd)?,
+        None => None,
+    };
+
+    // The project file wins over version files such as .tool-versions
+    let version_file = match (&config.versions, &cwd) {
+        (Some(versions), Some(cwd))
+            if project.as_ref().and_then(ProjectMatch::version).is_none() =>
+        {
+            let discovery = config.project.clone().unwrap_or_default();
+            versions.find_version_file(&config.shim.name, cwd, &discovery)
+        }
+        _ => None,
+    };
+    let requested = project
+        .as_ref()
+        .and_then(ProjectMatch::version)
+        .or(version_file
+            .as_ref()
+            .map(|(version, file)| (version.as_str(), file.as_path())));
+    let version = versions::apply(config, requested)?;
+    if let Some(ref project) = project {
+        project.apply(config)?;
+    }
+    Ok((project, version))
+}
+
+/// How the target is run
+enum Target {
+    /// A native executable or script started as a child process
+    Process(Command),
+    /// A WebAssembly module run in the embedded WASI runtime
+    Wasm(WasmModule, WasmInvocation),
+}
 
 /// Cache entry for executable validation results
 #[derive(Debug, Clone)]
@@ -76,38 +147,438 @@ fn get_executable_cache() -> &'static ExecutableCache {
 pub struct ShimRunner {
     config: ShimConfig,
     shim_file_path: Option<PathBuf>,
+    /// Project section whose overrides were applied
+    project: Option<ProjectMatch>,
+    /// Version selected for a multi-version shim
+    version: Option<SelectedVersion>,
+    /// Profile applied to this runner's configuration
+    profile: Option<ActiveProfile>,
+    history: Option<History>,
+    resolution_cache: Mutex<Option<ResolutionCache>>,
 }
 
 impl ShimRunner {
     /// Create a new shim runner from a configuration file
     pub fn from_file<P: AsRef<Path>>(shim_file: P) -> Result<Self> {
-        let mut config = ShimConfig::from_file(&shim_file)?;
-        config.expand_env_vars()?;
+        let (mut config, resolution_cache) = timing::measure(stage::TOML_PARSE, || {
+            ResolutionCache::load(shim_file.as_ref())
+        })?;
+        if let Some(shim_dir) = shim_file.as_ref().parent() {
+            config = alias::resolve(config, shim_dir)?;
+        }
+        let (project, version) = apply_overrides(&mut config)?;
+        timing::measure(stage::ENV_EXPANSION, || config.expand_env_vars())?;
+        let history = History::from_env(config.history.as_ref());
 
         Ok(Self {
             config,
             shim_file_path: Some(shim_file.as_ref().to_path_buf()),
+            project,
+            version,
+            profile: None,
+            history,
+            resolution_cache: Mutex::new(resolution_cache),
         })
     }
 
     /// Create a new shim runner from a configuration
+    ///
+    /// Aliases are resolved against the default shim directory (`~/.shimexe`).
+    /// Trusted project overrides found from the working directory are applied
+    /// and, for multi-version shims, the version to run is selected.
     pub fn from_config(mut config: ShimConfig) -> Result<Self> {
-        config.expand_env_vars()?;
+        if config.shim.target_shim.is_some() {
+            config = alias::resolve(config, &default_shim_dir()?)?;
+        }
+        let (project, version) = apply_overrides(&mut config)?;
+        timing::measure(stage::ENV_EXPANSION, || config.expand_env_vars())?;
+        let history = History::from_env(config.history.as_ref());
         Ok(Self {
             config,
             shim_file_path: None,
+            project,
+            version,
+            profile: None,
+            history,
+            resolution_cache: Mutex::new(None),
         })
     }
 
+    /// Record invocations in `history` instead of the default history (`None` disables it)
+    pub fn with_history(mut self, history: Option<History>) -> Self {
+        self.history = history;
+        self
+    }
+
     /// Execute the shim with additional arguments
     pub fn execute(&self, additional_args: &[String]) -> Result<i32> {
+        let (additional_args, profile) = self.select_profile(additional_args)?;
+        if let Some(profile) = profile {
+            debug!("Using profile {}", profile);
+            return self.with_profile(profile).execute(&additional_args);
+        }
+        let additional_args = additional_args.as_slice();
+
+        if let Some(ref dispatch) = self.config.dispatch {
+            if dispatch.is_help(additional_args) {
+                print!("{}", dispatch.help(&self.config.shim.name));
+                return Ok(0);
+            }
+            if let Some(route) = dispatch.route(additional_args)? {
+                debug!(
+                    "Dispatching '{}' to route '{}'",
+                    route.subcommand, route.name
+                );
+                return self.routed(&route)?.execute(&route.args(additional_args));
+            }
+        }
+
         let start_time = SystemTime::now();
 
         // Check for updates if auto-update is enabled
         if let Some(ref auto_update) = self.config.auto_update {
             if let Some(ref shim_file_path) = self.shim_file_path {
-                self.check_and_update(auto_update, shim_file_path)?;
+                timing::measure(stage::UPDATE_CHECK, || {
+                    self.check_and_update(auto_update, shim_file_path)
+                })?;
+            }
+        }
+
+        let (executable_path, interpreter) = timing::measure(stage::EXECUTABLE_RESOLUTION, || {
+            let executable = self.resolve_executable()?;
+            let interpreter = self.resolve_interpreter(&executable)?;
+            Ok::<_, ShimError>((executable, interpreter))
+        })?;
+
+        debug!("Executing: {:?}", executable_path);
+        debug!("Default args: {:?}", self.config.shim.args);
+        debug!("Additional args: {:?}", additional_args);
+
+        let argv = timing::measure(stage::TEMPLATE_RENDERING, || {
+            self.resolve_argv(additional_args)
+        })?;
+        let env = timing::measure(stage::ENV_SETUP, || self.build_env())?;
+        let timeout = TimeoutConfig::effective(self.config.timeout.as_ref())?;
+        if interpreter.is_none() && wasm::is_wasm(&executable_path) {
+            self.check_wasm_sections()?;
+        }
+        if let Some(ref limits) = self.config.limits {
+            limits.check()?;
+        }
+
+        // Held until the target and its hooks are done
+        let _instance = timing::measure(stage::INSTANCE_LOCK, || self.acquire_instance())?;
+
+        let hook_context = HookContext {
+            shim_name: &self.config.shim.name,
+            executable: &executable_path,
+            argv: &argv,
+            env: &env,
+            exit_code: None,
+        };
+        if let Some(ref hooks) = self.config.hooks {
+            hooks.run(HookStage::PreExec, &hook_context)?;
+        }
+
+        let watch = self.output_watch()?;
+        let tee = self.output_tee(watch.clone());
+        let mut target = match interpreter {
+            None if wasm::is_wasm(&executable_path) => {
+                debug!("Running WebAssembly module in the embedded runtime");
+                let config = self.config.wasm.clone().unwrap_or_default();
+                let invocation = config.invocation(
+                    &mut TemplateEngine::new(additional_args.to_vec()),
+                    &self.config.shim.name,
+                    &argv,
+                    &env,
+                    &self.working_dir()?,
+                )?;
+                let module = timing::measure(stage::SPAWN, || WasmModule::load(&executable_path))?;
+                Target::Wasm(module, invocation)
+            }
+            _ => Target::Process(self.prepare_command(
+                &executable_path,
+                interpreter.as_ref(),
+                &argv,
+                &env,
+                timeout.as_ref(),
+                additional_args,
+                tee.as_ref(),
+            )?),
+        };
+
+        info!(
+            "Executing shim '{}' -> {:?}",
+            self.config.shim.name, executable_path
+        );
+
+        // Execute the command, re-running it when the retry policy asks for it
+        let retry = self
+            .config
+            .retry
+            .as_ref()
+            .filter(|retry| retry.stdin_allows_retry());
+        if self.config.retry.is_some() && retry.is_none() {
+            debug!("stdin is not a terminal, retries are disabled");
+        }
+
+        let mut attempt = 1;
+        let result = loop {
+            let result = match target {
+                Target::Process(ref mut cmd) => {
+                    Self::run_target(cmd, timeout.as_ref(), tee.as_ref())
+                }
+                Target::Wasm(ref module, ref invocation) => {
+                    let _target = timing::Span::new(stage::TARGET);
+                    module.run(invocation, timeout.as_ref().map(TimeoutConfig::duration))
+                }
+            }
+            .map(|code| self.normalize_exit_code(code, watch.as_deref()));
+            let (Some(retry), Ok(exit_code)) = (retry, &result) else {
+                break result;
+            };
+            if !retry.should_retry(attempt, *exit_code) {
+                break result;
+            }
+
+            let delay = retry.delay(attempt);
+            warn!(
+                "Shim '{}' attempt {}/{} exited with code {}, retrying in {:?}",
+                self.config.shim.name, attempt, retry.attempts, exit_code, delay
+            );
+            std::thread::sleep(delay);
+            attempt += 1;
+        };
+
+        self.record_history(&executable_path, argv.len(), &result, start_time);
+
+        if let (Some(hooks), Ok(exit_code)) = (&self.config.hooks, &result) {
+            let hook_context = HookContext {
+                exit_code: Some(*exit_code),
+                ..hook_context
+            };
+            hooks.run(HookStage::PostExec, &hook_context)?;
+        }
+
+        // Log execution time for performance monitoring
+        if let Ok(elapsed) = start_time.elapsed() {
+            debug!("Shim execution took: {:?}", elapsed);
+        }
+
+        result
+    }
+
+    /// Refuse sections that only work for native processes
+    ///
+    /// The embedded runtime writes straight to the terminal and runs inside
+    /// shimexe, so output capture, `[limits]` and `[sandbox]` cannot apply.
+    fn check_wasm_sections(&self) -> Result<()> {
+        let mut unsupported = Vec::new();
+        if self
+            .config
+            .logging
+            .as_ref()
+            .is_some_and(|logging| logging.stdout || logging.stderr)
+        {
+            unsupported.push("[logging] stdout/stderr");
+        }
+        if self.config.exit.as_ref().is_some_and(|exit| {
+            exit.fail_on_stdout_regex.is_some() || exit.fail_on_stderr_regex.is_some()
+        }) {
+            unsupported.push("[exit] fail_on_*_regex");
+        }
+        if self.config.limits.is_some() {
+            unsupported.push("[limits]");
+        }
+        if self.config.sandbox.is_some() {
+            unsupported.push("[sandbox]");
+        }
+
+        if unsupported.is_empty() {
+            return Ok(());
+        }
+        Err(ShimError::Config(format!(
+            "{} not supported for WebAssembly targets; remove them or set interpreter = \"auto\" to run the module as a process",
+            unsupported.join(", ")
+        )))
+    }
+
+    /// Build the command that runs the target executable
+    #[allow(clippy::too_many_arguments)]
+    fn prepare_command(
+        &self,
+        executable_path: &Path,
+        interpreter: Option<&ResolvedInterpreter>,
+        argv: &[String],
+        env: &EnvDiff,
+        timeout: Option<&TimeoutConfig>,
+        additional_args: &[String],
+        tee: Option<&OutputTee>,
+    ) -> Result<Command> {
+        let mut cmd = match interpreter {
+            Some(interpreter) => {
+                debug!("Running script with interpreter {:?}", interpreter.program);
+                let mut cmd = Command::new(&interpreter.program);
+                cmd.args(&interpreter.args).arg(executable_path);
+                cmd
             }
+            None => Command::new(executable_path),
+        };
+        cmd.args(argv);
+
+        // Set working directory if specified
+        if let Some(ref cwd) = self.config.shim.cwd {
+            cmd.current_dir(cwd);
+        }
+
+        // Inherit the current environment and layer the shim environment on top
+        env.apply(&mut cmd);
+
+        // Configure stdio to inherit from parent
+        cmd.stdin(Stdio::inherit())
+            .stdout(Stdio::inherit())
+            .stderr(Stdio::inherit());
+
+        if let Some(timeout) = timeout {
+            timeout.configure(&mut cmd);
+        }
+
+        if let Some(ref limits) = self.config.limits {
+            limits.configure(&mut cmd);
+        }
+
+        if let Some(ref sandbox) = self.config.sandbox {
+            let mut engine = TemplateEngine::new(additional_args.to_vec());
+            let mut executables = vec![executable_path];
+            if let Some(interpreter) = interpreter {
+                executables.push(&interpreter.program);
+            }
+            let paths = sandbox.paths(&mut engine, &executables, &self.working_dir()?)?;
+            sandbox.configure(&mut cmd, &paths)?;
+        }
+
+        if let Some(tee) = tee {
+            tee.configure(&mut cmd);
+        }
+        Ok(cmd)
+    }
+
+    /// Directory the target runs in
+    fn working_dir(&self) -> Result<PathBuf> {
+        Ok(match self.config.shim.cwd {
+            Some(ref cwd) => PathBuf::from(cwd),
+            None => std::env::current_dir()?,
+        })
+    }
+
+    /// Runner for a dispatch route, sharing the shim file and history
+    fn routed(&self, route: &Dispatch) -> Result<ShimRunner> {
+        Ok(ShimRunner {
+            config: route.config(&self.config)?,
+            shim_file_path: self.shim_file_path.clone(),
+            project: self.project.clone(),
+            version: self.version.clone(),
+            profile: self.profile.clone(),
+            history: self.history.clone(),
+            // The cache belongs to the shim's own target
+            resolution_cache: Mutex::new(None),
+        })
+    }
+
+    /// Arguments without `--shimexe-profile`, and the profile to apply
+    ///
+    /// No profile is returned once one has been applied.
+    fn select_profile(
+        &self,
+        additional_args: &[String],
+    ) -> Result<(Vec<String>, Option<ActiveProfile>)> {
+        let (args, flag) = profiles::strip_flag(additional_args)?;
+        if self.profile.is_some() {
+            return Ok((args, None));
+        }
+        let profile = profiles::select(&self.config, flag.as_deref(), self.project.as_ref())?;
+        Ok((args, profile))
+    }
+
+    /// Runner with `profile` applied, sharing the shim file and history
+    fn with_profile(&self, profile: ActiveProfile) -> ShimRunner {
+        ShimRunner {
+            config: self.config.profiles[&profile.name].config(&self.config),
+            shim_file_path: self.shim_file_path.clone(),
+            project: self.project.clone(),
+            version: self.version.clone(),
+            profile: Some(profile),
+            history: self.history.clone(),
+            // The profile may change the target
+            resolution_cache: Mutex::new(None),
+        }
+    }
+
+    /// Take an instance slot when `[concurrency]` limits parallel runs
+    fn acquire_instance(&self) -> Result<Option<InstanceGuard>> {
+        let Some(ref concurrency) = self.config.concurrency else {
+            return Ok(None);
+        };
+        let guard = concurrency.acquire(&self.data_dir()?.join("locks"))?;
+        Ok(Some(guard))
+    }
+
+    /// Run the prepared target once and return its exit code
+    fn run_target(
+        cmd: &mut Command,
+        timeout: Option<&TimeoutConfig>,
+        tee: Option<&OutputTee>,
+    ) -> Result<i32> {
+        let status = timing::measure(stage::SPAWN, || cmd.spawn()).and_then(|mut child| {
+            let _target = timing::Span::new(stage::TARGET);
+            let pumps = tee.map(|tee| tee.attach(&mut child)).unwrap_or_default();
+            let status = match timeout {
+                Some(timeout) => timeout.wait(&mut child),
+                None => child.wait().map(Some),
+            };
+            for pump in pumps {
+                let _ = pump.join();
+            }
+            status
+        });
+
+        match status {
+            Ok(Some(status)) => {
+                let exit_code = status.code().unwrap_or(-1);
+                debug!("Process exited with code: {}", exit_code);
+                Ok(exit_code)
+            }
+            Ok(None) => {
+                warn!("Process timed out, exiting with code {}", TIMEOUT_EXIT_CODE);
+                Ok(TIMEOUT_EXIT_CODE)
+            }
+            Err(e) => {
+                warn!("Failed to execute process: {}", e);
+                Err(ShimError::ProcessExecution(e.to_string()))
+            }
+        }
+    }
+
+    /// Interpreter that runs the target, if it is a script
+    fn resolve_interpreter(&self, executable: &Path) -> Result<Option<ResolvedInterpreter>> {
+        match self.config.shim.interpreter {
+            Some(ref interpreter) => interpreter.resolve(executable),
+            None => Ok(None),
+        }
+    }
+
+    /// Locate the target executable, downloading it if needed, and validate it
+    fn resolve_executable(&self) -> Result<PathBuf> {
+        let mut resolution_cache = self
+            .resolution_cache
+            .lock()
+            .map_err(|_| ShimError::Config("Resolution cache lock poisoned".to_string()))?;
+        if let Some(executable) = resolution_cache
+            .as_ref()
+            .and_then(|cache| cache.executable(&self.config.shim.path))
+        {
+            debug!("Using cached executable: {}", executable.display());
+            return Ok(executable.to_path_buf());
         }
 
         // Check if we need to download the executable
@@ -134,65 +605,281 @@ impl ShimRunner {
             }
         }
 
-        debug!("Executing: {:?}", executable_path);
-        debug!("Default args: {:?}", self.config.shim.args);
-        debug!("Additional args: {:?}", additional_args);
+        if let Some(ref mut cache) = *resolution_cache {
+            cache.set_executable(&self.config.shim.path, &executable_path);
+        }
+        Ok(executable_path)
+    }
 
-        // Prepare command with optimized environment variable handling
-        let mut cmd = Command::new(&executable_path);
+    /// Describe the resolved invocation without executing anything
+    ///
+    /// Pending downloads and updates are reported but not performed.
+    pub fn explain(&self, additional_args: &[String]) -> Result<Explanation> {
+        let (additional_args, profile) = self.select_profile(additional_args)?;
+        if let Some(profile) = profile {
+            return self.with_profile(profile).explain(&additional_args);
+        }
+        let additional_args = additional_args.as_slice();
 
-        // Add default arguments
-        cmd.args(&self.config.shim.args);
+        if let Some(route) = self
+            .config
+            .dispatch
+            .as_ref()
+            .map(|dispatch| dispatch.route(additional_args))
+            .transpose()?
+            .flatten()
+        {
+            let mut explanation = self.routed(&route)?.explain(&route.args(additional_args))?;
+            explanation.resolution = format!(
+                "dispatch route '{}', {}",
+                route.name, explanation.resolution
+            );
+            return Ok(explanation);
+        }
 
-        // Add additional arguments
-        cmd.args(additional_args);
+        let pending_download = self.pending_download();
+        let executable = match pending_download {
+            Some(_) => None,
+            None => self.config.get_executable_path().ok(),
+        };
 
-        // Set working directory if specified
-        if let Some(ref cwd) = self.config.shim.cwd {
-            cmd.current_dir(cwd);
+        let mut pending = Vec::new();
+        if let Some(url) = pending_download {
+            pending.push(format!(
+                "download {} into {}",
+                url,
+                self.data_dir()?.join("bin").display()
+            ));
         }
+        if let Some(check) = self.pending_update_check()? {
+            pending.push(check);
+        }
+
+        let interpreter = match executable {
+            Some(ref executable) => self.resolve_interpreter(executable)?.map(|interpreter| {
+                let mut command = vec![interpreter.program.display().to_string()];
+                command.extend(interpreter.args);
+                command
+            }),
+            None => None,
+        };
 
-        // Set environment variables (inherit current env and add custom ones)
-        // First, set built-in environment variables
-        let builtin_vars = get_builtin_env_vars();
-        for (key, value) in builtin_vars {
-            cmd.env(key, value);
+        // `[env_from]` is only taken from its cache, the script or command is never run
+        let env_from = match self.config.env_from {
+            Some(ref env_from) => {
+                let cached = env_from.cached(self.shim_dir(), self.data_dir().ok().as_deref())?;
+                if cached.is_none() {
+                    pending.push(format!(
+                        "run `{}` for [env_from]",
+                        env_from.command_line(self.shim_dir())?
+                    ));
+                }
+                cached
+            }
+            None => None,
+        };
+        let env = self.layer_env(env_from)?;
+        let cwd = self.working_dir()?;
+
+        Ok(Explanation {
+            shim: self.config.shim.name.clone(),
+            config_file: self.shim_file_path.clone(),
+            config_origin: None,
+            project_file: self.project.as_ref().map(|project| project.file.clone()),
+            version: self.version.as_ref().map(ToString::to_string),
+            profile: self.profile.as_ref().map(ToString::to_string),
+            executable,
+            interpreter,
+            resolution: self.describe_resolution(),
+            argv: self.resolve_argv(additional_args)?,
+            env: EnvChange::from_diff(&env),
+            cwd,
+            timeout: TimeoutConfig::effective(self.config.timeout.as_ref())?,
+            limits: self
+                .config
+                .limits
+                .clone()
+                .filter(|limits| !limits.is_empty()),
+            pending,
+        })
+    }
+
+    /// Final arguments: the shim's `args` followed by the processed `[args]` section
+    fn resolve_argv(&self, additional_args: &[String]) -> Result<Vec<String>> {
+        let mut argv = self.config.shim.args.clone();
+        let mut engine = TemplateEngine::new(additional_args.to_vec());
+        argv.extend(engine.process_args(&self.config.args)?);
+        Ok(argv)
+    }
+
+    /// How the target executable is located
+    fn describe_resolution(&self) -> String {
+        match self.config.shim.source_type {
+            SourceType::Archive => "primary executable extracted from an archive".to_string(),
+            SourceType::Url => match self.config.get_download_url() {
+                Some(url) => format!("downloaded from {}", url),
+                None => format!("downloaded from {}", self.config.shim.path),
+            },
+            SourceType::File => {
+                let path = expand_env_vars(&self.config.shim.path)
+                    .unwrap_or_else(|_| self.config.shim.path.clone());
+                if Path::new(&path).is_absolute() {
+                    format!("absolute path {}", path)
+                } else {
+                    format!("'{}' looked up on PATH", path)
+                }
+            }
         }
+    }
 
-        // Then, set custom environment variables (these will override built-ins if same key)
-        for (key, value) in &self.config.env {
-            cmd.env(key, value);
+    /// URL that has to be downloaded before the target can run, if any
+    fn pending_download(&self) -> Option<&str> {
+        let url = match self.config.get_download_url() {
+            Some(url) => url.as_str(),
+            None if Downloader::is_url(&self.config.shim.path) => self.config.shim.path.as_str(),
+            None => return None,
+        };
+
+        match self.config.get_executable_path() {
+            Ok(path) if path.exists() => None,
+            _ => Some(url),
         }
+    }
 
-        // Configure stdio to inherit from parent
-        cmd.stdin(Stdio::inherit())
-            .stdout(Stdio::inherit())
-            .stderr(Stdio::inherit());
+    /// Description of the auto-update check due on the next run, if any
+    ///
+    /// Only the time of the last check is read; nothing is downloaded or contacted.
+    fn pending_update_check(&self) -> Result<Option<String>> {
+        let (Some(auto_update), Some(shim_file_path)) =
+            (&self.config.auto_update, &self.shim_file_path)
+        else {
+            return Ok(None);
+        };
+        if !auto_update.enabled {
+            return Ok(None);
+        }
 
-        info!(
-            "Executing shim '{}' -> {:?}",
-            self.config.shim.name, executable_path
-        );
+        let executable_path = self.config.get_executable_path().unwrap_or_default();
+        let updater =
+            ShimUpdater::new(auto_update.clone(), shim_file_path.clone(), executable_path);
+        Ok(updater
+            .should_check_for_updates()?
+            .then(|| "auto-update check (may download a new version)".to_string()))
+    }
 
-        // Execute the command
-        let result = match cmd.status() {
-            Ok(status) => {
-                let exit_code = status.code().unwrap_or(-1);
-                debug!("Process exited with code: {}", exit_code);
-                Ok(exit_code)
+    /// Append the invocation to the execution history, if enabled
+    fn record_history(
+        &self,
+        executable: &Path,
+        argc: usize,
+        result: &Result<i32>,
+        start_time: SystemTime,
+    ) {
+        let Some(ref history) = self.history else {
+            return;
+        };
+
+        let record = HistoryRecord {
+            timestamp: chrono::Utc::now(),
+            shim: self.config.shim.name.clone(),
+            executable: executable.to_string_lossy().to_string(),
+            argc,
+            cwd: match self.config.shim.cwd {
+                Some(ref cwd) => cwd.clone(),
+                None => std::env::current_dir()
+                    .map(|cwd| cwd.to_string_lossy().to_string())
+                    .unwrap_or_default(),
+            },
+            duration_ms: start_time
+                .elapsed()
+                .map(|d| d.as_millis() as u64)
+                .unwrap_or_default(),
+            exit_code: result.as_ref().ok().copied(),
+        };
+        if let Err(e) = history.append(&record) {
+            debug!("Failed to record history: {}", e);
+        }
+    }
+
+    /// Set up output logging and watching for the target, if configured
+    fn output_tee(&self, watch: Option<Arc<OutputWatch>>) -> Option<OutputTee> {
+        let tee = self.config.logging.as_ref().and_then(|logging| {
+            let log_path = match self.data_dir() {
+                Ok(data_dir) => logging.log_path(&data_dir, &self.config.shim.name),
+                Err(e) => {
+                    warn!("Output logging disabled: {}", e);
+                    return None;
+                }
+            };
+
+            match OutputTee::new(logging, log_path) {
+                Ok(tee) => tee,
+                Err(e) => {
+                    warn!("Output logging disabled: {}", e);
+                    None
+                }
             }
-            Err(e) => {
-                warn!("Failed to execute process: {}", e);
-                Err(ShimError::ProcessExecution(e.to_string()))
+        });
+
+        match (tee, watch) {
+            (Some(tee), Some(watch)) => Some(tee.with_watch(watch)),
+            (None, Some(watch)) => Some(OutputTee::for_watch(watch)),
+            (tee, None) => tee,
+        }
+    }
+
+    /// Watch for the output patterns of `[exit]`
+    fn output_watch(&self) -> Result<Option<Arc<OutputWatch>>> {
+        let Some(ref exit) = self.config.exit else {
+            return Ok(None);
+        };
+        Ok(exit.watch()?.map(Arc::new))
+    }
+
+    /// Apply the `[exit]` rules to the exit code of one run
+    fn normalize_exit_code(&self, code: i32, watch: Option<&OutputWatch>) -> i32 {
+        let matched = watch.is_some_and(|watch| watch.take_matched());
+        match self.config.exit {
+            Some(ref exit) => exit.normalize(code, matched),
+            None => code,
+        }
+    }
+
+    /// Build the environment changes applied to the target
+    ///
+    /// Precedence (later wins): built-in variables, `env_files`, `[env_from]`, `[env]`.
+    fn build_env(&self) -> Result<EnvDiff> {
+        let env_from = match self.config.env_from {
+            Some(ref env_from) => {
+                let cache_dir = self.data_dir().ok();
+                Some(env_from.resolve(self.shim_dir(), cache_dir.as_deref())?)
             }
+            None => None,
         };
+        self.layer_env(env_from)
+    }
 
-        // Log execution time for performance monitoring
-        if let Ok(elapsed) = start_time.elapsed() {
-            debug!("Shim execution took: {:?}", elapsed);
+    /// Builtin variables, env files, the `[env_from]` result and `[env]`, in that order
+    fn layer_env(&self, env_from: Option<EnvDiff>) -> Result<EnvDiff> {
+        let mut env = EnvDiff::default();
+
+        for (key, value) in get_builtin_env_vars() {
+            env.insert(key, value);
         }
 
-        result
+        for (key, value) in load_env_files(&self.config.env_files, self.shim_dir())? {
+            env.insert(key, value);
+        }
+
+        if let Some(env_from) = env_from {
+            env.extend(env_from);
+        }
+
+        for (key, value) in &self.config.env {
+            env.insert(key.clone(), value.clone());
+        }
+
+        Ok(env)
     }
 
     /// Fast executable validation without full metadata checks
@@ -205,6 +892,20 @@ impl ShimRunner {
         &self.config
     }
 
+    /// Directory containing the shim file, if the runner was created from one
+    fn shim_dir(&self) -> Option<&Path> {
+        self.shim_file_path.as_deref().and_then(Path::parent)
+    }
+
+    /// Per-shim data directory (`<shim dir>/<name>`, or `~/.shimexe/<name>` without a shim file)
+    pub fn data_dir(&self) -> Result<PathBuf> {
+        let base = match self.shim_dir() {
+            Some(dir) => dir.to_path_buf(),
+            None => default_shim_dir()?,
+        };
+        Ok(base.join(self.config.shim.data_name()))
+    }
+
     /// Validate that the target executable exists and is executable
     pub fn validate(&self) -> Result<()> {
         let executable_path = self.config.get_executable_path()?;
@@ -244,9 +945,14 @@ impl ShimRunner {
             )));
         }
 
+        // Scripts run through an interpreter need the interpreter, not an exec
+        // bit, and WebAssembly modules are loaded by the embedded runtime
+        let interpreted = self.resolve_interpreter(executable_path)?.is_some();
+        let loaded = !interpreted && wasm::is_wasm(executable_path);
+
         // On Unix-like systems, check if the file is executable
         #[cfg(unix)]
-        {
+        if !interpreted && !loaded {
             use std::os::unix::fs::PermissionsExt;
             let metadata = executable_path.metadata().map_err(ShimError::Io)?;
             let permissions = metadata.permissions();
@@ -259,6 +965,9 @@ impl ShimRunner {
             }
         }
 
+        #[cfg(not(unix))]
+        let _ = (interpreted, loaded);
+
         Ok(())
     }
 
@@ -285,13 +994,16 @@ impl ShimRunner {
             match updater.check_update_needed().await {
                 Ok(Some(version)) => {
                     info!("Auto-update available: {}", version);
-                    if let Err(e) = updater.update_to_version(&version).await {
-                        warn!("Auto-update failed: {}", e);
+                    match updater.update_to_version(&version).await {
+                        Err(e @ ShimError::Untrusted(_)) => report_untrusted(&e),
+                        Err(e) => warn!("Auto-update failed: {}", e),
+                        Ok(()) => {}
                     }
                 }
                 Ok(None) => {
                     debug!("No update needed");
                 }
+                Err(e @ ShimError::Untrusted(_)) => report_untrusted(&e),
                 Err(e) => {
                     warn!("Update check failed: {}", e);
                 }
@@ -343,24 +1055,8 @@ impl ShimRunner {
             ShimError::Config(format!("Could not extract filename from URL: {}", url))
         })?;
 
-        // Determine download directory
-        let download_dir = if let Some(ref shim_file_path) = self.shim_file_path {
-            // Use the same directory as the shim file
-            shim_file_path
-                .parent()
-                .ok_or_else(|| {
-                    ShimError::Config("Could not determine shim file directory".to_string())
-                })?
-                .join(&self.config.shim.name)
-                .join("bin")
-        } else {
-            // Fallback to home directory
-            dirs::home_dir()
-                .ok_or_else(|| ShimError::Config("Could not determine home directory".to_string()))?
-                .join(".shimexe")
-                .join(&self.config.shim.name)
-                .join("bin")
-        };
+        // Download next to the shim file, falling back to the home directory
+        let download_dir = self.data_dir()?.join("bin");
 
         let download_path = download_dir.join(&filename);
 
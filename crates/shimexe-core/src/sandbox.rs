@@ -0,0 +1,274 @@
+//! Filesystem and network sandboxing for the target (`[sandbox]`, Linux only).
+//!
+//! The sandbox is enforced with Landlock: the ruleset is built by the shim and
+//! applied to the child just before it executes the target, so the shim itself
+//! stays unrestricted. Kernels without Landlock run the target unsandboxed
+//! with a warning.
+
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use tracing::debug;
+
+use crate::error::{Result, ShimError};
+use crate::template::TemplateEngine;
+
+/// Paths every dynamically linked program needs to start, readable by default
+const SYSTEM_READ_ONLY: &[&str] = &[
+    "/usr", "/lib", "/lib32", "/lib64", "/bin", "/sbin", "/etc", "/proc", "/sys", "/dev",
+];
+
+/// Device files programs commonly open for writing
+const SYSTEM_READ_WRITE: &[&str] = &["/dev/null", "/dev/zero", "/dev/tty"];
+
+/// Sandbox settings for the target executable
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SandboxConfig {
+    /// Paths the target may read and execute (templates allowed)
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub read_only: Vec<String>,
+    /// Paths the target may read, write and execute (templates allowed)
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub read_write: Vec<String>,
+    /// Allow TCP connections and listening sockets
+    #[serde(default = "default_true")]
+    pub network: bool,
+    /// Allow read access to system directories such as `/usr` and `/etc`
+    #[serde(default = "default_true")]
+    pub system_paths: bool,
+}
+
+fn default_true() -> bool {
+    true
+}
+
+impl Default for SandboxConfig {
+    fn default() -> Self {
+        Self {
+            read_only: Vec::new(),
+            read_write: Vec::new(),
+            network: true,
+            system_paths: true,
+        }
+    }
+}
+
+/// Rendered allowlists of a sandbox
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct SandboxPaths {
+    pub read_only: Vec<PathBuf>,
+    pub read_write: Vec<PathBuf>,
+}
+
+impl SandboxConfig {
+    /// Validate the allowlists
+    pub fn validate(&self) -> Result<()> {
+        if self
+            .read_only
+            .iter()
+            .chain(&self.read_write)
+            .any(|path| path.trim().is_empty())
+        {
+            return Err(ShimError::Config(
+                "sandbox paths must not be empty".to_string(),
+            ));
+        }
+        Ok(())
+    }
+
+    /// Render the allowlists for a target made of `executables` running in `cwd`
+    ///
+    /// Relative paths are resolved against `cwd`. The directories containing
+    /// the executables (the target and its interpreter) are always readable.
+    pub fn paths(
+        &self,
+        engine: &mut TemplateEngine,
+        executables: &[&Path],
+        cwd: &Path,
+    ) -> Result<SandboxPaths> {
+        let mut render = |paths: &[String]| -> Result<Vec<PathBuf>> {
+            paths
+                .iter()
+                .map(|path| Ok(cwd.join(engine.render_template(path)?)))
+                .collect()
+        };
+
+        let mut read_only = render(&self.read_only)?;
+        let mut read_write = render(&self.read_write)?;
+        read_only.extend(
+            executables
+                .iter()
+                .filter_map(|executable| executable.parent())
+                .map(Path::to_path_buf),
+        );
+        if self.system_paths {
+            read_only.extend(SYSTEM_READ_ONLY.iter().map(PathBuf::from));
+            read_write.extend(SYSTEM_READ_WRITE.iter().map(PathBuf::from));
+        }
+
+        for path in read_only.iter().chain(&read_write) {
+            if !path.exists() {
+                debug!("Sandbox path {} does not exist, skipping", path.display());
+            }
+        }
+        Ok(SandboxPaths {
+            read_only,
+            read_write,
+        })
+    }
+
+    /// Restrict the child started by `cmd` to `paths`
+    pub fn configure(&self, cmd: &mut Command, paths: &SandboxPaths) -> Result<()> {
+        #[cfg(target_os = "linux")]
+        {
+            imp::configure(cmd, paths, self.network)
+        }
+
+        #[cfg(not(target_os = "linux"))]
+        {
+            let _ = (cmd, paths);
+            tracing::warn!("Sandboxing is only supported on Linux, running the target unsandboxed");
+            Ok(())
+        }
+    }
+}
+
+/// Whether the running kernel can enforce a sandbox
+pub fn is_supported() -> bool {
+    #[cfg(target_os = "linux")]
+    {
+        imp::abi() != landlock::ABI::Unsupported
+    }
+
+    #[cfg(not(target_os = "linux"))]
+    {
+        false
+    }
+}
+
+#[cfg(target_os = "linux")]
+mod imp {
+    use std::io;
+    use std::os::unix::process::CommandExt;
+    use std::process::Command;
+
+    use landlock::{
+        path_beneath_rules, Access, AccessFs, AccessNet, RulesetAttr, RulesetCreated,
+        RulesetCreatedAttr, RulesetError, ABI,
+    };
+    use tracing::{debug, warn};
+
+    use super::SandboxPaths;
+    use crate::error::{Result, ShimError};
+
+    /// `LANDLOCK_CREATE_RULESET_VERSION` flag of landlock_create_ruleset(2)
+    const CREATE_RULESET_VERSION: libc::c_uint = 1;
+
+    /// Landlock ABI supported by the running kernel
+    pub(super) fn abi() -> ABI {
+        // SAFETY: with this flag the syscall only reports the ABI version
+        let version = unsafe {
+            libc::syscall(
+                libc::SYS_landlock_create_ruleset,
+                std::ptr::null::<libc::c_void>(),
+                0usize,
+                CREATE_RULESET_VERSION,
+            )
+        };
+        if version <= 0 {
+            ABI::Unsupported
+        } else {
+            ABI::from(version as i32)
+        }
+    }
+
+    pub(super) fn configure(cmd: &mut Command, paths: &SandboxPaths, network: bool) -> Result<()> {
+        let abi = abi();
+        if abi == ABI::Unsupported {
+            warn!("Landlock is not available on this kernel, running the target unsandboxed");
+            return Ok(());
+        }
+        if !network && abi < ABI::V4 {
+            warn!("Blocking network access requires Landlock ABI 4 (Linux 6.7), network stays allowed");
+        }
+        debug!("Sandboxing target with Landlock ABI {:?}", abi);
+
+        let ruleset = ruleset(abi, paths, network)
+            .map_err(|e| ShimError::Config(format!("Failed to build sandbox: {}", e)))?;
+
+        // Each fork restricts its own copy of the ruleset, so retries work too
+        let mut ruleset = Some(ruleset);
+        // SAFETY: restrict_self only makes prctl(2) and landlock_restrict_self(2)
+        // calls and does not allocate on success
+        unsafe {
+            cmd.pre_exec(move || {
+                if let Some(ruleset) = ruleset.take() {
+                    ruleset.restrict_self().map_err(io::Error::other)?;
+                }
+                Ok(())
+            });
+        }
+        Ok(())
+    }
+
+    fn ruleset(
+        abi: ABI,
+        paths: &SandboxPaths,
+        network: bool,
+    ) -> std::result::Result<RulesetCreated, RulesetError> {
+        let mut ruleset = landlock::Ruleset::default().handle_access(AccessFs::from_all(abi))?;
+        if !network {
+            ruleset = ruleset.handle_access(AccessNet::BindTcp | AccessNet::ConnectTcp)?;
+        }
+        ruleset
+            .create()?
+            .add_rules(path_beneath_rules(
+                &paths.read_only,
+                AccessFs::from_read(abi),
+            ))?
+            .add_rules(path_beneath_rules(
+                &paths.read_write,
+                AccessFs::from_all(abi),
+            ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_paths_are_rendered() {
+        let config = SandboxConfig {
+            read_only: vec!["config".to_string()],
+            read_write: vec!["{{env('SANDBOX_TEST_UNSET', '/var/cache/tool')}}".to_string()],
+            system_paths: false,
+            ..Default::default()
+        };
+
+        let paths = config
+            .paths(
+                &mut TemplateEngine::new(vec![]),
+                &[Path::new("/opt/tool/bin/tool")],
+                Path::new("/work"),
+            )
+            .unwrap();
+        assert_eq!(
+            paths.read_only,
+            vec![
+                PathBuf::from("/work/config"),
+                PathBuf::from("/opt/tool/bin")
+            ]
+        );
+        assert_eq!(paths.read_write, vec![PathBuf::from("/var/cache/tool")]);
+    }
+
+    #[test]
+    fn test_validate_rejects_empty_paths() {
+        let config = SandboxConfig {
+            read_write: vec![" ".to_string()],
+            ..Default::default()
+        };
+        assert!(config.validate().is_err());
+    }
+}
@@ -3,6 +3,7 @@ use std::env;
 use std::path::Path;
 
 use crate::error::{Result, ShimError};
+use crate::rewrite::{self, RewriteRule};
 
 /// Template engine for processing dynamic configuration
 pub struct TemplateEngine {
@@ -35,6 +36,10 @@ pub struct ArgsConfig {
     /// Arguments always appended
     #[serde(default)]
     pub suffix: Vec<String>,
+
+    /// Rules rewriting the user arguments, applied in order
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub rewrite: Vec<RewriteRule>,
 }
 
 /// Argument processing modes
@@ -57,10 +62,18 @@ impl Default for ArgsConfig {
             default: Vec::new(),
             prefix: Vec::new(),
             suffix: Vec::new(),
+            rewrite: Vec::new(),
         }
     }
 }
 
+impl ArgsConfig {
+    /// Validate the rewrite rules
+    pub fn validate(&self) -> Result<()> {
+        self.rewrite.iter().try_for_each(RewriteRule::validate)
+    }
+}
+
 impl TemplateEngine {
     /// Create a new template engine with user arguments
     pub fn new(user_args: Vec<String>) -> Self {
@@ -68,7 +81,13 @@ impl TemplateEngine {
     }
 
     /// Process arguments based on configuration
+    ///
+    /// The user arguments are rewritten by `args_config.rewrite` first.
     pub fn process_args(&mut self, args_config: &ArgsConfig) -> Result<Vec<String>> {
+        if !args_config.rewrite.is_empty() {
+            self.user_args = rewrite::apply(&args_config.rewrite, &self.user_args)?;
+        }
+
         match args_config.mode {
             ArgsMode::Template => {
                 if let Some(ref template) = args_config.template {
@@ -0,0 +1,283 @@
+//! Per-stage startup timing (`SHIMEXE_TIMING`).
+//!
+//! With `SHIMEXE_TIMING=1` a shim prints how long each startup stage took to
+//! stderr; any other value is treated as a file path the report is appended to.
+//! Stages are recorded into a process-wide recorder that is a no-op unless
+//! timing is enabled.
+
+use std::fmt;
+use std::io::Write;
+use std::path::PathBuf;
+use std::sync::{Mutex, OnceLock};
+use std::time::{Duration, Instant};
+
+/// Environment variable enabling the timing report
+pub const TIMING_ENV_VAR: &str = "SHIMEXE_TIMING";
+
+/// Startup stage names, in the order they normally happen
+pub mod stage {
+    pub const PROCESS_START: &str = "process start";
+    pub const CONFIG_DISCOVERY: &str = "config discovery";
+    pub const TOML_PARSE: &str = "toml parse";
+    pub const ENV_EXPANSION: &str = "env expansion";
+    pub const UPDATE_CHECK: &str = "update check";
+    pub const EXECUTABLE_RESOLUTION: &str = "executable resolution";
+    pub const TEMPLATE_RENDERING: &str = "template rendering";
+    pub const ENV_SETUP: &str = "env setup";
+    pub const INSTANCE_LOCK: &str = "instance lock";
+    pub const SPAWN: &str = "spawn";
+    pub const TARGET: &str = "target";
+}
+
+/// Where the report goes
+#[derive(Debug, Clone, PartialEq)]
+enum Destination {
+    Stderr,
+    File(PathBuf),
+}
+
+struct Recorder {
+    destination: Destination,
+    started: Instant,
+    stages: Vec<(&'static str, Duration)>,
+}
+
+static RECORDER: OnceLock<Option<Mutex<Recorder>>> = OnceLock::new();
+
+fn recorder() -> Option<&'static Mutex<Recorder>> {
+    RECORDER
+        .get_or_init(|| {
+            let value = std::env::var_os(TIMING_ENV_VAR)?;
+            let destination = match value.to_str() {
+                Some("") | Some("0") | Some("false") | Some("off") => return None,
+                Some("1") | Some("true") | Some("on") | Some("stderr") => Destination::Stderr,
+                _ => Destination::File(PathBuf::from(value)),
+            };
+
+            let mut stages = Vec::new();
+            if let Some(age) = process_age() {
+                stages.push((stage::PROCESS_START, age));
+            }
+            Some(Mutex::new(Recorder {
+                destination,
+                started: Instant::now(),
+                stages,
+            }))
+        })
+        .as_ref()
+}
+
+/// Start the recorder if timing is enabled
+///
+/// Call this as early as possible so the report covers the whole startup.
+pub fn init() {
+    let _ = recorder();
+}
+
+/// Whether timing is enabled
+pub fn is_enabled() -> bool {
+    recorder().is_some()
+}
+
+/// Record a stage that took `duration`
+pub fn record(name: &'static str, duration: Duration) {
+    if let Some(recorder) = recorder() {
+        if let Ok(mut recorder) = recorder.lock() {
+            recorder.stages.push((name, duration));
+        }
+    }
+}
+
+/// Run `f` and record how long it took as stage `name`
+pub fn measure<T>(name: &'static str, f: impl FnOnce() -> T) -> T {
+    if !is_enabled() {
+        return f();
+    }
+    let start = Instant::now();
+    let result = f();
+    record(name, start.elapsed());
+    result
+}
+
+/// Records the time until it is dropped as stage `name`
+pub struct Span {
+    name: &'static str,
+    start: Option<Instant>,
+}
+
+impl Span {
+    /// Start timing stage `name`
+    pub fn new(name: &'static str) -> Self {
+        Self {
+            name,
+            start: is_enabled().then(Instant::now),
+        }
+    }
+}
+
+impl Drop for Span {
+    fn drop(&mut self) {
+        if let Some(start) = self.start {
+            record(self.name, start.elapsed());
+        }
+    }
+}
+
+/// Snapshot of the recorded stages
+pub fn report() -> Option<TimingReport> {
+    let recorder = recorder()?.lock().ok()?;
+    Some(TimingReport {
+        stages: recorder.stages.clone(),
+        total: recorder.started.elapsed(),
+    })
+}
+
+/// Write the report to its destination, if timing is enabled
+pub fn write_report() {
+    let Some(report) = report() else {
+        return;
+    };
+    let Some(destination) = recorder()
+        .and_then(|r| r.lock().ok())
+        .map(|r| r.destination.clone())
+    else {
+        return;
+    };
+
+    let result = match destination {
+        Destination::Stderr => write!(std::io::stderr(), "{}", report),
+        Destination::File(path) => std::fs::OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(path)
+            .and_then(|mut file| write!(file, "{}", report)),
+    };
+    if let Err(e) = result {
+        tracing::warn!("Failed to write timing report: {}", e);
+    }
+}
+
+/// Writes the report when dropped
+///
+/// Hold one for the whole invocation so the report is also written when the
+/// shim fails; drop it explicitly before `std::process::exit`.
+#[must_use = "the report is written when the guard is dropped"]
+pub struct ReportGuard(());
+
+impl ReportGuard {
+    /// Write the report once this guard goes out of scope
+    pub fn new() -> Self {
+        Self(())
+    }
+}
+
+impl Default for ReportGuard {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Drop for ReportGuard {
+    fn drop(&mut self) {
+        write_report();
+    }
+}
+
+/// Recorded stages of one invocation
+#[derive(Debug, Clone)]
+pub struct TimingReport {
+    pub stages: Vec<(&'static str, Duration)>,
+    /// Time since the recorder started
+    pub total: Duration,
+}
+
+impl TimingReport {
+    /// Time spent in shimexe itself, excluding the target and process start
+    pub fn overhead(&self) -> Duration {
+        self.stages
+            .iter()
+            .filter(|(name, _)| *name != stage::TARGET && *name != stage::PROCESS_START)
+            .map(|(_, duration)| *duration)
+            .sum()
+    }
+}
+
+impl fmt::Display for TimingReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "shimexe timing:")?;
+        for (name, duration) in &self.stages {
+            writeln!(f, "  {:<22} {:>10.3} ms", name, millis(*duration))?;
+        }
+        writeln!(
+            f,
+            "  {:<22} {:>10.3} ms",
+            "shim overhead",
+            millis(self.overhead())
+        )?;
+        writeln!(f, "  {:<22} {:>10.3} ms", "total", millis(self.total))
+    }
+}
+
+fn millis(duration: Duration) -> f64 {
+    duration.as_secs_f64() * 1000.0
+}
+
+/// Time between process creation and now (Linux only, clock-tick resolution)
+#[cfg(target_os = "linux")]
+fn process_age() -> Option<Duration> {
+    let stat = std::fs::read_to_string("/proc/self/stat").ok()?;
+    // Fields after the command name, which may itself contain spaces
+    let fields: Vec<&str> = stat.rsplit_once(')')?.1.split_whitespace().collect();
+    let start_ticks: f64 = fields.get(19)?.parse().ok()?;
+
+    let uptime: f64 = std::fs::read_to_string("/proc/uptime")
+        .ok()?
+        .split_whitespace()
+        .next()?
+        .parse()
+        .ok()?;
+
+    // SAFETY: sysconf has no memory safety requirements
+    let ticks_per_sec = unsafe { libc::sysconf(libc::_SC_CLK_TCK) };
+    if ticks_per_sec <= 0 {
+        return None;
+    }
+
+    let age = uptime - start_ticks / ticks_per_sec as f64;
+    (age >= 0.0).then(|| Duration::from_secs_f64(age))
+}
+
+#[cfg(not(target_os = "linux"))]
+fn process_age() -> Option<Duration> {
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_report_overhead_excludes_target() {
+        let report = TimingReport {
+            stages: vec![
+                (stage::PROCESS_START, Duration::from_millis(5)),
+                (stage::TOML_PARSE, Duration::from_millis(2)),
+                (stage::SPAWN, Duration::from_millis(1)),
+                (stage::TARGET, Duration::from_millis(100)),
+            ],
+            total: Duration::from_millis(104),
+        };
+
+        assert_eq!(report.overhead(), Duration::from_millis(3));
+        let text = report.to_string();
+        assert!(text.contains("toml parse"));
+        assert!(text.contains("shim overhead"));
+    }
+
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn test_process_age() {
+        let age = process_age().unwrap();
+        assert!(age < Duration::from_secs(3600));
+    }
+}
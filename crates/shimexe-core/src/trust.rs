@@ -0,0 +1,195 @@
+//! Trust store for files that can change what a shim runs.
+//!
+//! A project checked out from anywhere can carry a `.shimexe.toml`, and shim
+//! files can carry update commands run through `sh -c`, so such files are
+//! refused until the user approves them with `shimexe trust`. The store records
+//! the SHA-256 of each approved file; editing the file revokes the approval
+//! until it is trusted again, like direnv's `allow`.
+
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::{BTreeMap, HashSet};
+use std::path::{Path, PathBuf};
+use std::sync::{Mutex, OnceLock, PoisonError};
+use tracing::debug;
+
+use crate::error::{Result, ShimError};
+
+/// Environment variable overriding the trust store location
+pub const TRUST_FILE_ENV_VAR: &str = "SHIMEXE_TRUST_FILE";
+
+/// Approved files and the content hash they were approved with
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct TrustStore {
+    #[serde(skip)]
+    store_file: PathBuf,
+    /// SHA-256 (hex) keyed by canonical file path
+    #[serde(default)]
+    files: BTreeMap<PathBuf, String>,
+}
+
+impl TrustStore {
+    /// Default store location (`~/.shimexe/trust.json`, or `SHIMEXE_TRUST_FILE`)
+    pub fn default_path() -> Result<PathBuf> {
+        if let Some(path) = std::env::var_os(TRUST_FILE_ENV_VAR) {
+            return Ok(PathBuf::from(path));
+        }
+        Ok(dirs::home_dir()
+            .ok_or_else(|| ShimError::Config("Could not determine home directory".to_string()))?
+            .join(".shimexe")
+            .join("trust.json"))
+    }
+
+    /// Load the default store
+    pub fn load() -> Result<Self> {
+        Self::load_from(Self::default_path()?)
+    }
+
+    /// Load the store at `store_file`; a missing file is an empty store
+    pub fn load_from<P: AsRef<Path>>(store_file: P) -> Result<Self> {
+        let store_file = store_file.as_ref().to_path_buf();
+        let mut store = match std::fs::read_to_string(&store_file) {
+            Ok(content) => serde_json::from_str::<TrustStore>(&content).map_err(|e| {
+                ShimError::Config(format!(
+                    "Invalid trust store {}: {}",
+                    store_file.display(),
+                    e
+                ))
+            })?,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => TrustStore::default(),
+            Err(e) => return Err(e.into()),
+        };
+        store.store_file = store_file;
+        Ok(store)
+    }
+
+    /// Write the store back to disk
+    pub fn save(&self) -> Result<()> {
+        if let Some(parent) = self.store_file.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        let content = serde_json::to_string_pretty(self)
+            .map_err(|e| ShimError::Config(format!("Failed to serialize trust store: {}", e)))?;
+        std::fs::write(&self.store_file, content)?;
+        Ok(())
+    }
+
+    /// Whether `file` was trusted with its current content
+    pub fn is_trusted(&self, file: &Path) -> bool {
+        let Ok(path) = file.canonicalize() else {
+            return false;
+        };
+        let Some(approved) = self.files.get(&path) else {
+            return false;
+        };
+        match hash_file(&path) {
+            Ok(hash) if hash == *approved => true,
+            Ok(_) => {
+                debug!("{} changed since it was trusted", path.display());
+                false
+            }
+            Err(_) => false,
+        }
+    }
+
+    /// Fail with [`ShimError::Untrusted`] unless `file` is trusted
+    ///
+    /// `what` describes what the file would do, e.g. "runs update commands".
+    pub fn require(&self, file: &Path, what: &str) -> Result<()> {
+        if self.is_trusted(file) {
+            return Ok(());
+        }
+        Err(ShimError::Untrusted(format!(
+            "{} {}; review it and run `shimexe trust {}` to allow it",
+            file.display(),
+            what,
+            file.display()
+        )))
+    }
+
+    /// Trust `file` with its current content, returning its canonical path
+    pub fn trust(&mut self, file: &Path) -> Result<PathBuf> {
+        let path = file.canonicalize()?;
+        let hash = hash_file(&path)?;
+        self.files.insert(path.clone(), hash);
+        Ok(path)
+    }
+
+    /// Revoke the trust in `file`, returning the path it was recorded under
+    ///
+    /// Works for files that no longer exist.
+    pub fn untrust(&mut self, file: &Path) -> Option<PathBuf> {
+        let path = file.canonicalize().unwrap_or_else(|_| file.to_path_buf());
+        self.files.remove_entry(&path).map(|(path, _)| path)
+    }
+
+    /// Trusted files, in path order
+    pub fn files(&self) -> impl Iterator<Item = &Path> {
+        self.files.keys().map(PathBuf::as_path)
+    }
+}
+
+/// Tell the user on stderr that an untrusted file was ignored, once per message
+///
+/// Shim mode only logs errors and sets up logging after the configuration is
+/// loaded, so a log message would go unnoticed.
+pub fn report_untrusted(error: &ShimError) {
+    static REPORTED: OnceLock<Mutex<HashSet<String>>> = OnceLock::new();
+
+    let message = error.to_string();
+    let mut reported = REPORTED
+        .get_or_init(Default::default)
+        .lock()
+        .unwrap_or_else(PoisonError::into_inner);
+    if reported.insert(message.clone()) {
+        eprintln!("shimexe: {}", message);
+    }
+}
+
+/// SHA-256 of the file's content, hex encoded
+pub fn hash_file(file: &Path) -> Result<String> {
+    let digest = Sha256::digest(std::fs::read(file)?);
+    Ok(digest.iter().map(|byte| format!("{:02x}", byte)).collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_trust_is_tied_to_content() {
+        let dir = tempfile::tempdir().unwrap();
+        let file = dir.path().join(".shimexe.toml");
+        std::fs::write(&file, "[node]\nversion = \"18\"\n").unwrap();
+
+        let store_file = dir.path().join("store").join("trust.json");
+        let mut store = TrustStore::load_from(&store_file).unwrap();
+        assert!(!store.is_trusted(&file));
+
+        store.trust(&file).unwrap();
+        store.save().unwrap();
+        let store = TrustStore::load_from(&store_file).unwrap();
+        assert!(store.is_trusted(&file));
+
+        std::fs::write(&file, "[node]\nversion = \"20\"\n").unwrap();
+        assert!(!store.is_trusted(&file));
+        let err = store.require(&file, "overrides shims").unwrap_err();
+        assert!(matches!(err, ShimError::Untrusted(_)), "{}", err);
+    }
+
+    #[test]
+    fn test_untrust_removes_entry() {
+        let dir = tempfile::tempdir().unwrap();
+        let file = dir.path().join("tool.shim.toml");
+        std::fs::write(&file, "[shim]\nname = \"tool\"\n").unwrap();
+
+        let mut store = TrustStore::load_from(dir.path().join("trust.json")).unwrap();
+        let trusted = store.trust(&file).unwrap();
+        assert_eq!(store.files().collect::<Vec<_>>(), vec![trusted.as_path()]);
+
+        std::fs::remove_file(&file).unwrap();
+        assert_eq!(store.untrust(&trusted), Some(trusted));
+        assert_eq!(store.files().count(), 0);
+        assert!(store.require(&file, "runs commands").is_err());
+    }
+}
@@ -5,6 +5,7 @@ use tracing::{debug, info, warn};
 use crate::config::{AutoUpdate, UpdateProvider, VersionCheck};
 use crate::downloader::Downloader;
 use crate::error::{Result, ShimError};
+use crate::trust::TrustStore;
 use crate::utils::expand_env_vars;
 
 /// Shim updater that handles automatic updates
@@ -58,6 +59,7 @@ impl ShimUpdater {
     /// Perform the update to the specified version
     pub async fn update_to_version(&self, version: &str) -> Result<()> {
         info!("Starting update to version {}", version);
+        self.require_trusted_commands()?;
 
         // Run pre-update command if specified
         if let Some(ref cmd) = self.config.pre_update_command {
@@ -95,7 +97,7 @@ impl ShimUpdater {
     }
 
     /// Check if we should check for updates based on the interval
-    fn should_check_for_updates(&self) -> Result<bool> {
+    pub fn should_check_for_updates(&self) -> Result<bool> {
         if self.config.check_interval_hours == 0 {
             return Ok(true); // Always check if interval is 0
         }
@@ -139,8 +141,19 @@ impl ShimUpdater {
                     .await
             }
             VersionCheck::Semver { check_url, .. } => self.get_semver_version(check_url).await,
-            VersionCheck::Command { command, args } => self.get_command_version(command, args),
+            VersionCheck::Command { command, args } => {
+                self.require_trusted_commands()?;
+                self.get_command_version(command, args)
+            }
+        }
+    }
+
+    /// Refuse to run the configured commands unless the shim file is trusted
+    fn require_trusted_commands(&self) -> Result<()> {
+        if !self.config.runs_commands() {
+            return Ok(());
         }
+        TrustStore::load()?.require(&self.shim_path, "runs update commands")
     }
 
     /// Get current version of the executable
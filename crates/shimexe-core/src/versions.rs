@@ -0,0 +1,365 @@
+//! Multiple installed versions per shim (`[versions]`).
+//!
+//! A tool manager can install several versions of a tool behind one shim and
+//! switch between them without rewriting the shim. The version that runs is
+//! picked, in order, from the `<NAME>_VERSION` environment variable, the
+//! project file, version files such as `.tool-versions` or `.node-version`, or
+//! `versions.default`; its executable replaces `shim.path`.
+
+use serde::{Deserialize, Serialize};
+use std::cmp::Ordering;
+use std::collections::BTreeMap;
+use std::fmt;
+use std::path::{Path, PathBuf};
+use tracing::debug;
+
+use crate::config::{ShimConfig, SourceType};
+use crate::error::{Result, ShimError};
+use crate::project::ProjectConfig;
+
+/// asdf-style file listing the versions of several tools
+pub const TOOL_VERSIONS_FILE: &str = ".tool-versions";
+
+/// Installed versions of a shim's target
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct VersionsConfig {
+    /// Version used when neither the environment nor the project selects one
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub default: Option<String>,
+    /// Executable of each installed version, keyed by version
+    #[serde(default)]
+    pub installed: BTreeMap<String, String>,
+    /// Version files searched upward from the working directory, e.g.
+    /// `.node-version` or `.tool-versions`
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub files: Vec<String>,
+    /// Name of the tool in `.tool-versions` (default: the shim name)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tool_name: Option<String>,
+}
+
+/// Where the selected version came from
+#[derive(Debug, Clone, PartialEq)]
+pub enum VersionSource {
+    /// The `<NAME>_VERSION` environment variable
+    Env(String),
+    /// A project file or version file
+    Project(PathBuf),
+    /// `versions.default`
+    Default,
+}
+
+impl fmt::Display for VersionSource {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            VersionSource::Env(var) => write!(f, "{}", var),
+            VersionSource::Project(file) => write!(f, "{}", file.display()),
+            VersionSource::Default => write!(f, "default"),
+        }
+    }
+}
+
+/// Version picked for an invocation
+#[derive(Debug, Clone, PartialEq)]
+pub struct SelectedVersion {
+    pub version: String,
+    pub source: VersionSource,
+}
+
+impl fmt::Display for SelectedVersion {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} (from {})", self.version, self.source)
+    }
+}
+
+/// Environment variable selecting the version of `shim_name` (`node` -> `NODE_VERSION`)
+pub fn env_var(shim_name: &str) -> String {
+    let name: String = shim_name
+        .chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() {
+                c.to_ascii_uppercase()
+            } else {
+                '_'
+            }
+        })
+        .collect();
+    format!("{}_VERSION", name)
+}
+
+impl VersionsConfig {
+    /// Validate the installed versions and the default
+    pub fn validate(&self) -> Result<()> {
+        for (version, path) in &self.installed {
+            if version.trim().is_empty() || path.trim().is_empty() {
+                return Err(ShimError::Config(format!(
+                    "versions.installed entry '{}' needs a version and a path",
+                    version
+                )));
+            }
+        }
+        for file in &self.files {
+            if file.trim().is_empty() || file.contains(['/', '\\']) {
+                return Err(ShimError::Config(format!(
+                    "versions.files entries must be plain file names, got '{}'",
+                    file
+                )));
+            }
+        }
+        if let Some(ref default) = self.default {
+            if !self.installed.contains_key(default) {
+                return Err(ShimError::Config(format!(
+                    "versions.default '{}' is not an installed version",
+                    default
+                )));
+            }
+        }
+        Ok(())
+    }
+
+    /// Pick the version for `shim_name`
+    ///
+    /// `project` is the version requested by a project or version file, with that file.
+    pub fn select(
+        &self,
+        shim_name: &str,
+        project: Option<(&str, &Path)>,
+    ) -> Option<SelectedVersion> {
+        let var = env_var(shim_name);
+        if let Some(version) = std::env::var(&var).ok().filter(|v| !v.trim().is_empty()) {
+            return Some(SelectedVersion {
+                version: version.trim().to_string(),
+                source: VersionSource::Env(var),
+            });
+        }
+        if let Some((version, file)) = project {
+            return Some(SelectedVersion {
+                version: version.to_string(),
+                source: VersionSource::Project(file.to_path_buf()),
+            });
+        }
+        self.default.as_ref().map(|version| SelectedVersion {
+            version: version.clone(),
+            source: VersionSource::Default,
+        })
+    }
+
+    /// Version requested by the nearest version file at or above `start`
+    ///
+    /// In each directory the files are tried in the order of `files`; a
+    /// `.tool-versions` without a line for the tool is skipped.
+    pub fn find_version_file(
+        &self,
+        shim_name: &str,
+        start: &Path,
+        discovery: &ProjectConfig,
+    ) -> Option<(String, PathBuf)> {
+        if self.files.is_empty() {
+            return None;
+        }
+        let tool = self.tool_name.as_deref().unwrap_or(shim_name);
+        discovery.ancestors(start).find_map(|dir| {
+            self.files.iter().find_map(|name| {
+                let file = dir.join(name);
+                let version = read_version_file(&file, tool)?;
+                debug!(
+                    "{} requests version {} of '{}'",
+                    file.display(),
+                    version,
+                    tool
+                );
+                Some((version, file))
+            })
+        })
+    }
+
+    /// Executable of `selected`, failing when that version is not installed
+    pub fn path(&self, shim_name: &str, selected: &SelectedVersion) -> Result<&str> {
+        self.installed
+            .get(&selected.version)
+            .map(String::as_str)
+            .ok_or_else(|| {
+                let installed: Vec<&str> = self.installed.keys().map(String::as_str).collect();
+                ShimError::Config(format!(
+                    "Version '{}' of '{}' (from {}) is not installed; installed: {}",
+                    selected.version,
+                    shim_name,
+                    selected.source,
+                    if installed.is_empty() {
+                        "none".to_string()
+                    } else {
+                        installed.join(", ")
+                    }
+                ))
+            })
+    }
+}
+
+/// Order two versions, semver-aware
+///
+/// Versions that parse as semver (with an optional leading `v`) sort by
+/// precedence and before any others, which sort as strings.
+pub fn compare(a: &str, b: &str) -> Ordering {
+    let parse = |v: &str| semver::Version::parse(v.strip_prefix('v').unwrap_or(v)).ok();
+    match (parse(a), parse(b)) {
+        (Some(x), Some(y)) => x.cmp(&y).then_with(|| a.cmp(b)),
+        (Some(_), None) => Ordering::Less,
+        (None, Some(_)) => Ordering::Greater,
+        (None, None) => a.cmp(b),
+    }
+}
+
+/// Version of `tool` requested by a version file
+///
+/// `.tool-versions` lines are `<tool> <version>...`; other files hold just
+/// the version. Comments (`#`) and a leading `v` are ignored.
+fn read_version_file(file: &Path, tool: &str) -> Option<String> {
+    let content = std::fs::read_to_string(file).ok()?;
+    let tool_versions = file
+        .file_name()
+        .is_some_and(|name| name == TOOL_VERSIONS_FILE);
+
+    content.lines().find_map(|line| {
+        let line = line.split('#').next().unwrap_or_default();
+        let mut fields = line.split_whitespace();
+        if tool_versions && fields.next() != Some(tool) {
+            return None;
+        }
+        let version = fields.next()?;
+        Some(version.strip_prefix('v').unwrap_or(version).to_string())
+    })
+}
+
+/// Select the version of a multi-version shim and point `shim.path` at it
+///
+/// Returns `None` for shims without `[versions]` or when nothing selects a version.
+pub fn apply(
+    config: &mut ShimConfig,
+    project: Option<(&str, &Path)>,
+) -> Result<Option<SelectedVersion>> {
+    let Some(ref versions) = config.versions else {
+        return Ok(None);
+    };
+    let Some(selected) = versions.select(&config.shim.name, project) else {
+        return Ok(None);
+    };
+    let path = versions.path(&config.shim.name, &selected)?.to_string();
+    debug!(
+        "Using version {} of '{}': {}",
+        selected, config.shim.name, path
+    );
+
+    config.shim.path = path;
+    config.shim.source_type = SourceType::File;
+    config.shim.download_url = None;
+    config.shim.extracted_executables.clear();
+    config.metadata.version = Some(selected.version.clone());
+    Ok(Some(selected))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn node() -> ShimConfig {
+        let mut config = ShimConfig::new("node", "/opt/node/20/bin/node");
+        config.versions = Some(VersionsConfig {
+            default: Some("20".to_string()),
+            installed: BTreeMap::from([
+                ("18".to_string(), "/opt/node/18/bin/node".to_string()),
+                ("20".to_string(), "/opt/node/20/bin/node".to_string()),
+            ]),
+            ..Default::default()
+        });
+        config
+    }
+
+    #[test]
+    fn test_compare_versions() {
+        let mut versions = vec!["10.0.0", "lts", "9.0.0", "v9.1.0", "9.0.0-rc.1"];
+        versions.sort_by(|a, b| compare(a, b));
+        assert_eq!(
+            versions,
+            vec!["9.0.0-rc.1", "9.0.0", "v9.1.0", "10.0.0", "lts"]
+        );
+    }
+
+    #[test]
+    fn test_env_var_name() {
+        assert_eq!(env_var("node"), "NODE_VERSION");
+        assert_eq!(env_var("my-tool.v2"), "MY_TOOL_V2_VERSION");
+    }
+
+    #[test]
+    fn test_project_then_default() {
+        let mut config = node();
+        let selected = apply(&mut config, Some(("18", Path::new("/work/.shimexe.toml"))))
+            .unwrap()
+            .unwrap();
+        assert_eq!(
+            selected.source,
+            VersionSource::Project(PathBuf::from("/work/.shimexe.toml"))
+        );
+        assert_eq!(config.shim.path, "/opt/node/18/bin/node");
+        assert_eq!(config.metadata.version.as_deref(), Some("18"));
+
+        let mut config = node();
+        let selected = apply(&mut config, None).unwrap().unwrap();
+        assert_eq!(selected.source, VersionSource::Default);
+        assert_eq!(config.shim.path, "/opt/node/20/bin/node");
+    }
+
+    #[test]
+    fn test_missing_version_is_an_error() {
+        let mut config = node();
+        let err = apply(&mut config, Some(("16", Path::new(".shimexe.toml")))).unwrap_err();
+        assert!(err.to_string().contains("installed: 18, 20"), "{}", err);
+    }
+
+    #[test]
+    fn test_validate_default() {
+        let versions = VersionsConfig {
+            default: Some("1.0".to_string()),
+            ..Default::default()
+        };
+        assert!(versions.validate().is_err());
+    }
+
+    #[test]
+    fn test_version_files() {
+        let dir = tempfile::tempdir().unwrap();
+        let nested = dir.path().join("app").join("src");
+        std::fs::create_dir_all(&nested).unwrap();
+        std::fs::write(
+            dir.path().join(TOOL_VERSIONS_FILE),
+            "# pinned tools\npython 3.11.4 3.10.0\nnodejs 18.17.0\n",
+        )
+        .unwrap();
+        std::fs::write(
+            dir.path().join("app").join(TOOL_VERSIONS_FILE),
+            "ruby 3.2.0\n",
+        )
+        .unwrap();
+
+        let versions = VersionsConfig {
+            files: vec![".node-version".to_string(), TOOL_VERSIONS_FILE.to_string()],
+            tool_name: Some("nodejs".to_string()),
+            ..Default::default()
+        };
+        let discovery = ProjectConfig::default();
+        // app/.tool-versions has no nodejs line, so the search goes on upward
+        assert_eq!(
+            versions.find_version_file("node", &nested, &discovery),
+            Some(("18.17.0".to_string(), dir.path().join(TOOL_VERSIONS_FILE)))
+        );
+
+        std::fs::write(dir.path().join("app").join(".node-version"), "v20.5.0\n").unwrap();
+        assert_eq!(
+            versions.find_version_file("node", &nested, &discovery),
+            Some((
+                "20.5.0".to_string(),
+                dir.path().join("app").join(".node-version")
+            ))
+        );
+    }
+}
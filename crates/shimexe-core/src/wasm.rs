@@ -0,0 +1,300 @@
+//! WebAssembly (WASI) targets (`[wasm]`).
+//!
+//! A target whose path ends in `.wasm` is run in an embedded WASI runtime
+//! instead of being executed as a process, so one module works on every
+//! platform. The guest only sees the arguments, the environment variables set
+//! by the shim configuration and the directories listed in `[wasm]`.
+//! Requires the `wasm` feature.
+
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+use crate::env_from::EnvDiff;
+use crate::error::{Result, ShimError};
+use crate::template::TemplateEngine;
+
+/// WASI settings for `.wasm` targets
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct WasmConfig {
+    /// Host directories made available to the guest
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub preopen: Vec<Preopen>,
+    /// Pass the whole host environment instead of only the shim's variables
+    #[serde(default)]
+    pub inherit_env: bool,
+}
+
+/// A host directory mapped into the guest
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Preopen {
+    /// Directory on the host (templates allowed, relative to the working directory)
+    pub host: String,
+    /// Path the guest sees (default: same as `host`)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub guest: Option<String>,
+    /// Deny writes to the directory
+    #[serde(default)]
+    pub read_only: bool,
+}
+
+/// A preopen with its host path rendered
+#[derive(Debug, Clone, PartialEq)]
+pub struct ResolvedPreopen {
+    pub host: PathBuf,
+    pub guest: String,
+    pub read_only: bool,
+}
+
+/// Whether `path` is a WebAssembly module run by the embedded runtime
+pub fn is_wasm(path: &Path) -> bool {
+    path.extension()
+        .is_some_and(|extension| extension.eq_ignore_ascii_case("wasm"))
+}
+
+impl WasmConfig {
+    /// Validate the preopened directories
+    pub fn validate(&self) -> Result<()> {
+        if self.preopen.iter().any(|p| p.host.trim().is_empty()) {
+            return Err(ShimError::Config(
+                "wasm.preopen host directories must not be empty".to_string(),
+            ));
+        }
+        Ok(())
+    }
+
+    /// Render the preopened directories for a guest running in `cwd`
+    pub fn preopens(
+        &self,
+        engine: &mut TemplateEngine,
+        cwd: &Path,
+    ) -> Result<Vec<ResolvedPreopen>> {
+        self.preopen
+            .iter()
+            .map(|preopen| {
+                let host = engine.render_template(&preopen.host)?;
+                let guest = match preopen.guest {
+                    Some(ref guest) => engine.render_template(guest)?,
+                    None => host.clone(),
+                };
+                Ok(ResolvedPreopen {
+                    host: cwd.join(host),
+                    guest,
+                    read_only: preopen.read_only,
+                })
+            })
+            .collect()
+    }
+
+    /// Arguments, environment and directories for one run of the guest
+    ///
+    /// Without `inherit_env` the guest only sees the variables the shim sets.
+    pub fn invocation(
+        &self,
+        engine: &mut TemplateEngine,
+        program: &str,
+        argv: &[String],
+        env: &EnvDiff,
+        cwd: &Path,
+    ) -> Result<WasmInvocation> {
+        let mut args = vec![program.to_string()];
+        args.extend_from_slice(argv);
+
+        let mut vars: BTreeMap<String, String> = if self.inherit_env {
+            std::env::vars()
+                .filter(|(key, _)| !env.unset.contains(key))
+                .collect()
+        } else {
+            BTreeMap::new()
+        };
+        vars.extend(env.set.clone());
+
+        Ok(WasmInvocation {
+            args,
+            env: vars.into_iter().collect(),
+            preopens: self.preopens(engine, cwd)?,
+        })
+    }
+}
+
+/// Everything a guest run needs besides the module
+#[derive(Debug, Clone, Default)]
+pub struct WasmInvocation {
+    /// `argv[0]` followed by the arguments
+    pub args: Vec<String>,
+    pub env: Vec<(String, String)>,
+    pub preopens: Vec<ResolvedPreopen>,
+}
+
+#[cfg(feature = "wasm")]
+pub use runtime::WasmModule;
+
+#[cfg(feature = "wasm")]
+mod runtime {
+    use std::path::Path;
+    use std::time::Duration;
+
+    use tracing::debug;
+    use wasmtime::{Config, Engine, Linker, Module, Store};
+    use wasmtime_wasi::preview1::{self, WasiP1Ctx};
+    use wasmtime_wasi::{DirPerms, FilePerms, I32Exit, WasiCtxBuilder};
+
+    use super::WasmInvocation;
+    use crate::error::{Result, ShimError};
+    use crate::process::TIMEOUT_EXIT_CODE;
+
+    /// A compiled WASI module
+    pub struct WasmModule {
+        engine: Engine,
+        module: Module,
+    }
+
+    fn wasm_error(context: &str, error: impl std::fmt::Display) -> ShimError {
+        ShimError::ProcessExecution(format!("{}: {}", context, error))
+    }
+
+    impl WasmModule {
+        /// Compile the module at `path`
+        pub fn load(path: &Path) -> Result<Self> {
+            let mut config = Config::new();
+            config.epoch_interruption(true);
+            let engine = Engine::new(&config).map_err(|e| wasm_error("WASM engine", e))?;
+            let module = Module::from_file(&engine, path)
+                .map_err(|e| wasm_error(&format!("Failed to load {}", path.display()), e))?;
+            Ok(Self { engine, module })
+        }
+
+        /// Run the module's `_start` and return its exit code
+        ///
+        /// The guest runs on its own thread, outside any async runtime of the
+        /// caller. `timeout` interrupts it, exiting with the timeout exit code.
+        pub fn run(&self, invocation: &WasmInvocation, timeout: Option<Duration>) -> Result<i32> {
+            let mut wasi = WasiCtxBuilder::new();
+            wasi.inherit_stdio()
+                .args(&invocation.args)
+                .envs(&invocation.env);
+            for preopen in &invocation.preopens {
+                let (dir_perms, file_perms) = if preopen.read_only {
+                    (DirPerms::READ, FilePerms::READ)
+                } else {
+                    (DirPerms::all(), FilePerms::all())
+                };
+                wasi.preopened_dir(&preopen.host, &preopen.guest, dir_perms, file_perms)
+                    .map_err(|e| {
+                        wasm_error(&format!("Failed to open {}", preopen.host.display()), e)
+                    })?;
+            }
+            let mut store = Store::new(&self.engine, wasi.build_p1());
+            store.set_epoch_deadline(1);
+
+            let mut linker: Linker<WasiP1Ctx> = Linker::new(&self.engine);
+            preview1::add_to_linker_sync(&mut linker, |ctx| ctx)
+                .map_err(|e| wasm_error("WASI", e))?;
+
+            std::thread::scope(|scope| {
+                let guest = scope.spawn(move || {
+                    let instance = linker
+                        .instantiate(&mut store, &self.module)
+                        .map_err(|e| wasm_error("Failed to instantiate module", e))?;
+                    let start = instance
+                        .get_typed_func::<(), ()>(&mut store, "_start")
+                        .map_err(|e| wasm_error("Module has no WASI entry point", e))?;
+                    Ok::<_, ShimError>(start.call(&mut store, ()))
+                });
+
+                if let Some(timeout) = timeout {
+                    let deadline = std::time::Instant::now() + timeout;
+                    while !guest.is_finished() {
+                        if std::time::Instant::now() >= deadline {
+                            debug!("WASM guest timed out, interrupting it");
+                            self.engine.increment_epoch();
+                            break;
+                        }
+                        std::thread::sleep(Duration::from_millis(10));
+                    }
+                }
+
+                let result = guest
+                    .join()
+                    .map_err(|_| ShimError::ProcessExecution("WASM guest panicked".to_string()))?;
+                match result? {
+                    Ok(()) => Ok(0),
+                    Err(e) => {
+                        if let Some(exit) = e.downcast_ref::<I32Exit>() {
+                            return Ok(exit.0);
+                        }
+                        if matches!(
+                            e.downcast_ref::<wasmtime::Trap>(),
+                            Some(wasmtime::Trap::Interrupt)
+                        ) {
+                            return Ok(TIMEOUT_EXIT_CODE);
+                        }
+                        Err(wasm_error("WASM guest trapped", e))
+                    }
+                }
+            })
+        }
+    }
+}
+
+/// Stand-in used when shimexe is built without the `wasm` feature
+#[cfg(not(feature = "wasm"))]
+pub struct WasmModule;
+
+#[cfg(not(feature = "wasm"))]
+impl WasmModule {
+    /// Always fails: the embedded runtime is not compiled in
+    pub fn load(path: &Path) -> Result<Self> {
+        Err(ShimError::Config(format!(
+            "{} is a WebAssembly module, but shimexe was built without the `wasm` feature; \
+             set `interpreter = \"auto\"` to run it with the wasmtime CLI instead",
+            path.display()
+        )))
+    }
+
+    pub fn run(
+        &self,
+        _invocation: &WasmInvocation,
+        _timeout: Option<std::time::Duration>,
+    ) -> Result<i32> {
+        unreachable!("WasmModule cannot be loaded without the `wasm` feature")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_is_wasm() {
+        assert!(is_wasm(Path::new("/tools/fmt.wasm")));
+        assert!(is_wasm(Path::new("FMT.WASM")));
+        assert!(!is_wasm(Path::new("/usr/bin/fmt")));
+    }
+
+    #[test]
+    fn test_preopens_are_rendered() {
+        let config: WasmConfig = toml::from_str(
+            r#"
+[[preopen]]
+host = "."
+
+[[preopen]]
+host = "{{env('WASM_TEST_UNSET', '/srv/data')}}"
+guest = "/data"
+read_only = true
+"#,
+        )
+        .unwrap();
+        config.validate().unwrap();
+
+        let preopens = config
+            .preopens(&mut TemplateEngine::new(vec![]), Path::new("/work"))
+            .unwrap();
+        assert_eq!(preopens[0].host, Path::new("/work/."));
+        assert_eq!(preopens[0].guest, ".");
+        assert_eq!(preopens[1].host, Path::new("/srv/data"));
+        assert_eq!(preopens[1].guest, "/data");
+        assert!(preopens[1].read_only);
+    }
+}
@@ -0,0 +1,10 @@
+// Fixtures shared by the runner integration tests
+
+use shimexe_core::ShimConfig;
+
+/// Shim `name` running `script` with `sh -c`
+pub fn sh_config(name: &str, script: &str) -> ShimConfig {
+    let mut config = ShimConfig::new(name, "sh");
+    config.shim.args = vec!["-c".to_string(), script.to_string()];
+    config
+}
@@ -14,11 +14,13 @@ fn write_config(tmp: &mut NamedTempFile, name: &str) {
             download_url: None,
             source_type: SourceType::File,
             extracted_executables: vec![],
+            ..Default::default()
         },
         args: Default::default(),
         env: Default::default(),
         metadata: Default::default(),
         auto_update: None,
+        ..Default::default()
     };
     cfg.to_file(tmp.path()).unwrap();
 }
@@ -15,11 +15,13 @@ fn test_config_from_and_to_file_roundtrip() {
             download_url: None,
             source_type: SourceType::File,
             extracted_executables: vec![],
+            ..Default::default()
         },
         args: Default::default(),
         env: Default::default(),
         metadata: Default::default(),
         auto_update: None,
+        ..Default::default()
     };
 
     let tmp = NamedTempFile::new().unwrap();
@@ -43,11 +45,13 @@ fn test_get_executable_path_archive_primary() {
             download_url: None,
             source_type: SourceType::Archive,
             extracted_executables: vec![],
+            ..Default::default()
         },
         args: Default::default(),
         env: Default::default(),
         metadata: Default::default(),
         auto_update: None,
+        ..Default::default()
     };
 
     assert!(cfg.get_executable_path().is_err());
@@ -33,11 +33,13 @@ fn test_archive_first_executable_when_no_primary() {
                     is_primary: false,
                 },
             ],
+            ..Default::default()
         },
         args: Default::default(),
         env: Default::default(),
         metadata: Default::default(),
         auto_update: None,
+        ..Default::default()
     };
 
     let p = cfg.get_executable_path().unwrap();
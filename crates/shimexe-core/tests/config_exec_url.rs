@@ -13,11 +13,13 @@ fn test_get_executable_path_url_no_file_yet() {
             download_url: None,
             source_type: SourceType::Url,
             extracted_executables: vec![],
+            ..Default::default()
         },
         args: Default::default(),
         env: Default::default(),
         metadata: Default::default(),
         auto_update: None,
+        ..Default::default()
     };
 
     let err = cfg.get_executable_path().unwrap_err();
@@ -36,11 +38,13 @@ fn test_get_executable_path_legacy_url_path() {
             download_url: None,
             source_type: SourceType::Url,
             extracted_executables: vec![],
+            ..Default::default()
         },
         args: Default::default(),
         env: Default::default(),
         metadata: Default::default(),
         auto_update: None,
+        ..Default::default()
     };
 
     let err = cfg.get_executable_path().unwrap_err();
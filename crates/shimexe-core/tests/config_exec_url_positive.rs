@@ -28,11 +28,13 @@ fn test_get_executable_path_url_positive_with_home() {
             download_url: Some(format!("https://host/{filename}")),
             source_type: SourceType::Url,
             extracted_executables: vec![],
+            ..Default::default()
         },
         args: Default::default(),
         env: Default::default(),
         metadata: Default::default(),
         auto_update: None,
+        ..Default::default()
     };
 
     let p = cfg.get_executable_path().unwrap();
@@ -25,11 +25,13 @@ fn test_get_executable_path_archive_primary_ok() {
                 full_path: exe_file.to_string_lossy().to_string(),
                 is_primary: true,
             }],
+            ..Default::default()
         },
         args: Default::default(),
         env: Default::default(),
         metadata: Default::default(),
         auto_update: None,
+        ..Default::default()
     };
 
     let got = cfg.get_executable_path().unwrap();
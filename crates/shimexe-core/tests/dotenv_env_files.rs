@@ -0,0 +1,66 @@
+// Tests for env_files loading, resolution and precedence between files
+
+use shimexe_core::dotenv::{load_env_files, EnvFile, RelativeTo};
+use shimexe_core::ShimConfig;
+
+#[test]
+fn test_load_env_files_relative_to_shim_dir_and_override_order() {
+    let dir = tempfile::tempdir().unwrap();
+    std::fs::write(dir.path().join("base.env"), "A=1\nB=from_base\n").unwrap();
+    std::fs::write(dir.path().join("local.env"), "B=from_local\nC=${B}_c\n").unwrap();
+
+    let files = vec![
+        EnvFile::Path("base.env".to_string()),
+        EnvFile::Path("local.env".to_string()),
+    ];
+    let vars = load_env_files(&files, Some(dir.path())).unwrap();
+
+    assert_eq!(vars.get("A").map(String::as_str), Some("1"));
+    assert_eq!(vars.get("B").map(String::as_str), Some("from_local"));
+    assert_eq!(vars.get("C").map(String::as_str), Some("from_local_c"));
+}
+
+#[test]
+fn test_load_env_files_missing_required_and_optional() {
+    let dir = tempfile::tempdir().unwrap();
+
+    let required = vec![EnvFile::Path("missing.env".to_string())];
+    assert!(load_env_files(&required, Some(dir.path())).is_err());
+
+    let optional = vec![EnvFile::Detailed {
+        path: "missing.env".to_string(),
+        optional: true,
+        relative_to: RelativeTo::Shim,
+    }];
+    assert!(load_env_files(&optional, Some(dir.path()))
+        .unwrap()
+        .is_empty());
+}
+
+#[test]
+fn test_env_files_config_forms() {
+    let dir = tempfile::tempdir().unwrap();
+    let shim_file = dir.path().join("tool.shim.toml");
+    std::fs::write(
+        &shim_file,
+        r#"
+env_files = [".env", { path = "${HOME}/.config/tool/env", optional = true, relative_to = "cwd" }]
+
+[shim]
+name = "tool"
+path = "echo"
+"#,
+    )
+    .unwrap();
+
+    let config = ShimConfig::from_file(&shim_file).unwrap();
+    assert_eq!(config.env_files.len(), 2);
+    assert_eq!(config.env_files[0], EnvFile::Path(".env".to_string()));
+    assert!(config.env_files[1].is_optional());
+    assert_eq!(config.env_files[1].relative_to(), RelativeTo::Cwd);
+
+    // Round-trips through serialization
+    config.to_file(&shim_file).unwrap();
+    let reloaded = ShimConfig::from_file(&shim_file).unwrap();
+    assert_eq!(reloaded.env_files, config.env_files);
+}
@@ -0,0 +1,95 @@
+// Tests for [env_from] script sourcing, command output capture and caching
+
+#![cfg(unix)]
+
+use shimexe_core::{EnvCapture, EnvFrom, ShimConfig};
+
+fn script_env_from(script: &str, ttl_secs: u64) -> EnvFrom {
+    EnvFrom {
+        script: Some(script.to_string()),
+        command: None,
+        capture: EnvCapture::Diff,
+        shell: None,
+        ttl_secs,
+    }
+}
+
+#[test]
+fn test_env_from_script_diff_and_cache() {
+    let dir = tempfile::tempdir().unwrap();
+    let cache_dir = dir.path().join("data");
+    std::fs::write(
+        dir.path().join("activate"),
+        "export ENV_FROM_TEST_ADDED=\"venv value\"\nunset ENV_FROM_TEST_REMOVED\necho activated\n",
+    )
+    .unwrap();
+    std::env::set_var("ENV_FROM_TEST_REMOVED", "1");
+
+    let env_from = script_env_from("activate", 3600);
+    let diff = env_from
+        .resolve(Some(dir.path()), Some(&cache_dir))
+        .unwrap();
+    assert_eq!(
+        diff.set.get("ENV_FROM_TEST_ADDED").map(String::as_str),
+        Some("venv value")
+    );
+    assert!(diff.unset.contains(&"ENV_FROM_TEST_REMOVED".to_string()));
+    assert!(!diff.set.contains_key("PATH"));
+    assert!(cache_dir.join("env_from.json").exists());
+
+    // A cached result is returned as long as the script is unchanged
+    let cached = env_from
+        .resolve(Some(dir.path()), Some(&cache_dir))
+        .unwrap();
+    assert_eq!(cached, diff);
+
+    std::env::remove_var("ENV_FROM_TEST_REMOVED");
+}
+
+#[test]
+fn test_env_from_command_output() {
+    let env_from = EnvFrom {
+        script: None,
+        command: Some("echo 'TOOL_ROOT=/opt/tool'; echo 'TOOL_BIN=${TOOL_ROOT}/bin'".to_string()),
+        capture: EnvCapture::Output,
+        shell: None,
+        ttl_secs: 0,
+    };
+
+    let diff = env_from.resolve(None, None).unwrap();
+    assert_eq!(
+        diff.set.get("TOOL_BIN").map(String::as_str),
+        Some("/opt/tool/bin")
+    );
+    assert!(diff.unset.is_empty());
+}
+
+#[test]
+fn test_env_from_failing_script_is_an_error() {
+    let dir = tempfile::tempdir().unwrap();
+    std::fs::write(dir.path().join("broken"), "exit 3\n").unwrap();
+
+    let env_from = script_env_from("broken", 0);
+    assert!(env_from.resolve(Some(dir.path()), None).is_err());
+}
+
+#[test]
+fn test_env_from_config_validation() {
+    let dir = tempfile::tempdir().unwrap();
+    let shim_file = dir.path().join("tool.shim.toml");
+    std::fs::write(
+        &shim_file,
+        r#"
+[shim]
+name = "tool"
+path = "echo"
+
+[env_from]
+script = "activate"
+command = "opam env"
+"#,
+    )
+    .unwrap();
+
+    assert!(ShimConfig::from_file(&shim_file).is_err());
+}
@@ -22,14 +22,16 @@ fn test_list_and_validate_invalid_exec() {
             download_url: None,
             source_type: SourceType::File,
             extracted_executables: vec![],
+            ..Default::default()
         },
         args: Default::default(),
         env: Default::default(),
         metadata: Default::default(),
         auto_update: None,
+        ..Default::default()
     };
 
-    cfg.to_file(&tmp.path().join("badexec.shim.toml")).unwrap();
+    cfg.to_file(tmp.path().join("badexec.shim.toml")).unwrap();
 
     // list_shims shows it
     let items = mgr.list_shims().unwrap();
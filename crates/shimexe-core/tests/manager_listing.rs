@@ -18,11 +18,13 @@ fn test_manager_list_and_get() {
             download_url: None,
             source_type: SourceType::File,
             extracted_executables: vec![],
+            ..Default::default()
         },
         args: Default::default(),
         env: Default::default(),
         metadata: Default::default(),
         auto_update: None,
+        ..Default::default()
     };
 
     // Save config the same way create_shim would
@@ -37,5 +39,5 @@ fn test_manager_list_and_get() {
     // get_shim should return metadata and validity state
     let got = mgr.get_shim("echoer").unwrap().unwrap();
     assert_eq!(got.name, "echoer");
-    assert_eq!(got.is_valid, false); // shim exe not created in test
+    assert!(!got.is_valid); // shim exe not created in test
 }
@@ -0,0 +1,101 @@
+// Tests for multi-version shims managed through ShimManager and selected by ShimRunner
+
+use shimexe_core::manager::ShimManager;
+use shimexe_core::ShimRunner;
+
+#[test]
+fn test_install_use_and_uninstall_versions() {
+    let tmp = tempfile::tempdir().unwrap();
+    let mgr = ShimManager::new(tmp.path().to_path_buf()).unwrap();
+
+    mgr.install_version("vertool", "1.0.0", "/opt/vertool/1.0.0/vertool")
+        .unwrap();
+    mgr.install_version("vertool", "2.0.0", "/opt/vertool/2.0.0/vertool")
+        .unwrap();
+
+    let versions = mgr.list_versions("vertool").unwrap();
+    let listed: Vec<(&str, bool)> = versions
+        .iter()
+        .map(|v| (v.version.as_str(), v.is_default))
+        .collect();
+    assert_eq!(listed, vec![("1.0.0", true), ("2.0.0", false)]);
+
+    mgr.use_version("vertool", "2.0.0").unwrap();
+    let info = mgr.get_shim("vertool").unwrap().unwrap();
+    assert_eq!(info.path, "/opt/vertool/2.0.0/vertool");
+    assert_eq!(info.version.as_deref(), Some("2.0.0"));
+
+    assert!(mgr.use_version("vertool", "3.0.0").is_err());
+    assert!(mgr.uninstall_version("vertool", "2.0.0").is_err());
+    mgr.uninstall_version("vertool", "1.0.0").unwrap();
+    assert_eq!(mgr.list_versions("vertool").unwrap().len(), 1);
+
+    // Removing the last version removes the shim
+    mgr.uninstall_version("vertool", "2.0.0").unwrap();
+    assert!(mgr.get_shim("vertool").unwrap().is_none());
+}
+
+#[test]
+fn test_runner_selects_version_from_env() {
+    let tmp = tempfile::tempdir().unwrap();
+    let mgr = ShimManager::new(tmp.path().to_path_buf()).unwrap();
+    mgr.install_version("envtool", "1.0.0", "/opt/envtool/1/envtool")
+        .unwrap();
+    mgr.install_version("envtool", "2.0.0", "/opt/envtool/2/envtool")
+        .unwrap();
+    let shim_file = tmp.path().join("envtool.shim.toml");
+
+    let runner = ShimRunner::from_file(&shim_file).unwrap();
+    assert_eq!(runner.config().shim.path, "/opt/envtool/1/envtool");
+
+    std::env::set_var("ENVTOOL_VERSION", "2.0.0");
+    let runner = ShimRunner::from_file(&shim_file).unwrap();
+    assert_eq!(runner.config().shim.path, "/opt/envtool/2/envtool");
+    let explanation = runner.explain(&[]).unwrap();
+    assert_eq!(
+        explanation.version.as_deref(),
+        Some("2.0.0 (from ENVTOOL_VERSION)")
+    );
+
+    std::env::set_var("ENVTOOL_VERSION", "3.0.0");
+    let err = ShimRunner::from_file(&shim_file).err().unwrap();
+    assert!(
+        err.to_string().contains("installed: 1.0.0, 2.0.0"),
+        "{}",
+        err
+    );
+    std::env::remove_var("ENVTOOL_VERSION");
+}
+
+#[test]
+fn test_versions_are_listed_in_version_order() {
+    let tmp = tempfile::tempdir().unwrap();
+    let mgr = ShimManager::new(tmp.path().to_path_buf()).unwrap();
+    for version in ["10.0.0", "9.0.0", "9.1.0"] {
+        mgr.install_version("ordtool", version, format!("/opt/ordtool/{}", version))
+            .unwrap();
+    }
+
+    let versions = mgr.list_versions("ordtool").unwrap();
+    let listed: Vec<&str> = versions.iter().map(|v| v.version.as_str()).collect();
+    assert_eq!(listed, vec!["9.0.0", "9.1.0", "10.0.0"]);
+}
+
+#[test]
+fn test_install_version_keeps_unversioned_executable() {
+    let tmp = tempfile::tempdir().unwrap();
+    let mgr = ShimManager::new(tmp.path().to_path_buf()).unwrap();
+    let config = mgr
+        .builder("plaintool")
+        .path("/usr/bin/plaintool")
+        .build()
+        .unwrap();
+    mgr.create_shim(config).unwrap();
+
+    assert!(mgr
+        .install_version("plaintool", "2.0.0", "/opt/plaintool/2/plaintool")
+        .is_err());
+    let info = mgr.get_shim("plaintool").unwrap().unwrap();
+    assert_eq!(info.path, "/usr/bin/plaintool");
+    assert!(mgr.list_versions("plaintool").unwrap().is_empty());
+}
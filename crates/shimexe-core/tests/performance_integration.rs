@@ -14,6 +14,7 @@ fn create_test_config() -> ShimConfig {
             download_url: None,
             source_type: shimexe_core::config::SourceType::File,
             extracted_executables: vec![],
+            ..Default::default()
         },
         args: Default::default(),
         env: {
@@ -24,6 +25,7 @@ fn create_test_config() -> ShimConfig {
         },
         metadata: Default::default(),
         auto_update: None,
+        ..Default::default()
     }
 }
 
@@ -214,7 +216,6 @@ fn test_memory_usage_optimization() {
 
     // This test mainly ensures we don't panic or crash
     // In a real scenario, you might use a memory profiler
-    assert!(true, "Memory usage test completed without issues");
 }
 
 #[test]
@@ -0,0 +1,113 @@
+// Tests for alias shims (shim.target_shim) loaded through ShimRunner
+
+#![cfg(unix)]
+
+use shimexe_core::{ShimConfig, ShimRunner, SourceType};
+
+#[test]
+fn test_alias_runs_target_with_extra_args_and_env() {
+    let dir = tempfile::tempdir().unwrap();
+    let mut base = ShimConfig::new("check", "sh");
+    base.shim.args = vec!["-c".to_string()];
+    base.to_file(dir.path().join("check.shim.toml")).unwrap();
+
+    let mut alias = ShimConfig::new("strict-check", "");
+    alias.shim.target_shim = Some("check".to_string());
+    alias.shim.args = vec!["printenv CHECK_MODE | grep -qx strict".to_string()];
+    alias
+        .env
+        .insert("CHECK_MODE".to_string(), "strict".to_string());
+    let alias_file = dir.path().join("strict-check.shim.toml");
+    alias.to_file(&alias_file).unwrap();
+
+    let runner = ShimRunner::from_file(&alias_file)
+        .unwrap()
+        .with_history(None);
+    assert_eq!(runner.config().shim.name, "strict-check");
+    assert_eq!(runner.config().shim.path, "sh");
+    assert_eq!(runner.execute(&[]).unwrap(), 0);
+}
+
+#[test]
+fn test_alias_cycle_fails_to_load() {
+    let dir = tempfile::tempdir().unwrap();
+    for (name, target) in [("a", "b"), ("b", "a")] {
+        let mut alias = ShimConfig::new(name, "");
+        alias.shim.target_shim = Some(target.to_string());
+        alias
+            .to_file(dir.path().join(format!("{}.shim.toml", name)))
+            .unwrap();
+    }
+
+    let err = ShimRunner::from_file(dir.path().join("a.shim.toml"))
+        .err()
+        .unwrap();
+    assert!(err.to_string().contains("Alias cycle"), "{}", err);
+}
+
+#[test]
+fn test_alias_of_url_shim_shares_target_downloads() {
+    let dir = tempfile::tempdir().unwrap();
+    let url = "https://example.com/releases/alias-python-3.11.4";
+    let mut python = ShimConfig::new("python", url);
+    python.shim.source_type = SourceType::Url;
+    python.shim.download_url = Some(url.to_string());
+    python.to_file(dir.path().join("python.shim.toml")).unwrap();
+
+    let mut alias = ShimConfig::new("py311", "");
+    alias.shim.target_shim = Some("python".to_string());
+    let alias_file = dir.path().join("py311.shim.toml");
+    alias.to_file(&alias_file).unwrap();
+
+    let runner = ShimRunner::from_file(&alias_file)
+        .unwrap()
+        .with_history(None);
+    assert_eq!(runner.config().shim.name, "py311");
+    assert_eq!(runner.data_dir().unwrap(), dir.path().join("python"));
+
+    let explanation = runner.explain(&[]).unwrap();
+    let download_dir = dir.path().join("python").join("bin");
+    assert_eq!(
+        explanation.pending,
+        vec![format!("download {} into {}", url, download_dir.display())]
+    );
+}
+
+#[test]
+fn test_alias_appends_rewrite_rules() {
+    let dir = tempfile::tempdir().unwrap();
+    std::fs::write(
+        dir.path().join("check.shim.toml"),
+        r#"
+[shim]
+name = "check"
+path = "sh"
+args = ["-c"]
+
+[[args.rewrite]]
+match = "^exit 3$"
+replace = "exit 4"
+"#,
+    )
+    .unwrap();
+    let alias_file = dir.path().join("lenient-check.shim.toml");
+    std::fs::write(
+        &alias_file,
+        r#"
+[shim]
+name = "lenient-check"
+target_shim = "check"
+
+[[args.rewrite]]
+match = "^exit 4$"
+replace = "exit 0"
+"#,
+    )
+    .unwrap();
+
+    let runner = ShimRunner::from_file(&alias_file)
+        .unwrap()
+        .with_history(None);
+    assert_eq!(runner.config().args.rewrite.len(), 2);
+    assert_eq!(runner.execute(&["exit 3".to_string()]).unwrap(), 0);
+}
@@ -0,0 +1,55 @@
+// Tests for the argv ShimRunner passes to the target
+
+#![cfg(unix)]
+
+use shimexe_core::{ArgsConfig, ArgsMode, ShimConfig, ShimRunner};
+
+/// A target that writes each of its arguments on its own line to `out`
+fn echo_args_config(out: &std::path::Path) -> ShimConfig {
+    let mut config = ShimConfig::new("echo-args", "sh");
+    config.shim.args = vec![
+        "-c".to_string(),
+        format!("printf '%s\\n' \"$@\" > '{}'", out.display()),
+        "sh".to_string(),
+    ];
+    config
+}
+
+fn received(out: &std::path::Path) -> Vec<String> {
+    std::fs::read_to_string(out)
+        .unwrap()
+        .lines()
+        .map(String::from)
+        .collect()
+}
+
+#[test]
+fn test_argv_without_args_section_is_unchanged() {
+    let dir = tempfile::tempdir().unwrap();
+    let out = dir.path().join("argv");
+
+    let runner = ShimRunner::from_config(echo_args_config(&out))
+        .unwrap()
+        .with_history(None);
+    let user_args = vec!["--flag".to_string(), "two words".to_string()];
+    assert_eq!(runner.execute(&user_args).unwrap(), 0);
+    assert_eq!(received(&out), user_args);
+}
+
+#[test]
+fn test_args_section_is_rendered() {
+    let dir = tempfile::tempdir().unwrap();
+    let out = dir.path().join("argv");
+
+    let mut config = echo_args_config(&out);
+    config.args = ArgsConfig {
+        mode: ArgsMode::Merge,
+        prefix: vec!["--prefix".to_string()],
+        default: vec!["--default".to_string()],
+        ..Default::default()
+    };
+
+    let runner = ShimRunner::from_config(config).unwrap().with_history(None);
+    assert_eq!(runner.execute(&["--user".to_string()]).unwrap(), 0);
+    assert_eq!(received(&out), ["--prefix", "--default", "--user"]);
+}
@@ -0,0 +1,66 @@
+// Tests for [concurrency] limiting parallel runs through ShimRunner
+
+#![cfg(unix)]
+
+use shimexe_core::{ShimError, ShimRunner};
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
+
+/// A shim sleeping for a while, limited to one instance
+fn write_shim(dir: &Path, lock_wait: &str) -> PathBuf {
+    let shim_file = dir.join("single.shim.toml");
+    std::fs::write(
+        &shim_file,
+        format!(
+            "[shim]\nname = \"single\"\npath = \"sh\"\nargs = [\"-c\", \"sleep 1\"]\n\n\
+             [concurrency]\nmax_instances = 1\nlock_wait = \"{}\"\n",
+            lock_wait
+        ),
+    )
+    .unwrap();
+    shim_file
+}
+
+fn run_in_background(shim_file: &Path) -> std::thread::JoinHandle<i32> {
+    let runner = ShimRunner::from_file(shim_file).unwrap().with_history(None);
+    let handle = std::thread::spawn(move || runner.execute(&[]).unwrap());
+    // Give the first run time to take the lock
+    std::thread::sleep(Duration::from_millis(300));
+    handle
+}
+
+#[test]
+fn test_second_instance_fails() {
+    let dir = tempfile::tempdir().unwrap();
+    let shim_file = write_shim(dir.path(), "fail");
+
+    let first = run_in_background(&shim_file);
+    let runner = ShimRunner::from_file(&shim_file)
+        .unwrap()
+        .with_history(None);
+    assert!(matches!(
+        runner.execute(&[]),
+        Err(ShimError::InstanceLimit(_))
+    ));
+
+    assert_eq!(first.join().unwrap(), 0);
+    assert_eq!(runner.execute(&[]).unwrap(), 0);
+    assert!(dir.path().join("single/locks/instance-0.lock").exists());
+}
+
+#[test]
+fn test_second_instance_waits() {
+    let dir = tempfile::tempdir().unwrap();
+    let shim_file = write_shim(dir.path(), "wait");
+
+    let start = Instant::now();
+    let first = run_in_background(&shim_file);
+    let runner = ShimRunner::from_file(&shim_file)
+        .unwrap()
+        .with_history(None);
+    assert_eq!(runner.execute(&[]).unwrap(), 0);
+    assert_eq!(first.join().unwrap(), 0);
+
+    // The runs happened one after the other
+    assert!(start.elapsed() >= Duration::from_secs(2));
+}
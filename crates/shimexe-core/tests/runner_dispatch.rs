@@ -0,0 +1,86 @@
+// Tests for [dispatch] routing subcommands through ShimRunner
+
+#![cfg(unix)]
+
+use shimexe_core::{DispatchConfig, ShimConfig, ShimRunner};
+
+fn args(args: &[&str]) -> Vec<String> {
+    args.iter().map(|s| s.to_string()).collect()
+}
+
+fn dispatch_runner(toml_str: &str) -> ShimRunner {
+    let mut config = ShimConfig::new("tool", "sh");
+    config.shim.args = args(&["-c", "exit 10"]);
+    config.dispatch = Some(toml::from_str::<DispatchConfig>(toml_str).unwrap());
+    config.validate().unwrap();
+    ShimRunner::from_config(config).unwrap().with_history(None)
+}
+
+#[test]
+fn test_routes_by_name_and_alias() {
+    let runner = dispatch_runner(
+        r#"
+[routes.build]
+path = "sh"
+aliases = ["b"]
+args = ["-c", "exit 3"]
+"#,
+    );
+
+    assert_eq!(runner.execute(&args(&["build"])).unwrap(), 3);
+    assert_eq!(runner.execute(&args(&["b"])).unwrap(), 3);
+    // Anything else runs shim.path
+    assert_eq!(runner.execute(&args(&["deploy"])).unwrap(), 10);
+    assert_eq!(runner.execute(&[]).unwrap(), 10);
+}
+
+#[test]
+fn test_route_env_override() {
+    let runner = dispatch_runner(
+        r#"
+[routes.check]
+path = "sh"
+args = ["-c", "printenv ROUTE_MODE | grep -qx strict"]
+env = { ROUTE_MODE = "strict" }
+"#,
+    );
+
+    assert_eq!(runner.execute(&args(&["check"])).unwrap(), 0);
+}
+
+#[test]
+fn test_strip_or_keep_subcommand() {
+    // `test foo = foo` succeeds, `test = foo` is a usage error
+    let runner = dispatch_runner(
+        r#"
+[routes.foo]
+path = "test"
+args = []
+strip = false
+
+[routes."ba*"]
+path = "test"
+args = []
+"#,
+    );
+
+    assert_eq!(runner.execute(&args(&["foo", "=", "foo"])).unwrap(), 0);
+    assert_eq!(runner.execute(&args(&["bar", "=", "bar"])).unwrap(), 2);
+}
+
+#[test]
+fn test_help_and_explain() {
+    let runner = dispatch_runner(
+        r#"
+[routes.build]
+path = "sh"
+description = "Build the project"
+"#,
+    );
+
+    assert_eq!(runner.execute(&args(&["--help"])).unwrap(), 0);
+
+    let explanation = runner.explain(&args(&["build", "--release"])).unwrap();
+    assert!(explanation.resolution.starts_with("dispatch route 'build'"));
+    assert_eq!(explanation.argv, args(&["-c", "exit 10", "--release"]));
+}
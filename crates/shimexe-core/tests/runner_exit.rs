@@ -0,0 +1,67 @@
+// Tests for [exit] normalizing the target's exit status through ShimRunner
+
+#![cfg(unix)]
+
+mod common;
+
+use common::sh_config;
+use shimexe_core::{ExitConfig, LoggingConfig, ShimRunner};
+
+fn exit_config(toml_str: &str) -> ExitConfig {
+    toml::from_str(toml_str).unwrap()
+}
+
+#[test]
+fn test_exit_code_is_mapped() {
+    let mut config = sh_config("vendor", "exit 3");
+    config.exit = Some(exit_config("map = { 3 = 0 }"));
+
+    let runner = ShimRunner::from_config(config).unwrap().with_history(None);
+    assert_eq!(runner.execute(&[]).unwrap(), 0);
+}
+
+#[test]
+fn test_stderr_pattern_fails_successful_run() {
+    let mut config = sh_config(
+        "vendor",
+        "echo 'ERROR: license server unreachable' >&2; exit 0",
+    );
+    config.exit = Some(exit_config(
+        "fail_on_stderr_regex = '^ERROR'\nfailure_code = 9",
+    ));
+
+    let runner = ShimRunner::from_config(config).unwrap().with_history(None);
+    assert_eq!(runner.execute(&[]).unwrap(), 9);
+}
+
+#[test]
+fn test_pattern_on_other_stream_is_ignored() {
+    let mut config = sh_config("vendor", "echo 'ERROR: only a message'; exit 0");
+    config.exit = Some(exit_config("fail_on_stderr_regex = '^ERROR'"));
+
+    let runner = ShimRunner::from_config(config).unwrap().with_history(None);
+    assert_eq!(runner.execute(&[]).unwrap(), 0);
+}
+
+#[test]
+fn test_watch_and_log_share_the_tee() {
+    let dir = tempfile::tempdir().unwrap();
+    let shim_file = dir.path().join("vendor.shim.toml");
+    let mut config = sh_config("vendor", "echo done; echo FATAL >&2");
+    config.exit = Some(exit_config("fail_on_stderr_regex = 'FATAL'"));
+    config.logging = Some(LoggingConfig {
+        stdout: true,
+        tee_terminal: true,
+        ..Default::default()
+    });
+    config.to_file(&shim_file).unwrap();
+
+    let runner = ShimRunner::from_file(&shim_file)
+        .unwrap()
+        .with_history(None);
+    assert_eq!(runner.execute(&[]).unwrap(), 1);
+
+    // Only the logged stream ends up in the log
+    let log = std::fs::read_to_string(dir.path().join("vendor/logs/vendor.log")).unwrap();
+    assert_eq!(log, "[stdout] done\n");
+}
@@ -0,0 +1,123 @@
+// Tests for ShimRunner::explain dry-run reports
+
+#![cfg(unix)]
+
+use shimexe_core::{ExplainFormat, ShimRunner};
+
+#[test]
+fn test_explain_reports_resolved_invocation() {
+    let dir = tempfile::tempdir().unwrap();
+    let marker = dir.path().join("ran");
+    let shim_file = dir.path().join("tool.shim.toml");
+    std::fs::write(
+        &shim_file,
+        format!(
+            r#"
+[shim]
+name = "tool"
+path = "sh"
+args = ["-c", "touch '{}'"]
+cwd = "/tmp"
+
+[args]
+template = ["{{{{args('--default')}}}}"]
+
+[env]
+EXPLAIN_RUNNER_VAR = "set-by-shim"
+"#,
+            marker.display()
+        ),
+    )
+    .unwrap();
+
+    let runner = ShimRunner::from_file(&shim_file).unwrap();
+    let explanation = runner.explain(&[]).unwrap();
+
+    assert!(!marker.exists(), "explain must not run the target");
+    assert_eq!(
+        explanation.config_file.as_deref(),
+        Some(shim_file.as_path())
+    );
+    assert!(explanation.executable.unwrap().ends_with("sh"));
+    assert!(explanation.resolution.contains("PATH"));
+    assert_eq!(explanation.argv[2], "--default");
+    assert_eq!(explanation.cwd, std::path::Path::new("/tmp"));
+    assert!(explanation
+        .env
+        .iter()
+        .any(|c| c.name == "EXPLAIN_RUNNER_VAR" && c.value.as_deref() == Some("set-by-shim")));
+    assert!(explanation.pending.is_empty());
+
+    let user_args = vec!["--user".to_string()];
+    let explanation = runner.explain(&user_args).unwrap();
+    assert_eq!(explanation.argv[2], "--user");
+
+    let json: serde_json::Value =
+        serde_json::from_str(&explanation.render(ExplainFormat::Json)).unwrap();
+    assert_eq!(json["shim"], "tool");
+}
+
+#[test]
+fn test_explain_reports_pending_download() {
+    let dir = tempfile::tempdir().unwrap();
+    let shim_file = dir.path().join("remote.shim.toml");
+    std::fs::write(
+        &shim_file,
+        r#"
+[shim]
+name = "remote"
+path = "https://example.invalid/releases/remote-tool"
+"#,
+    )
+    .unwrap();
+
+    let runner = ShimRunner::from_file(&shim_file).unwrap();
+    let explanation = runner.explain(&[]).unwrap();
+    assert!(explanation.executable.is_none());
+    assert!(explanation.pending[0].starts_with("download https://example.invalid"));
+}
+
+#[test]
+fn test_explain_does_not_run_env_from() {
+    let dir = tempfile::tempdir().unwrap();
+    let marker = dir.path().join("ran");
+    let shim_file = dir.path().join("activated.shim.toml");
+    std::fs::write(
+        &shim_file,
+        format!(
+            r#"
+[shim]
+name = "activated"
+path = "true"
+
+[env_from]
+command = "touch '{}'; echo EXPLAIN_ENV_FROM=computed"
+capture = "output"
+"#,
+            marker.display()
+        ),
+    )
+    .unwrap();
+
+    let runner = ShimRunner::from_file(&shim_file)
+        .unwrap()
+        .with_history(None);
+    let explanation = runner.explain(&[]).unwrap();
+    assert!(!marker.exists(), "explain must not run [env_from]");
+    assert!(explanation
+        .pending
+        .iter()
+        .any(|p| p.starts_with("run `") && p.ends_with("for [env_from]")));
+    assert!(!explanation.env.iter().any(|c| c.name == "EXPLAIN_ENV_FROM"));
+
+    // A real run caches the result, which explain then reports
+    assert_eq!(runner.execute(&[]).unwrap(), 0);
+    std::fs::remove_file(&marker).unwrap();
+    let explanation = runner.explain(&[]).unwrap();
+    assert!(!marker.exists());
+    assert!(explanation.pending.is_empty());
+    assert!(explanation
+        .env
+        .iter()
+        .any(|c| c.name == "EXPLAIN_ENV_FROM" && c.value.as_deref() == Some("computed")));
+}
@@ -0,0 +1,60 @@
+// Tests for execution history recording through ShimRunner
+
+#![cfg(unix)]
+
+use shimexe_core::history::{summarize, HISTORY_FILE_ENV_VAR, NO_HISTORY_ENV_VAR};
+use shimexe_core::{History, HistoryConfig, ShimConfig, ShimRunner};
+
+#[test]
+fn test_invocations_are_recorded() {
+    let dir = tempfile::tempdir().unwrap();
+    let history = History::new(dir.path().join("history.jsonl"));
+
+    let mut config = ShimConfig::new("recorded", "sh");
+    config.shim.args = vec!["-c".to_string(), "exit 0".to_string()];
+    let runner = ShimRunner::from_config(config.clone())
+        .unwrap()
+        .with_history(Some(history.clone()));
+    assert_eq!(runner.execute(&["extra".to_string()]).unwrap(), 0);
+
+    config.shim.args[1] = "exit 3".to_string();
+    let runner = ShimRunner::from_config(config)
+        .unwrap()
+        .with_history(Some(history.clone()));
+    assert_eq!(runner.execute(&[]).unwrap(), 3);
+
+    let records = history.read().unwrap();
+    assert_eq!(records.len(), 2);
+    assert_eq!(records[0].shim, "recorded");
+    assert_eq!(records[0].argc, 3);
+    assert!(records[0].executable.ends_with("sh"));
+    assert_eq!(records[1].exit_code, Some(3));
+
+    let usage = summarize(&records);
+    assert_eq!(usage[0].runs, 2);
+    assert_eq!(usage[0].failures, 1);
+}
+
+// The only test in this file that changes the process environment
+#[test]
+fn test_history_is_opt_in() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("history.jsonl");
+    std::env::set_var(HISTORY_FILE_ENV_VAR, &path);
+
+    let mut config = ShimConfig::new("quiet", "true");
+    let runner = ShimRunner::from_config(config.clone()).unwrap();
+    assert_eq!(runner.execute(&[]).unwrap(), 0);
+    assert!(!path.exists());
+
+    config.history = Some(HistoryConfig { enabled: true });
+    let runner = ShimRunner::from_config(config.clone()).unwrap();
+    assert_eq!(runner.execute(&[]).unwrap(), 0);
+    assert_eq!(History::new(&path).read().unwrap().len(), 1);
+
+    std::env::set_var(NO_HISTORY_ENV_VAR, "1");
+    let runner = ShimRunner::from_config(config).unwrap();
+    std::env::remove_var(NO_HISTORY_ENV_VAR);
+    assert_eq!(runner.execute(&[]).unwrap(), 0);
+    assert_eq!(History::new(&path).read().unwrap().len(), 1);
+}
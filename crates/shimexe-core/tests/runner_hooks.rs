@@ -0,0 +1,107 @@
+// Tests for [hooks] pre_exec/post_exec execution through ShimRunner
+
+#![cfg(unix)]
+
+mod common;
+
+use common::sh_config;
+use shimexe_core::{Hook, HookFailure, HooksConfig, ShimConfig, ShimError, ShimRunner};
+
+#[test]
+fn test_hooks_receive_argv_and_exit_code() {
+    let dir = tempfile::tempdir().unwrap();
+    let pre_out = dir.path().join("pre.txt");
+    let post_out = dir.path().join("post.txt");
+
+    let hooks = HooksConfig {
+        pre_exec: vec![Hook::Command(format!(
+            "echo \"$SHIMEXE_HOOK $SHIMEXE_SHIM_NAME $SHIMEXE_ARGC $SHIMEXE_ARG_0\" > '{}'",
+            pre_out.display()
+        ))],
+        post_exec: vec![Hook::Command(format!(
+            "echo \"$SHIMEXE_HOOK $SHIMEXE_EXIT_CODE\" > '{}'",
+            post_out.display()
+        ))],
+    };
+
+    let mut config = sh_config("hooked", "exit 3");
+    config.hooks = Some(hooks);
+    let runner = ShimRunner::from_config(config).unwrap().with_history(None);
+    let exit_code = runner.execute(&[]).unwrap();
+    assert_eq!(exit_code, 3);
+
+    let pre = std::fs::read_to_string(&pre_out).unwrap();
+    assert_eq!(pre.trim(), "pre_exec hooked 2 -c");
+    let post = std::fs::read_to_string(&post_out).unwrap();
+    assert_eq!(post.trim(), "post_exec 3");
+}
+
+#[test]
+fn test_failing_pre_exec_hook_aborts() {
+    let dir = tempfile::tempdir().unwrap();
+    let marker = dir.path().join("ran");
+
+    let hooks = HooksConfig {
+        pre_exec: vec![Hook::Command("exit 1".to_string())],
+        post_exec: vec![],
+    };
+    let mut config = sh_config("hooked", &format!("touch '{}'", marker.display()));
+    config.hooks = Some(hooks);
+    let runner = ShimRunner::from_config(config).unwrap().with_history(None);
+
+    let result = runner.execute(&[]);
+    assert!(matches!(result, Err(ShimError::HookFailed(_))));
+    assert!(
+        !marker.exists(),
+        "target must not run after an aborting hook"
+    );
+}
+
+#[test]
+fn test_warn_policy_and_timeout() {
+    let hooks = HooksConfig {
+        pre_exec: vec![
+            Hook::Detailed {
+                command: "exit 1".to_string(),
+                on_failure: HookFailure::Warn,
+                timeout_secs: None,
+                background: false,
+            },
+            Hook::Detailed {
+                command: "sleep 30".to_string(),
+                on_failure: HookFailure::Warn,
+                timeout_secs: Some(1),
+                background: false,
+            },
+        ],
+        post_exec: vec![],
+    };
+
+    let start = std::time::Instant::now();
+    let mut config = sh_config("hooked", "exit 0");
+    config.hooks = Some(hooks);
+    let runner = ShimRunner::from_config(config).unwrap().with_history(None);
+    assert_eq!(runner.execute(&[]).unwrap(), 0);
+    assert!(start.elapsed() < std::time::Duration::from_secs(20));
+}
+
+#[test]
+fn test_hooks_config_forms() {
+    let config: ShimConfig = toml::from_str(
+        r#"
+[shim]
+name = "tool"
+path = "tool"
+
+[hooks]
+pre_exec = ["license-check", { command = "warm-cache", background = true }]
+post_exec = [{ command = "report", on_failure = "warn", timeout_secs = 5 }]
+"#,
+    )
+    .unwrap();
+
+    let hooks = config.hooks.unwrap();
+    assert_eq!(hooks.pre_exec[0].command(), "license-check");
+    assert!(hooks.pre_exec[1].is_background());
+    assert_eq!(hooks.post_exec[0].on_failure(), HookFailure::Warn);
+}
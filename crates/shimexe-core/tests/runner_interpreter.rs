@@ -0,0 +1,93 @@
+// Tests for shim.interpreter running script targets through ShimRunner
+
+#![cfg(unix)]
+
+use shimexe_core::{Interpreter, ShimConfig, ShimError, ShimRunner};
+use std::path::Path;
+
+/// A script that is not executable on its own
+fn script_config(path: &Path, content: &str, interpreter: Interpreter) -> ShimConfig {
+    std::fs::write(path, content).unwrap();
+    let mut config = ShimConfig::new("script", path.display().to_string());
+    config.shim.interpreter = Some(interpreter);
+    config
+}
+
+#[test]
+fn test_interpreter_inferred_from_extension() {
+    let dir = tempfile::tempdir().unwrap();
+    let config = script_config(
+        &dir.path().join("tool.sh"),
+        "exit $1\n",
+        Interpreter::Command("auto".to_string()),
+    );
+
+    let runner = ShimRunner::from_config(config).unwrap().with_history(None);
+    assert_eq!(runner.execute(&["7".to_string()]).unwrap(), 7);
+}
+
+#[test]
+fn test_interpreter_inferred_from_shebang() {
+    let dir = tempfile::tempdir().unwrap();
+    let config = script_config(
+        &dir.path().join("tool"),
+        "#!/usr/bin/env sh\nexit 6\n",
+        Interpreter::Command("auto".to_string()),
+    );
+
+    let runner = ShimRunner::from_config(config).unwrap().with_history(None);
+    assert_eq!(runner.execute(&[]).unwrap(), 6);
+
+    let explanation = runner.explain(&[]).unwrap();
+    let interpreter = explanation.interpreter.unwrap();
+    assert!(interpreter[0].ends_with("/sh"));
+}
+
+#[test]
+fn test_explicit_interpreter_with_args() {
+    let dir = tempfile::tempdir().unwrap();
+    let config = script_config(
+        &dir.path().join("tool.txt"),
+        "false\nexit 0\n",
+        Interpreter::Args(vec!["sh".to_string(), "-e".to_string()]),
+    );
+
+    let runner = ShimRunner::from_config(config).unwrap().with_history(None);
+    assert_eq!(runner.execute(&[]).unwrap(), 1);
+}
+
+#[test]
+fn test_missing_interpreter() {
+    let dir = tempfile::tempdir().unwrap();
+    let config = script_config(
+        &dir.path().join("tool.txt"),
+        "exit 0\n",
+        Interpreter::Command("shimexe-missing-interpreter".to_string()),
+    );
+
+    let runner = ShimRunner::from_config(config).unwrap().with_history(None);
+    assert!(matches!(
+        runner.execute(&[]),
+        Err(ShimError::ExecutableNotFound(_))
+    ));
+}
+
+#[test]
+fn test_validate_accepts_non_executable_script() {
+    let dir = tempfile::tempdir().unwrap();
+    let config = script_config(
+        &dir.path().join("tool.txt"),
+        "exit 0\n",
+        Interpreter::Command("sh".to_string()),
+    );
+    let runner = ShimRunner::from_config(config).unwrap().with_history(None);
+    runner.validate().unwrap();
+
+    let config = script_config(
+        &dir.path().join("other.txt"),
+        "exit 0\n",
+        Interpreter::Command("shimexe-missing-interpreter".to_string()),
+    );
+    let runner = ShimRunner::from_config(config).unwrap().with_history(None);
+    assert!(runner.validate().is_err());
+}
@@ -0,0 +1,71 @@
+// Tests for [limits] applying resource limits to the target through ShimRunner
+
+#![cfg(unix)]
+
+mod common;
+
+use common::sh_config;
+use shimexe_core::{LimitsConfig, ShimError, ShimRunner};
+
+#[test]
+fn test_limits_apply_to_target() {
+    let dir = tempfile::tempdir().unwrap();
+    let output = dir.path().join("limits");
+    let limits = LimitsConfig {
+        open_files: Some(64),
+        cpu_seconds: Some(30),
+        umask: Some(0o027),
+        ..Default::default()
+    };
+    let script = format!(
+        "ulimit -n > '{0}'; ulimit -t >> '{0}'; umask >> '{0}'",
+        output.display()
+    );
+
+    let mut config = sh_config("limited", &script);
+    config.limits = Some(limits);
+    let runner = ShimRunner::from_config(config).unwrap().with_history(None);
+    assert_eq!(runner.execute(&[]).unwrap(), 0);
+
+    let lines: Vec<String> = std::fs::read_to_string(&output)
+        .unwrap()
+        .lines()
+        .map(str::to_string)
+        .collect();
+    assert_eq!(lines[0], "64");
+    assert_eq!(lines[1], "30");
+    assert!(lines[2].ends_with("027"));
+}
+
+#[test]
+fn test_invalid_limits_fail_before_running() {
+    let dir = tempfile::tempdir().unwrap();
+    let marker = dir.path().join("ran");
+    let limits = LimitsConfig {
+        nice: Some(42),
+        ..Default::default()
+    };
+
+    let mut config = sh_config("limited", &format!("touch '{}'", marker.display()));
+    config.limits = Some(limits);
+    let runner = ShimRunner::from_config(config).unwrap().with_history(None);
+    assert!(matches!(runner.execute(&[]), Err(ShimError::Config(_))));
+    assert!(!marker.exists());
+}
+
+#[test]
+fn test_limits_in_explanation() {
+    let limits = LimitsConfig {
+        memory_bytes: Some(1 << 30),
+        nice: Some(5),
+        ..Default::default()
+    };
+    let mut config = sh_config("limited", "true");
+    config.limits = Some(limits);
+    let runner = ShimRunner::from_config(config).unwrap();
+
+    let explanation = runner.explain(&[]).unwrap();
+    assert!(explanation
+        .to_string()
+        .contains("Limits: memory 1073741824 bytes, nice 5"));
+}
@@ -0,0 +1,69 @@
+// Tests for [logging] teeing target output into per-shim log files
+
+#![cfg(unix)]
+
+use shimexe_core::{LogFormat, LoggingConfig, ShimConfig, ShimRunner};
+
+#[test]
+fn test_output_is_teed_into_log() {
+    let dir = tempfile::tempdir().unwrap();
+    let shim_file = dir.path().join("noisy.shim.toml");
+    std::fs::write(
+        &shim_file,
+        r#"
+[shim]
+name = "noisy"
+path = "sh"
+args = ["-c", "echo out-line; echo err-line >&2; printf tail; exit 4"]
+
+[logging]
+stdout = true
+stderr = true
+tee_terminal = true
+"#,
+    )
+    .unwrap();
+
+    let runner = ShimRunner::from_file(&shim_file)
+        .unwrap()
+        .with_history(None);
+    assert_eq!(runner.execute(&[]).unwrap(), 4);
+
+    let log = std::fs::read_to_string(dir.path().join("noisy/logs/noisy.log")).unwrap();
+    assert!(log.contains("[stdout] out-line\n"));
+    assert!(log.contains("[stderr] err-line\n"));
+    assert!(log.contains("[stdout] tail\n"));
+}
+
+#[test]
+fn test_logging_config_forms() {
+    let config: ShimConfig = toml::from_str(
+        r#"
+[shim]
+name = "backup"
+path = "backup"
+
+[logging]
+stderr = true
+file = "cron.log"
+max_size_bytes = 1048576
+max_files = 3
+timestamps = true
+level = "debug"
+format = "json"
+"#,
+    )
+    .unwrap();
+
+    let logging = config.logging.unwrap();
+    assert!(!logging.stdout);
+    assert!(logging.stderr);
+    assert_eq!(logging.max_files, 3);
+    assert_eq!(logging.level.as_deref(), Some("debug"));
+    assert_eq!(logging.format, Some(LogFormat::Json));
+    assert_eq!(
+        logging.log_path(std::path::Path::new("/data/backup"), "backup"),
+        std::path::Path::new("/data/backup/logs/cron.log")
+    );
+    assert_eq!(LoggingConfig::default().max_files, 5);
+}
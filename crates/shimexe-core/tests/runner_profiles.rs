@@ -0,0 +1,91 @@
+// Tests for named profiles selected by ShimRunner
+
+use shimexe_core::profiles::PROFILE_ENV_VAR;
+use shimexe_core::{ShimConfig, ShimRunner, TrustStore};
+
+fn args(args: &[&str]) -> Vec<String> {
+    args.iter().map(|s| s.to_string()).collect()
+}
+
+// Changes the environment and working directory, so everything runs in one test
+#[test]
+fn test_profile_selected_by_flag_env_and_project() {
+    let dir = tempfile::tempdir().unwrap();
+    std::env::set_var(
+        shimexe_core::trust::TRUST_FILE_ENV_VAR,
+        dir.path().join("trust.json"),
+    );
+    std::env::remove_var(PROFILE_ENV_VAR);
+    let shim_file = dir.path().join("tool.shim.toml");
+    std::fs::write(
+        &shim_file,
+        r#"
+[shim]
+name = "tool"
+path = "echo"
+args = ["run"]
+
+[profiles.debug]
+extra_args = ["--verbose"]
+env = { RUST_LOG = "trace" }
+
+[profiles.release]
+args = ["build", "--release"]
+"#,
+    )
+    .unwrap();
+    std::env::set_current_dir(dir.path()).unwrap();
+
+    // No profile selected
+    let runner = ShimRunner::from_file(&shim_file).unwrap();
+    let explanation = runner.explain(&args(&["x"])).unwrap();
+    assert_eq!(explanation.argv, args(&["run", "x"]));
+    assert!(explanation.profile.is_none());
+
+    // The flag is stripped and its profile applied
+    let explanation = runner
+        .explain(&args(&["x", "--shimexe-profile", "debug"]))
+        .unwrap();
+    assert_eq!(explanation.argv, args(&["run", "--verbose", "x"]));
+    assert_eq!(
+        explanation.profile.as_deref(),
+        Some("debug (from --shimexe-profile)")
+    );
+    assert!(explanation
+        .env
+        .iter()
+        .any(|change| change.name == "RUST_LOG" && change.value.as_deref() == Some("trace")));
+    let err = runner
+        .explain(&args(&["--shimexe-profile=fast"]))
+        .unwrap_err();
+    assert!(
+        err.to_string().contains("profiles: debug, release"),
+        "{}",
+        err
+    );
+
+    // SHIMEXE_PROFILE, overridden by the flag; unknown names are ignored
+    std::env::set_var(PROFILE_ENV_VAR, "release");
+    let explanation = runner.explain(&[]).unwrap();
+    assert_eq!(explanation.argv, args(&["build", "--release"]));
+    assert_eq!(
+        explanation.profile.as_deref(),
+        Some("release (from SHIMEXE_PROFILE)")
+    );
+    let explanation = runner.explain(&args(&["--shimexe-profile=debug"])).unwrap();
+    assert_eq!(explanation.argv, args(&["run", "--verbose"]));
+    std::env::set_var(PROFILE_ENV_VAR, "other-tools-profile");
+    assert!(runner.explain(&[]).unwrap().profile.is_none());
+    std::env::remove_var(PROFILE_ENV_VAR);
+
+    // A trusted project file
+    let project_file = dir.path().join(".shimexe.toml");
+    std::fs::write(&project_file, "[tool]\nprofile = \"release\"\n").unwrap();
+    let mut store = TrustStore::load().unwrap();
+    store.trust(&project_file).unwrap();
+    store.save().unwrap();
+    let runner = ShimRunner::from_config(ShimConfig::from_file(&shim_file).unwrap()).unwrap();
+    let explanation = runner.explain(&[]).unwrap();
+    assert_eq!(explanation.argv, args(&["build", "--release"]));
+    assert!(explanation.profile.unwrap().starts_with("release (from "));
+}
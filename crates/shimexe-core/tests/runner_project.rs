@@ -0,0 +1,74 @@
+// Tests for project-local overrides (.shimexe.toml) applied by ShimRunner
+
+use shimexe_core::{ShimConfig, ShimRunner, TrustStore};
+
+// Changes the process working directory, so everything runs in one test
+#[test]
+fn test_project_overrides_apply_only_when_trusted() {
+    let dir = tempfile::tempdir().unwrap();
+    std::env::set_var(
+        shimexe_core::trust::TRUST_FILE_ENV_VAR,
+        dir.path().join("trust.json"),
+    );
+
+    let project = dir.path().join("project");
+    let nested = project.join("src").join("module");
+    std::fs::create_dir_all(&nested).unwrap();
+    let project_file = project.join(".shimexe.toml");
+    std::fs::write(
+        &project_file,
+        r#"
+[tool]
+path = "bin/tool"
+version = "2.0.0"
+args = ["--project"]
+
+[tool.env]
+TOOL_MODE = "project"
+"#,
+    )
+    .unwrap();
+    std::env::set_current_dir(&nested).unwrap();
+
+    let mut config = ShimConfig::new("tool", "echo");
+    config.shim.args = vec!["--global".to_string()];
+
+    let runner = ShimRunner::from_config(config.clone()).unwrap();
+    assert_eq!(runner.config().shim.path, "echo");
+    assert!(runner.explain(&[]).unwrap().project_file.is_none());
+
+    let mut store = TrustStore::load().unwrap();
+    store.trust(&project_file).unwrap();
+    store.save().unwrap();
+
+    let runner = ShimRunner::from_config(config.clone()).unwrap();
+    let trusted = project_file.canonicalize().unwrap();
+    assert_eq!(
+        runner.config().shim.path,
+        project.join("bin/tool").to_string_lossy()
+    );
+    assert_eq!(runner.config().metadata.version.as_deref(), Some("2.0.0"));
+    assert_eq!(runner.config().env["TOOL_MODE"], "project");
+    let explanation = runner.explain(&[]).unwrap();
+    assert_eq!(explanation.argv, vec!["--project"]);
+    assert_eq!(
+        explanation.project_file.unwrap().canonicalize().unwrap(),
+        trusted
+    );
+
+    // Editing the file revokes the trust
+    std::fs::write(&project_file, "[tool]\npath = \"bin/other\"\n").unwrap();
+    let runner = ShimRunner::from_config(config.clone()).unwrap();
+    assert_eq!(runner.config().shim.path, "echo");
+
+    // Malformed project files are not parsed until trusted, and skipped when they are
+    std::fs::write(&project_file, "[tool\npath = ").unwrap();
+    let runner = ShimRunner::from_config(config.clone()).unwrap();
+    assert_eq!(runner.config().shim.path, "echo");
+
+    let mut store = TrustStore::load().unwrap();
+    store.trust(&project_file).unwrap();
+    store.save().unwrap();
+    let runner = ShimRunner::from_config(config).unwrap();
+    assert_eq!(runner.config().shim.path, "echo");
+}
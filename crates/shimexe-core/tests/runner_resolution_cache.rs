@@ -0,0 +1,62 @@
+// Tests for the persistent resolution cache used by ShimRunner::from_file
+
+#![cfg(unix)]
+
+use shimexe_core::{ResolutionCache, ShimRunner};
+use std::os::unix::fs::PermissionsExt;
+
+fn write_script(path: &std::path::Path, code: i32) {
+    std::fs::write(path, format!("#!/bin/sh\nexit {}\n", code)).unwrap();
+    std::fs::set_permissions(path, std::fs::Permissions::from_mode(0o755)).unwrap();
+}
+
+#[test]
+fn test_cache_is_written_and_invalidated_on_edit() {
+    let dir = tempfile::tempdir().unwrap();
+    let target = dir.path().join("target.sh");
+    write_script(&target, 7);
+
+    let shim_file = dir.path().join("cached.shim.toml");
+    std::fs::write(
+        &shim_file,
+        format!(
+            "[shim]\nname = \"cached\"\npath = \"{}\"\n",
+            target.display()
+        ),
+    )
+    .unwrap();
+
+    let runner = ShimRunner::from_file(&shim_file)
+        .unwrap()
+        .with_history(None);
+    assert_eq!(runner.execute(&[]).unwrap(), 7);
+
+    let cache_file = ResolutionCache::cache_path(&shim_file);
+    let content = std::fs::read_to_string(&cache_file).unwrap();
+    assert!(content.contains("target.sh"));
+
+    // A new runner (as in a new process) reads the cached config and executable
+    let runner = ShimRunner::from_file(&shim_file)
+        .unwrap()
+        .with_history(None);
+    assert_eq!(runner.config().shim.name, "cached");
+    assert_eq!(runner.execute(&[]).unwrap(), 7);
+
+    // Editing the shim file invalidates the cached entry
+    let other = dir.path().join("other.sh");
+    write_script(&other, 3);
+    std::fs::write(
+        &shim_file,
+        format!(
+            "[shim]\nname = \"cached\"\npath = \"{}\"\n",
+            other.display()
+        ),
+    )
+    .unwrap();
+    let runner = ShimRunner::from_file(&shim_file)
+        .unwrap()
+        .with_history(None);
+    assert_eq!(runner.execute(&[]).unwrap(), 3);
+    let content = std::fs::read_to_string(&cache_file).unwrap();
+    assert!(content.contains("other.sh"));
+}
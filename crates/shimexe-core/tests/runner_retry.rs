@@ -0,0 +1,109 @@
+// Tests for [retry] re-running the target through ShimRunner
+
+#![cfg(unix)]
+
+mod common;
+
+use common::sh_config;
+use shimexe_core::{Backoff, RetryConfig, ShimConfig, ShimRunner};
+
+/// A script that fails with `code` until it has run `fail_runs` times
+fn flaky_script(counter: &std::path::Path, fail_runs: u32, code: i32) -> String {
+    format!(
+        "echo run >> '{0}'; [ $(wc -l < '{0}') -gt {1} ] || exit {2}",
+        counter.display(),
+        fail_runs,
+        code
+    )
+}
+
+fn retry_policy(attempts: u32, on_exit_codes: Vec<i32>) -> RetryConfig {
+    let mut retry = RetryConfig::new(attempts, on_exit_codes);
+    retry.delay_ms = 10;
+    // Test runs have no terminal on stdin
+    retry.allow_piped_stdin = true;
+    retry
+}
+
+fn runs(counter: &std::path::Path) -> usize {
+    std::fs::read_to_string(counter).unwrap().lines().count()
+}
+
+#[test]
+fn test_retry_until_success() {
+    let dir = tempfile::tempdir().unwrap();
+    let counter = dir.path().join("runs");
+
+    let mut config = sh_config("flaky", &flaky_script(&counter, 2, 75));
+    config.retry = Some(retry_policy(5, vec![75]));
+
+    let runner = ShimRunner::from_config(config).unwrap().with_history(None);
+    assert_eq!(runner.execute(&[]).unwrap(), 0);
+    assert_eq!(runs(&counter), 3);
+}
+
+#[test]
+fn test_retry_returns_last_exit_code() {
+    let dir = tempfile::tempdir().unwrap();
+    let counter = dir.path().join("runs");
+
+    let mut config = sh_config("flaky", &flaky_script(&counter, 10, 75));
+    config.retry = Some(retry_policy(2, vec![75]));
+
+    let runner = ShimRunner::from_config(config).unwrap().with_history(None);
+    assert_eq!(runner.execute(&[]).unwrap(), 75);
+    assert_eq!(runs(&counter), 2);
+}
+
+#[test]
+fn test_unlisted_exit_code_is_not_retried() {
+    let dir = tempfile::tempdir().unwrap();
+    let counter = dir.path().join("runs");
+
+    let mut config = sh_config("flaky", &flaky_script(&counter, 10, 2));
+    config.retry = Some(retry_policy(3, vec![75]));
+
+    let runner = ShimRunner::from_config(config).unwrap().with_history(None);
+    assert_eq!(runner.execute(&[]).unwrap(), 2);
+    assert_eq!(runs(&counter), 1);
+}
+
+#[test]
+fn test_piped_stdin_disables_retry() {
+    let dir = tempfile::tempdir().unwrap();
+    let counter = dir.path().join("runs");
+
+    let mut retry = retry_policy(3, vec![75]);
+    retry.allow_piped_stdin = false;
+    let mut config = sh_config("flaky", &flaky_script(&counter, 10, 75));
+    config.retry = Some(retry);
+
+    let runner = ShimRunner::from_config(config).unwrap().with_history(None);
+    assert_eq!(runner.execute(&[]).unwrap(), 75);
+    if !std::io::IsTerminal::is_terminal(&std::io::stdin()) {
+        assert_eq!(runs(&counter), 1);
+    }
+}
+
+#[test]
+fn test_retry_config_forms() {
+    let config: ShimConfig = toml::from_str(
+        r#"
+[shim]
+name = "fetch"
+path = "curl"
+
+[retry]
+attempts = 4
+backoff = "fixed"
+on_exit_codes = [6, 7, 28]
+"#,
+    )
+    .unwrap();
+
+    let retry = config.retry.unwrap();
+    assert_eq!(retry.attempts, 4);
+    assert_eq!(retry.backoff, Backoff::Fixed);
+    assert_eq!(retry.on_exit_codes, vec![6, 7, 28]);
+    assert!(!retry.allow_piped_stdin);
+}
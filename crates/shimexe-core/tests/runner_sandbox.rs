@@ -0,0 +1,58 @@
+// Tests for [sandbox] restricting the target with Landlock through ShimRunner
+
+#![cfg(target_os = "linux")]
+
+use shimexe_core::{sandbox, SandboxConfig, ShimConfig, ShimRunner};
+
+#[test]
+fn test_sandbox_limits_writes_to_allowlist() {
+    if !sandbox::is_supported() {
+        eprintln!("Landlock is not available, skipping");
+        return;
+    }
+
+    let dir = tempfile::tempdir().unwrap();
+    let allowed = dir.path().join("allowed");
+    let denied = dir.path().join("denied");
+    std::fs::create_dir_all(&allowed).unwrap();
+    std::fs::create_dir_all(&denied).unwrap();
+
+    let script = format!(
+        "echo a > '{0}/out' || exit 10; echo b > '{1}/out' 2>/dev/null && exit 11; exit 0",
+        allowed.display(),
+        denied.display()
+    );
+    let mut config = ShimConfig::new("sandboxed", "sh");
+    config.shim.args = vec!["-c".to_string(), script];
+    config.sandbox = Some(SandboxConfig {
+        read_write: vec![allowed.display().to_string()],
+        ..Default::default()
+    });
+
+    let runner = ShimRunner::from_config(config).unwrap().with_history(None);
+    assert_eq!(runner.execute(&[]).unwrap(), 0);
+    assert!(allowed.join("out").exists());
+    assert!(!denied.join("out").exists());
+
+    // The shim itself is not restricted
+    std::fs::write(denied.join("shim"), "ok").unwrap();
+}
+
+#[test]
+fn test_sandbox_denies_reads_outside_allowlist() {
+    if !sandbox::is_supported() {
+        eprintln!("Landlock is not available, skipping");
+        return;
+    }
+
+    let dir = tempfile::tempdir().unwrap();
+    let secret = dir.path().join("secret");
+    std::fs::write(&secret, "hidden").unwrap();
+
+    let mut config = ShimConfig::new("sandboxed", "cat");
+    config.shim.args = vec![secret.display().to_string()];
+    config.sandbox = Some(SandboxConfig::default());
+
+    let runner = ShimRunner::from_config(config).unwrap().with_history(None);
+    assert_ne!(runner.execute(&[]).unwrap(), 0);
+}
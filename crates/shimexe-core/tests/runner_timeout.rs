@@ -0,0 +1,76 @@
+// Tests for [timeout] enforcement through ShimRunner
+
+#![cfg(unix)]
+
+mod common;
+
+use common::sh_config;
+use shimexe_core::{KillSignal, ShimConfig, ShimRunner, TimeoutConfig};
+use std::time::{Duration, Instant};
+
+#[test]
+fn test_timeout_kills_process_group() {
+    let dir = tempfile::tempdir().unwrap();
+    let marker = dir.path().join("survived");
+
+    let mut timeout = TimeoutConfig::new(1);
+    timeout.grace_secs = 1;
+    timeout.process_group = Some(true);
+
+    // The grandchild must die with the group instead of outliving the shim
+    let script = format!("(sleep 3; touch '{}') & sleep 30", marker.display());
+    let start = Instant::now();
+    let mut config = sh_config("slow", &script);
+    config.timeout = Some(timeout);
+    let runner = ShimRunner::from_config(config).unwrap().with_history(None);
+    assert_eq!(runner.execute(&[]).unwrap(), 124);
+    assert!(start.elapsed() < Duration::from_secs(10));
+
+    std::thread::sleep(Duration::from_secs(3));
+    assert!(!marker.exists());
+}
+
+#[test]
+fn test_timeout_escalates_to_sigkill() {
+    let mut timeout = TimeoutConfig::new(1);
+    timeout.grace_secs = 1;
+    timeout.process_group = Some(false);
+
+    let start = Instant::now();
+    let mut config = sh_config("slow", "trap '' TERM; sleep 30");
+    config.timeout = Some(timeout);
+    let runner = ShimRunner::from_config(config).unwrap().with_history(None);
+    assert_eq!(runner.execute(&[]).unwrap(), 124);
+    assert!(start.elapsed() < Duration::from_secs(10));
+}
+
+#[test]
+fn test_fast_target_keeps_exit_code() {
+    let mut config = sh_config("slow", "exit 5");
+    config.timeout = Some(TimeoutConfig::new(30));
+    let runner = ShimRunner::from_config(config).unwrap().with_history(None);
+    assert_eq!(runner.execute(&[]).unwrap(), 5);
+}
+
+#[test]
+fn test_timeout_config_forms() {
+    let config: ShimConfig = toml::from_str(
+        r#"
+[shim]
+name = "cc"
+path = "cc"
+
+[timeout]
+secs = 600
+signal = "int"
+grace_secs = 10
+"#,
+    )
+    .unwrap();
+
+    let timeout = config.timeout.unwrap();
+    assert_eq!(timeout.secs, 600);
+    assert_eq!(timeout.signal, KillSignal::Int);
+    assert_eq!(timeout.grace_secs, 10);
+    assert_eq!(timeout.process_group, None);
+}
@@ -28,11 +28,13 @@ fn test_runner_validate_ok_and_cache() {
             download_url: None,
             source_type: SourceType::File,
             extracted_executables: vec![],
+            ..Default::default()
         },
         args: Default::default(),
         env: Default::default(),
         metadata: Default::default(),
         auto_update: None,
+        ..Default::default()
     };
 
     let runner = ShimRunner::from_config(cfg).unwrap();
@@ -0,0 +1,49 @@
+// Tests for selecting the version of a multi-version shim from version files
+
+use shimexe_core::{ShimConfig, ShimRunner, VersionsConfig};
+use std::collections::BTreeMap;
+
+// Changes the process working directory, so everything runs in one test
+#[test]
+fn test_version_files_select_installed_version() {
+    let dir = tempfile::tempdir().unwrap();
+    let project = dir.path().join("project");
+    std::fs::create_dir_all(&project).unwrap();
+    std::fs::write(project.join(".tool-versions"), "python 3.11.4\n").unwrap();
+    std::env::set_current_dir(&project).unwrap();
+
+    let mut config = ShimConfig::new("python", "/opt/python/3.12.0/bin/python");
+    config.versions = Some(VersionsConfig {
+        default: Some("3.12.0".to_string()),
+        installed: BTreeMap::from([
+            (
+                "3.11.4".to_string(),
+                "/opt/python/3.11.4/bin/python".to_string(),
+            ),
+            (
+                "3.12.0".to_string(),
+                "/opt/python/3.12.0/bin/python".to_string(),
+            ),
+        ]),
+        files: vec![".python-version".to_string(), ".tool-versions".to_string()],
+        tool_name: None,
+    });
+
+    let runner = ShimRunner::from_config(config.clone()).unwrap();
+    assert_eq!(runner.config().shim.path, "/opt/python/3.11.4/bin/python");
+    let explanation = runner.explain(&[]).unwrap();
+    assert!(
+        explanation
+            .version
+            .as_deref()
+            .unwrap()
+            .starts_with("3.11.4 (from "),
+        "{:?}",
+        explanation.version
+    );
+
+    // Version files are opt-in per shim
+    config.versions.as_mut().unwrap().files.clear();
+    let runner = ShimRunner::from_config(config).unwrap();
+    assert_eq!(runner.config().shim.path, "/opt/python/3.12.0/bin/python");
+}
@@ -0,0 +1,110 @@
+// Tests for running WebAssembly (WASI) targets through ShimRunner
+
+use shimexe_core::{ShimConfig, ShimRunner};
+use std::path::Path;
+
+/// A WASI module in text format; the runtime accepts it in place of a binary
+fn wasm_config(path: &Path, wat: &str) -> ShimConfig {
+    std::fs::write(path, wat).unwrap();
+    ShimConfig::new("wasm-tool", path.display().to_string())
+}
+
+/// Exits with the number of arguments it received, including `argv[0]`
+#[cfg(feature = "wasm")]
+const EXIT_WITH_ARGC: &str = r#"
+(module
+  (import "wasi_snapshot_preview1" "args_sizes_get" (func (param i32 i32) (result i32)))
+  (import "wasi_snapshot_preview1" "proc_exit" (func (param i32)))
+  (memory (export "memory") 1)
+  (func (export "_start")
+    (drop (call 0 (i32.const 0) (i32.const 4)))
+    (call 1 (i32.load (i32.const 0)))))
+"#;
+
+#[cfg(feature = "wasm")]
+#[test]
+fn test_wasm_exit_code_and_args() {
+    let dir = tempfile::tempdir().unwrap();
+    let mut config = wasm_config(&dir.path().join("tool.wasm"), EXIT_WITH_ARGC);
+    config.shim.args = vec!["--check".to_string()];
+
+    let runner = ShimRunner::from_config(config).unwrap().with_history(None);
+    assert_eq!(runner.execute(&[]).unwrap(), 2);
+    assert_eq!(
+        runner.execute(&["a".to_string(), "b".to_string()]).unwrap(),
+        4
+    );
+}
+
+#[cfg(feature = "wasm")]
+#[test]
+fn test_wasm_module_returning_normally_exits_zero() {
+    let dir = tempfile::tempdir().unwrap();
+    let config = wasm_config(
+        &dir.path().join("noop.wasm"),
+        r#"(module (memory (export "memory") 1) (func (export "_start")))"#,
+    );
+
+    let runner = ShimRunner::from_config(config).unwrap().with_history(None);
+    assert_eq!(runner.execute(&[]).unwrap(), 0);
+}
+
+#[cfg(feature = "wasm")]
+#[test]
+fn test_wasm_timeout_interrupts_guest() {
+    let dir = tempfile::tempdir().unwrap();
+    let mut config = wasm_config(
+        &dir.path().join("spin.wasm"),
+        r#"(module (memory (export "memory") 1) (func (export "_start") (loop (br 0))))"#,
+    );
+    config.timeout = Some(shimexe_core::TimeoutConfig::new(1));
+
+    let runner = ShimRunner::from_config(config).unwrap().with_history(None);
+    assert_eq!(runner.execute(&[]).unwrap(), 124);
+}
+
+#[cfg(not(feature = "wasm"))]
+#[test]
+fn test_wasm_requires_feature() {
+    let dir = tempfile::tempdir().unwrap();
+    let config = wasm_config(&dir.path().join("tool.wasm"), "(module)");
+
+    let runner = ShimRunner::from_config(config).unwrap().with_history(None);
+    let err = runner.execute(&[]).unwrap_err();
+    assert!(
+        matches!(err, shimexe_core::ShimError::Config(ref msg) if msg.contains("`wasm` feature"))
+    );
+}
+
+#[test]
+fn test_wasm_rejects_process_only_sections() {
+    let dir = tempfile::tempdir().unwrap();
+    let mut config = wasm_config(&dir.path().join("tool.wasm"), "(module)");
+    config.limits = Some(shimexe_core::LimitsConfig::default());
+    config.exit = Some(shimexe_core::ExitConfig {
+        fail_on_stderr_regex: Some("^ERROR".to_string()),
+        ..Default::default()
+    });
+
+    let runner = ShimRunner::from_config(config).unwrap().with_history(None);
+    let err = runner.execute(&[]).unwrap_err();
+    assert!(matches!(
+        err,
+        shimexe_core::ShimError::Config(ref msg)
+            if msg.contains("[exit] fail_on_*_regex") && msg.contains("[limits]")
+    ));
+}
+
+#[cfg(unix)]
+#[test]
+fn test_validate_accepts_non_executable_module() {
+    use std::os::unix::fs::PermissionsExt;
+
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("tool.wasm");
+    let config = wasm_config(&path, "(module)");
+    std::fs::set_permissions(&path, std::fs::Permissions::from_mode(0o644)).unwrap();
+
+    let runner = ShimRunner::from_config(config).unwrap().with_history(None);
+    runner.validate().unwrap();
+}
@@ -0,0 +1,51 @@
+// Test [[args.rewrite]] rules applied to user args
+
+use shimexe_core::template::{ArgsConfig, ArgsMode, TemplateEngine};
+use shimexe_core::{ShimConfig, ShimRunner};
+
+const REWRITE: &str = r#"
+mode = "merge"
+prefix = ["run"]
+
+[[rewrite]]
+match = "^--old-flag(=.*)?$"
+replace = "--new-flag$1"
+
+[[rewrite]]
+match = "^--unsupported$"
+drop = true
+"#;
+
+#[test]
+fn test_rewrite_applies_to_user_args_only() {
+    let cfg: ArgsConfig = toml::from_str(REWRITE).unwrap();
+    assert_eq!(cfg.mode, ArgsMode::Merge);
+
+    let mut engine = TemplateEngine::new(vec![
+        "--old-flag=1".into(),
+        "--unsupported".into(),
+        "file".into(),
+    ]);
+    let out = engine.process_args(&cfg).unwrap();
+    assert_eq!(out, vec!["run", "--new-flag=1", "file"]);
+}
+
+#[test]
+fn test_rewritten_argv_in_explain() {
+    let mut config = ShimConfig::new("tool", "/bin/tool");
+    config.args = toml::from_str(REWRITE).unwrap();
+    config.validate().unwrap();
+
+    let runner = ShimRunner::from_config(config).unwrap();
+    let explanation = runner
+        .explain(&["--old-flag".to_string(), "--unsupported".to_string()])
+        .unwrap();
+    assert_eq!(explanation.argv, vec!["run", "--new-flag"]);
+}
+
+#[test]
+fn test_invalid_rule_fails_validation() {
+    let mut config = ShimConfig::new("tool", "/bin/tool");
+    config.args = toml::from_str("[[rewrite]]\nmatch = '--x'").unwrap();
+    assert!(config.validate().is_err());
+}
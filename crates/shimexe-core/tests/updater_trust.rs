@@ -0,0 +1,48 @@
+// Tests for ShimUpdater refusing update commands from untrusted shim files
+
+#![cfg(unix)]
+
+use shimexe_core::config::{AutoUpdate, UpdateProvider, VersionCheck};
+use shimexe_core::{ShimError, ShimUpdater, TrustStore};
+
+#[tokio::test]
+async fn test_update_commands_require_trusted_shim_file() {
+    let dir = tempfile::tempdir().unwrap();
+    std::env::set_var(
+        shimexe_core::trust::TRUST_FILE_ENV_VAR,
+        dir.path().join("trust.json"),
+    );
+
+    let marker = dir.path().join("updated");
+    let shim_file = dir.path().join("tool.shim.toml");
+    std::fs::write(&shim_file, "[shim]\nname = \"tool\"\npath = \"tool\"\n").unwrap();
+
+    let config = AutoUpdate {
+        enabled: true,
+        provider: UpdateProvider::Custom {
+            update_command: format!("echo {{version}} > {}", marker.display()),
+            version_command: "tool --version".to_string(),
+        },
+        download_url: String::new(),
+        version_check: VersionCheck::Semver {
+            current: "1.0.0".to_string(),
+            check_url: String::new(),
+        },
+        check_interval_hours: 24,
+        pre_update_command: None,
+        post_update_command: None,
+    };
+    let updater = ShimUpdater::new(config, shim_file.clone(), dir.path().join("tool"));
+
+    let err = updater.update_to_version("2.0.0").await.unwrap_err();
+    assert!(matches!(err, ShimError::Untrusted(_)), "{}", err);
+    assert!(err.to_string().contains("shimexe trust"), "{}", err);
+    assert!(!marker.exists());
+
+    let mut store = TrustStore::load().unwrap();
+    store.trust(&shim_file).unwrap();
+    store.save().unwrap();
+
+    updater.update_to_version("2.0.0").await.unwrap();
+    assert_eq!(std::fs::read_to_string(&marker).unwrap().trim(), "2.0.0");
+}
@@ -43,19 +43,10 @@ impl VxShimIntegration {
         Ok(shim_path)
     }
 
-    /// Switch tool to a different version
+    /// Switch tool to a different version, installing it next to the others
     pub fn switch_version(&self, name: &str, version: &str, tool_path: &str) -> Result<()> {
-        let config = self
-            .manager
-            .builder(name)
-            .path(tool_path)
-            .version(version)
-            .description(format!("{} managed by vx", name))
-            .tag("vx-managed")
-            .tag(name)
-            .build()?;
-
-        self.manager.update_shim(name, config)?;
+        self.manager.install_version(name, version, tool_path)?;
+        self.manager.use_version(name, version)?;
         println!("Switched {} to version {}", name, version);
         Ok(())
     }
@@ -203,6 +194,13 @@ mod tests {
         let info = vx.get_tool_info("node")?.unwrap();
         assert_eq!(info.version, Some("20.5.0".to_string()));
 
+        // Both versions stay installed, so switching back is cheap
+        let versions = vx.manager.list_versions("node")?;
+        assert_eq!(versions.len(), 2);
+        vx.manager.use_version("node", "18.17.0")?;
+        let info = vx.get_tool_info("node")?.unwrap();
+        assert_eq!(info.version, Some("18.17.0".to_string()));
+
         Ok(())
     }
 }
@@ -226,6 +224,7 @@ mod old_approach_comparison {
                 download_url: None,
                 source_type: SourceType::File,
                 extracted_executables: Vec::new(),
+                ..Default::default()
             },
             args: Default::default(),
             env: HashMap::new(),
@@ -236,6 +235,7 @@ mod old_approach_comparison {
                 tags: vec!["vx-managed".to_string(), name.to_string()],
             },
             auto_update: None,
+            ..Default::default()
         };
 
         // Manual file operations
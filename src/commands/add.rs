@@ -6,7 +6,7 @@ use std::path::{Path, PathBuf};
 use tracing::{debug, info};
 
 use crate::shim_manager::ShimManager;
-use shimexe_core::{Downloader, ShimConfig, ShimCore, ShimMetadata};
+use shimexe_core::{Downloader, Interpreter, ShimConfig, ShimCore, ShimMetadata};
 
 #[derive(Args)]
 pub struct AddCommand {
@@ -25,6 +25,10 @@ pub struct AddCommand {
     #[arg(short, long)]
     pub cwd: Option<String>,
 
+    /// Interpreter for script targets ("auto" infers it from the extension or shebang)
+    #[arg(long)]
+    pub interpreter: Option<String>,
+
     /// Environment variables (format: KEY=VALUE)
     #[arg(short, long, value_delimiter = ',')]
     pub env: Vec<String>,
@@ -292,6 +296,8 @@ impl AddCommand {
                 download_url,
                 source_type,
                 extracted_executables,
+                interpreter: self.interpreter.clone().map(Interpreter::Command),
+                ..Default::default()
             },
             args: Default::default(),
             env: env_vars,
@@ -302,6 +308,7 @@ impl AddCommand {
                 tags: vec![],
             },
             auto_update: None,
+            ..Default::default()
         })
     }
 
@@ -0,0 +1,76 @@
+use anyhow::Result;
+use clap::Args;
+use std::path::PathBuf;
+use tracing::info;
+
+use crate::shim_manager::ShimManager;
+use shimexe_core::ShimConfig;
+
+#[derive(Args)]
+pub struct AliasCommand {
+    /// Name of the new alias shim
+    pub name: String,
+
+    /// Existing shim the alias runs
+    pub target: String,
+
+    /// Environment variables layered over the target's (format: KEY=VALUE)
+    #[arg(short, long, value_delimiter = ',')]
+    pub env: Vec<String>,
+
+    /// Description of the alias
+    #[arg(short, long)]
+    pub description: Option<String>,
+
+    /// Force overwrite if a shim with this name already exists
+    #[arg(short, long)]
+    pub force: bool,
+
+    /// Arguments appended to the target's default arguments (after `--`)
+    #[arg(last = true)]
+    pub args: Vec<String>,
+}
+
+impl AliasCommand {
+    pub fn execute(&self, shim_dir: Option<PathBuf>) -> Result<()> {
+        let manager = ShimManager::new(shim_dir)?;
+
+        if !manager.shim_exists(&self.target) {
+            return Err(anyhow::anyhow!(
+                "Shim '{}' does not exist. Use 'shimexe list' to see available shims.",
+                self.target
+            ));
+        }
+        if manager.shim_exists(&self.name) && !self.force {
+            return Err(anyhow::anyhow!(
+                "Shim '{}' already exists. Use --force to overwrite.",
+                self.name
+            ));
+        }
+
+        let mut config = ShimConfig::new(&self.name, "");
+        config.shim.target_shim = Some(self.target.clone());
+        config.shim.args = self.args.clone();
+        for env_var in &self.env {
+            let (key, value) = env_var.split_once('=').ok_or_else(|| {
+                anyhow::anyhow!("Invalid environment variable format: {}", env_var)
+            })?;
+            config.env.insert(key.to_string(), value.to_string());
+        }
+        config.metadata.description = self.description.clone();
+        config.validate()?;
+
+        // Fails on cycles before anything is written
+        shimexe_core::alias::resolve(config.clone(), manager.shim_dir())?;
+
+        manager.add_shim(&self.name, &config)?;
+
+        info!("Successfully added alias '{}'", self.name);
+        let command: Vec<&str> = std::iter::once(&self.target)
+            .chain(&self.args)
+            .map(String::as_str)
+            .collect();
+        println!("[OK] Added alias '{}' -> {}", self.name, command.join(" "));
+        Ok(())
+    }
+}
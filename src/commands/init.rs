@@ -43,6 +43,7 @@ impl InitCommand {
                 download_url: None,
                 source_type: shimexe_core::SourceType::File,
                 extracted_executables: vec![],
+                ..Default::default()
             },
             args: Default::default(),
             env: HashMap::new(),
@@ -53,6 +54,7 @@ impl InitCommand {
                 tags: vec!["example".to_string()],
             },
             auto_update: None,
+            ..Default::default()
         };
 
         manager.add_shim("hello", &echo_config)?;
@@ -71,6 +73,7 @@ impl InitCommand {
                 download_url: None,
                 source_type: shimexe_core::SourceType::File,
                 extracted_executables: vec![],
+                ..Default::default()
             },
             args: Default::default(),
             env: env_vars,
@@ -81,6 +84,7 @@ impl InitCommand {
                 tags: ["example", "env"].iter().map(|s| s.to_string()).collect(),
             },
             auto_update: None,
+            ..Default::default()
         };
 
         manager.add_shim("greet", &env_config)?;
@@ -3,6 +3,7 @@ use clap::Args;
 use std::path::PathBuf;
 
 use crate::shim_manager::ShimManager;
+use shimexe_core::{profiles, project};
 
 #[derive(Args)]
 pub struct ListCommand {
@@ -22,9 +23,13 @@ impl ListCommand {
         }
 
         if self.detailed {
+            let cwd = std::env::current_dir()?;
             for (name, config) in shims {
                 println!("📦 {}", name);
-                println!("   Path: {}", config.shim.path);
+                match config.shim.target_shim {
+                    Some(ref target) => println!("   Alias of: {}", target),
+                    None => println!("   Path: {}", config.shim.path),
+                }
                 if !config.shim.args.is_empty() {
                     println!("   Args: {}", config.shim.args.join(" "));
                 }
@@ -34,6 +39,15 @@ impl ListCommand {
                 if !config.env.is_empty() {
                     println!("   Env:  {} variables", config.env.len());
                 }
+                if !config.profiles.is_empty() {
+                    let names: Vec<&str> = config.profiles.keys().map(String::as_str).collect();
+                    println!("   Profiles: {}", names.join(", "));
+                    // A broken project file is reported when the shim runs
+                    let project = project::find(&config, &cwd).ok().flatten();
+                    if let Some(active) = profiles::select(&config, None, project.as_ref())? {
+                        println!("   Profile:  {}", active);
+                    }
+                }
                 if let Some(ref desc) = config.metadata.description {
                     println!("   Desc: {}", desc);
                 }
@@ -42,7 +56,10 @@ impl ListCommand {
         } else {
             println!("Available shims:");
             for (name, config) in shims {
-                println!("  {} -> {}", name, config.shim.path);
+                match config.shim.target_shim {
+                    Some(ref target) => println!("  {} -> shim {}", name, target),
+                    None => println!("  {} -> {}", name, config.shim.path),
+                }
             }
         }
 
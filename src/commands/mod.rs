@@ -1,19 +1,27 @@
 pub mod add;
+pub mod alias;
 pub mod auto_update;
 pub mod init;
 pub mod list;
 pub mod remove;
 pub mod run;
+pub mod stats;
+pub mod trust;
+pub mod untrust;
 pub mod update;
 pub mod update_check;
 pub mod validate;
 
 pub use add::AddCommand;
+pub use alias::AliasCommand;
 pub use auto_update::AutoUpdateCommand;
 pub use init::InitCommand;
 pub use list::ListCommand;
 pub use remove::RemoveCommand;
 pub use run::RunCommand;
+pub use stats::StatsCommand;
+pub use trust::TrustCommand;
+pub use untrust::UntrustCommand;
 pub use update::UpdateCommand;
 pub use update_check::UpdateCheckCommand;
 pub use validate::ValidateCommand;
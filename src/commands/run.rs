@@ -3,10 +3,18 @@ use clap::Args;
 use std::path::PathBuf;
 
 use crate::shim_manager::ShimManager;
-use shimexe_core::ShimRunner;
+use shimexe_core::{ExplainFormat, ShimRunner};
 
 #[derive(Args)]
 pub struct RunCommand {
+    /// Print the resolved invocation instead of running it
+    #[arg(long)]
+    pub dry_run: bool,
+
+    /// Print the dry-run report as JSON
+    #[arg(long, requires = "dry_run")]
+    pub json: bool,
+
     /// Name of the shim to run (or path to shim file)
     pub shim_name_or_path: String,
 
@@ -17,13 +25,15 @@ pub struct RunCommand {
 
 impl RunCommand {
     pub fn execute(&self, shim_dir: Option<PathBuf>) -> Result<()> {
-        let runner = if self.shim_name_or_path.contains('/')
+        let report = shimexe_core::timing::ReportGuard::new();
+
+        let (runner, config_file) = if self.shim_name_or_path.contains('/')
             || self.shim_name_or_path.contains('\\')
             || self.shim_name_or_path.ends_with(".toml")
         {
             // Treat as file path
             let path = PathBuf::from(&self.shim_name_or_path);
-            ShimRunner::from_file(&path)?
+            (ShimRunner::from_file(&path)?, path)
         } else {
             // Treat as shim name
             let manager = ShimManager::new(shim_dir)?;
@@ -35,10 +45,27 @@ impl RunCommand {
                         self.shim_name_or_path
                     )
                 })?;
-            ShimRunner::from_config(config)?
+            let config = shimexe_core::alias::resolve(config, manager.shim_dir())?;
+            let path = manager
+                .shim_dir()
+                .join(format!("{}.shim.toml", self.shim_name_or_path));
+            (ShimRunner::from_config(config)?, path)
+        };
+
+        let format = match (self.dry_run, self.json) {
+            (true, true) => Some(ExplainFormat::Json),
+            (true, false) => Some(ExplainFormat::Text),
+            (false, _) => ExplainFormat::from_env(),
         };
+        if let Some(format) = format {
+            let mut explanation = runner.explain(&self.args)?;
+            explanation.config_file = Some(config_file);
+            println!("{}", explanation.render(format));
+            return Ok(());
+        }
 
         let exit_code = runner.execute(&self.args)?;
+        drop(report);
         std::process::exit(exit_code);
     }
 }
@@ -0,0 +1,103 @@
+use anyhow::Result;
+use clap::Args;
+use serde::Serialize;
+use std::path::PathBuf;
+
+use crate::shim_manager::ShimManager;
+use shimexe_core::history::{never_used, summarize};
+use shimexe_core::{History, ShimUsage};
+
+#[derive(Args)]
+pub struct StatsCommand {
+    /// Number of shims to show
+    #[arg(short = 'n', long, default_value_t = 10)]
+    pub top: usize,
+
+    /// Only include invocations from the last N days
+    #[arg(long)]
+    pub days: Option<u32>,
+
+    /// Print the statistics as JSON
+    #[arg(long)]
+    pub json: bool,
+}
+
+#[derive(Serialize)]
+struct StatsReport<'a> {
+    total_runs: usize,
+    shims: &'a [ShimUsage],
+    never_used: Vec<&'a str>,
+}
+
+impl StatsCommand {
+    pub fn execute(&self, shim_dir: Option<PathBuf>) -> Result<()> {
+        let manager = ShimManager::new(shim_dir)?;
+        let known: Vec<String> = manager
+            .list_shims()?
+            .into_iter()
+            .map(|(name, _)| name)
+            .collect();
+
+        let Some(mut history) = History::location() else {
+            println!("Cannot locate the execution history: no home directory.");
+            return Ok(());
+        };
+        if let Some(days) = self.days {
+            history = history.with_max_age_days(days);
+        }
+
+        let records = history.read()?;
+        let usage = summarize(&records);
+        let top = &usage[..usage.len().min(self.top)];
+        let report = StatsReport {
+            total_runs: records.len(),
+            shims: top,
+            never_used: never_used(&known, &records),
+        };
+
+        if self.json {
+            println!("{}", serde_json::to_string_pretty(&report)?);
+            return Ok(());
+        }
+
+        if records.is_empty() {
+            println!("No recorded invocations in {}", history.path().display());
+            println!(
+                "Recording is opt-in: set SHIMEXE_HISTORY=1 or add `[history] enabled = true` to a shim."
+            );
+        } else {
+            println!(
+                "{} invocations of {} shims ({})",
+                report.total_runs,
+                usage.len(),
+                history.path().display()
+            );
+            println!();
+            println!(
+                "  {:<24} {:>7} {:>9} {:>9} {:>9}  LAST USED",
+                "SHIM", "RUNS", "FAILED", "P50", "P95"
+            );
+            for shim in report.shims {
+                println!(
+                    "  {:<24} {:>7} {:>8.1}% {:>7}ms {:>7}ms  {}",
+                    shim.shim,
+                    shim.runs,
+                    shim.failure_rate() * 100.0,
+                    shim.p50_ms,
+                    shim.p95_ms,
+                    shim.last_used.format("%Y-%m-%d %H:%M")
+                );
+            }
+        }
+
+        if !report.never_used.is_empty() {
+            println!();
+            println!("Never used:");
+            for name in &report.never_used {
+                println!("  {}", name);
+            }
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,103 @@
+use anyhow::Result;
+use clap::Args;
+use std::path::PathBuf;
+
+use crate::shim_manager::ShimManager;
+use shimexe_core::project::{ProjectConfig, ProjectFile};
+use shimexe_core::{ShimConfig, TrustStore};
+
+#[derive(Args)]
+pub struct TrustCommand {
+    /// Project or shim file to trust (defaults to the nearest .shimexe.toml,
+    /// or the nearest project file of --shim)
+    pub path: Option<PathBuf>,
+
+    /// Find the project file with this shim's [project] settings
+    /// (file_name, max_depth, stop_at_home)
+    #[arg(long, conflicts_with = "path")]
+    pub shim: Option<String>,
+
+    /// List the trusted files instead
+    #[arg(long, conflicts_with_all = ["path", "shim"])]
+    pub list: bool,
+}
+
+/// `path`, or the nearest project file above the working directory
+///
+/// The search uses the `[project]` settings of `shim` when one is given, and
+/// the defaults otherwise.
+pub fn project_file_or_nearest(
+    path: Option<&PathBuf>,
+    shim: Option<&str>,
+    shim_dir: Option<PathBuf>,
+) -> Result<PathBuf> {
+    if let Some(path) = path {
+        return Ok(path.clone());
+    }
+    let settings = match shim {
+        Some(name) => {
+            let config = ShimManager::new(shim_dir)?.get_shim_config(name)?;
+            let settings = config.project.unwrap_or_default();
+            if !settings.enabled {
+                anyhow::bail!("Shim '{}' does not use project files", name);
+            }
+            settings
+        }
+        None => ProjectConfig::default(),
+    };
+
+    let cwd = std::env::current_dir()?;
+    settings
+        .find(&cwd)
+        .ok_or_else(|| anyhow::anyhow!("No {} found from {}", settings.file_name, cwd.display()))
+}
+
+impl TrustCommand {
+    pub fn execute(&self, shim_dir: Option<PathBuf>) -> Result<()> {
+        if self.list {
+            return self.list_trusted();
+        }
+
+        let path = project_file_or_nearest(self.path.as_ref(), self.shim.as_deref(), shim_dir)?;
+
+        // Refuse to trust a file that could not be applied anyway
+        let is_shim_file = path.to_string_lossy().ends_with(".shim.toml");
+        let overrides: Vec<String> = if is_shim_file {
+            ShimConfig::from_file(&path)?;
+            Vec::new()
+        } else {
+            ProjectFile::from_file(&path)?.shims.into_keys().collect()
+        };
+
+        let mut store = TrustStore::load()?;
+        let path = store.trust(&path)?;
+        store.save()?;
+
+        println!("[OK] Trusted {}", path.display());
+        if !overrides.is_empty() {
+            println!("  Overrides: {}", overrides.join(", "));
+        }
+        Ok(())
+    }
+
+    fn list_trusted(&self) -> Result<()> {
+        let store = TrustStore::load()?;
+        let files: Vec<_> = store.files().collect();
+        if files.is_empty() {
+            println!("No trusted files.");
+            return Ok(());
+        }
+
+        println!("Trusted files:");
+        for file in files {
+            if !file.exists() {
+                println!("  {} (missing)", file.display());
+            } else if !store.is_trusted(file) {
+                println!("  {} (changed since trusted)", file.display());
+            } else {
+                println!("  {}", file.display());
+            }
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,33 @@
+use anyhow::Result;
+use clap::Args;
+use std::path::PathBuf;
+
+use super::trust::project_file_or_nearest;
+use shimexe_core::TrustStore;
+
+#[derive(Args)]
+pub struct UntrustCommand {
+    /// Project or shim file to stop trusting (defaults to the nearest
+    /// .shimexe.toml, or the nearest project file of --shim)
+    pub path: Option<PathBuf>,
+
+    /// Find the project file with this shim's [project] settings
+    /// (file_name, max_depth, stop_at_home)
+    #[arg(long, conflicts_with = "path")]
+    pub shim: Option<String>,
+}
+
+impl UntrustCommand {
+    pub fn execute(&self, shim_dir: Option<PathBuf>) -> Result<()> {
+        let path = project_file_or_nearest(self.path.as_ref(), self.shim.as_deref(), shim_dir)?;
+
+        let mut store = TrustStore::load()?;
+        let path = store
+            .untrust(&path)
+            .ok_or_else(|| anyhow::anyhow!("{} is not trusted", path.display()))?;
+        store.save()?;
+
+        println!("[OK] Untrusted {}", path.display());
+        Ok(())
+    }
+}
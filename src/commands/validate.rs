@@ -18,6 +18,11 @@ impl ValidateCommand {
         let config = ShimConfig::from_file(&self.shim_file)?;
         println!("[OK] Configuration syntax is valid");
 
+        let config = match self.shim_file.parent() {
+            Some(shim_dir) => shimexe_core::alias::resolve(config, shim_dir)?,
+            None => config,
+        };
+
         // Create runner and validate executable
         let runner = ShimRunner::from_config(config)?;
         runner.validate()?;
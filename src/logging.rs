@@ -0,0 +1,125 @@
+use anyhow::Result;
+use std::fs::OpenOptions;
+use std::io::IsTerminal;
+use std::path::Path;
+use std::sync::Mutex;
+use tracing_subscriber::fmt::writer::BoxMakeWriter;
+use tracing_subscriber::EnvFilter;
+
+use shimexe_core::{LogFormat, LoggingConfig};
+
+/// Level or filter directives for shim mode (e.g. `debug`, `shimexe_core=trace`)
+pub const LOG_ENV_VAR: &str = "SHIMEXE_LOG";
+/// Append shim mode logs to this file instead of stderr
+pub const LOG_FILE_ENV_VAR: &str = "SHIMEXE_LOG_FILE";
+/// Shim mode log format (`text` or `json`)
+pub const LOG_FORMAT_ENV_VAR: &str = "SHIMEXE_LOG_FORMAT";
+
+/// Level used in shim mode when nothing else is configured
+const DEFAULT_SHIM_LEVEL: &str = "error";
+
+/// Whether logging was requested through the environment
+///
+/// In that case logging is set up before the shim configuration is loaded,
+/// so problems with finding and parsing it are logged too.
+pub fn requested_by_env() -> bool {
+    std::env::var(LOG_ENV_VAR).is_ok_and(|v| !v.trim().is_empty())
+}
+
+/// Initialize logging for shim mode
+///
+/// Environment variables take precedence over the shim's `[logging]` section.
+/// Logs never go to stdout, so the target's output stays clean. Logging
+/// problems never stop the shim: an invalid level falls back to the default
+/// level and an unwritable log file falls back to stderr, with a warning.
+pub fn init_shim_logging(config: Option<&LoggingConfig>) {
+    let level = std::env::var(LOG_ENV_VAR)
+        .ok()
+        .filter(|v| !v.trim().is_empty())
+        .or_else(|| config.and_then(|c| c.level.clone()))
+        .unwrap_or_else(|| DEFAULT_SHIM_LEVEL.to_string());
+    let filter = shim_filter(&level).unwrap_or_else(|e| {
+        eprintln!(
+            "shimexe: invalid log level '{}' ({}), using '{}'",
+            level, e, DEFAULT_SHIM_LEVEL
+        );
+        shim_filter(DEFAULT_SHIM_LEVEL).expect("default level is valid")
+    });
+
+    let format = match std::env::var(LOG_FORMAT_ENV_VAR) {
+        Ok(value) if value.eq_ignore_ascii_case("json") => LogFormat::Json,
+        Ok(value) if value.eq_ignore_ascii_case("text") => LogFormat::Text,
+        _ => config.and_then(|c| c.format).unwrap_or_default(),
+    };
+
+    let file = std::env::var_os(LOG_FILE_ENV_VAR).and_then(|path| {
+        OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&path)
+            .map_err(|e| {
+                eprintln!(
+                    "shimexe: cannot open log file {} ({}), logging to stderr",
+                    Path::new(&path).display(),
+                    e
+                )
+            })
+            .ok()
+    });
+    let (writer, ansi) = match file {
+        Some(file) => (BoxMakeWriter::new(Mutex::new(file)), false),
+        None => (
+            BoxMakeWriter::new(std::io::stderr),
+            std::io::stderr().is_terminal(),
+        ),
+    };
+
+    let builder = tracing_subscriber::fmt()
+        .with_env_filter(filter)
+        .with_writer(writer)
+        .with_ansi(ansi);
+    // Only fails when a subscriber is already installed, which keeps logging
+    let _ = match format {
+        LogFormat::Json => builder.json().try_init(),
+        LogFormat::Text => builder.try_init(),
+    };
+}
+
+/// Parse a level or filter directives for shim mode
+fn shim_filter(level: &str) -> Result<EnvFilter> {
+    EnvFilter::try_new(filter_directives(level)).map_err(Into::into)
+}
+
+/// Turn a bare level into directives covering both shimexe crates
+fn filter_directives(level: &str) -> String {
+    let level = level.trim();
+    if level.contains('=') || level.contains(',') {
+        level.to_string()
+    } else {
+        format!("shimexe={0},shimexe_core={0}", level)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{filter_directives, shim_filter, DEFAULT_SHIM_LEVEL};
+
+    #[test]
+    fn expands_bare_levels() {
+        assert_eq!(
+            filter_directives("debug"),
+            "shimexe=debug,shimexe_core=debug"
+        );
+        assert_eq!(
+            filter_directives("shimexe_core=trace"),
+            "shimexe_core=trace"
+        );
+    }
+
+    #[test]
+    fn rejects_invalid_levels() {
+        assert!(shim_filter("loud").is_err());
+        assert!(shim_filter(DEFAULT_SHIM_LEVEL).is_ok());
+        assert!(shim_filter("shimexe_core=trace").is_ok());
+    }
+}
@@ -6,11 +6,14 @@ use std::env;
 use std::path::PathBuf;
 
 mod commands;
+mod logging;
 mod path_manager;
 mod shim_manager;
 
 use commands::*;
 use shimexe_core::prelude::*;
+use shimexe_core::timing::stage;
+use shimexe_core::ExplainFormat;
 
 #[derive(Parser)]
 #[command(name = "shimexe")]
@@ -37,6 +40,8 @@ struct Cli {
 enum Commands {
     /// Add a new shim
     Add(AddCommand),
+    /// Create a shim that runs another shim with extra args and env
+    Alias(AliasCommand),
     /// Remove a shim
     Remove(RemoveCommand),
     /// List all shims
@@ -53,10 +58,18 @@ enum Commands {
     CheckUpdate(UpdateCheckCommand),
     /// Manage auto-update settings
     AutoUpdate(AutoUpdateCommand),
+    /// Show usage statistics from the execution history
+    Stats(StatsCommand),
+    /// Trust a project file's overrides or a shim file's update commands
+    Trust(TrustCommand),
+    /// Revoke the trust in a project or shim file
+    Untrust(UntrustCommand),
 }
 
 #[tokio::main]
 async fn main() -> Result<()> {
+    shimexe_core::timing::init();
+
     // Determine if we're running as a shim BEFORE parsing CLI args
     let current_exe = env::current_exe()?;
     let exe_name = current_exe
@@ -66,11 +79,6 @@ async fn main() -> Result<()> {
 
     // Only treat clearly non-shimexe names as shim invocations.
     if !is_main_cli_binary_name(exe_name) {
-        // Initialize minimal logging for shim mode (only errors by default)
-        tracing_subscriber::fmt()
-            .with_env_filter("shimexe=error")
-            .init();
-
         return run_as_shim(exe_name, &env::args().collect::<Vec<_>>()[1..]);
     }
 
@@ -99,6 +107,7 @@ async fn main() -> Result<()> {
     // Run as main CLI
     match cli.command {
         Some(Commands::Add(cmd)) => cmd.execute(cli.shim_dir).await,
+        Some(Commands::Alias(cmd)) => cmd.execute(cli.shim_dir),
         Some(Commands::Remove(cmd)) => cmd.execute(cli.shim_dir),
         Some(Commands::List(cmd)) => cmd.execute(cli.shim_dir),
         Some(Commands::Update(cmd)) => cmd.execute(cli.shim_dir),
@@ -107,6 +116,9 @@ async fn main() -> Result<()> {
         Some(Commands::Init(cmd)) => cmd.execute(cli.shim_dir),
         Some(Commands::CheckUpdate(cmd)) => cmd.execute(cli.shim_dir),
         Some(Commands::AutoUpdate(cmd)) => cmd.execute(cli.shim_dir),
+        Some(Commands::Stats(cmd)) => cmd.execute(cli.shim_dir),
+        Some(Commands::Trust(cmd)) => cmd.execute(cli.shim_dir),
+        Some(Commands::Untrust(cmd)) => cmd.execute(cli.shim_dir),
         None => {
             // No command provided, show help
             let mut cmd = Cli::command();
@@ -123,6 +135,15 @@ fn is_main_cli_binary_name(exe_name: &str) -> bool {
 
 /// Run the executable as a shim
 fn run_as_shim(shim_name: &str, args: &[String]) -> Result<()> {
+    let report = shimexe_core::timing::ReportGuard::new();
+
+    // Logging requested through the environment also covers config discovery;
+    // otherwise the shim's `[logging]` section is honored once it is loaded
+    let early_logging = logging::requested_by_env();
+    if early_logging {
+        logging::init_shim_logging(None);
+    }
+
     // First, try to find the shim file in the same directory as the executable
     let current_exe = env::current_exe()?;
     let exe_dir = current_exe
@@ -130,8 +151,9 @@ fn run_as_shim(shim_name: &str, args: &[String]) -> Result<()> {
         .unwrap_or_else(|| std::path::Path::new("."));
     let local_shim_file = exe_dir.join(format!("{}.shim.toml", shim_name));
 
-    let shim_file = if local_shim_file.exists() {
-        local_shim_file
+    let discovery = shimexe_core::timing::Span::new(stage::CONFIG_DISCOVERY);
+    let (shim_file, origin) = if local_shim_file.exists() {
+        (local_shim_file, "found next to the shim executable")
     } else {
         // Fallback to the default shim directory
         let shim_dir = get_shim_directory(None)?;
@@ -146,11 +168,28 @@ fn run_as_shim(shim_name: &str, args: &[String]) -> Result<()> {
             ));
         }
 
-        default_shim_file
+        (
+            default_shim_file,
+            "not found next to the shim executable, using the default shim directory",
+        )
     };
 
+    drop(discovery);
+
     let runner = ShimRunner::from_file(&shim_file)?;
+    if !early_logging {
+        logging::init_shim_logging(runner.config().logging.as_ref());
+    }
+
+    if let Some(format) = ExplainFormat::from_env() {
+        let mut explanation = runner.explain(args)?;
+        explanation.config_origin = Some(origin.to_string());
+        println!("{}", explanation.render(format));
+        return Ok(());
+    }
+
     let exit_code = runner.execute(args)?;
+    drop(report);
 
     std::process::exit(exit_code);
 }
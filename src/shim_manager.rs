@@ -4,7 +4,7 @@ use std::path::{Path, PathBuf};
 use tracing::{debug, info};
 
 use crate::path_manager::{DefaultSystemPathManager, SystemPathManager};
-use shimexe_core::ShimConfig;
+use shimexe_core::{ResolutionCache, ShimConfig, TrustStore};
 
 /// Manages shim files and operations
 pub struct ShimManager {
@@ -37,7 +37,7 @@ impl ShimManager {
     /// Add a new shim
     pub fn add_shim(&self, name: &str, config: &ShimConfig) -> Result<()> {
         let shim_file = self.get_shim_file_path(name);
-        config.to_file(&shim_file)?;
+        self.write_config(config, &shim_file)?;
 
         // Create executable shim (copy of shimexe binary)
         self.create_executable_shim(name, config)?;
@@ -55,6 +55,7 @@ impl ShimManager {
             fs::remove_file(&shim_file)?;
             debug!("Removed shim file: {}", shim_file.display());
         }
+        ResolutionCache::remove(&shim_file);
 
         if exe_file.exists() {
             // Also remove the local shim config file next to the executable
@@ -66,6 +67,7 @@ impl ShimManager {
                 fs::remove_file(&local_shim_file)?;
                 debug!("Removed local shim config: {}", local_shim_file.display());
             }
+            ResolutionCache::remove(&local_shim_file);
 
             fs::remove_file(&exe_file)?;
             debug!("Removed executable: {}", exe_file.display());
@@ -163,12 +165,32 @@ impl ShimManager {
             .parent()
             .unwrap_or_else(|| std::path::Path::new("."));
         let local_shim_file = exe_dir.join(format!("{}.shim.toml", name));
-        config.to_file(&local_shim_file)?;
+        self.write_config(config, &local_shim_file)?;
 
         debug!("Created local shim config: {}", local_shim_file.display());
         Ok(())
     }
 
+    /// Write a shim file, trusting it when its auto-update runs commands
+    ///
+    /// The user asked for the commands by running `add` or `update`, so the
+    /// file does not need a separate `shimexe trust`.
+    fn write_config(&self, config: &ShimConfig, shim_file: &Path) -> Result<()> {
+        config.to_file(shim_file)?;
+
+        if config
+            .auto_update
+            .as_ref()
+            .is_some_and(|auto_update| auto_update.runs_commands())
+        {
+            let mut store = TrustStore::load()?;
+            store.trust(shim_file)?;
+            store.save()?;
+            debug!("Trusted shim file: {}", shim_file.display());
+        }
+        Ok(())
+    }
+
     /// Check if the target executable is up to date compared to source
     fn is_executable_up_to_date(&self, source: &Path, target: &Path) -> Result<bool> {
         if !target.exists() {
@@ -29,11 +29,13 @@ fn test_archive_config_serialization() {
                     is_primary: false,
                 },
             ],
+            ..Default::default()
         },
         args: Default::default(),
         env: HashMap::new(),
         metadata: Default::default(),
         auto_update: None,
+        ..Default::default()
     };
 
     // Test serialization
@@ -61,11 +63,13 @@ fn test_url_config_serialization() {
             download_url: Some("https://example.com/tool.exe".to_string()),
             source_type: SourceType::Url,
             extracted_executables: vec![],
+            ..Default::default()
         },
         args: Default::default(),
         env: HashMap::new(),
         metadata: Default::default(),
         auto_update: None,
+        ..Default::default()
     };
 
     let toml_str = toml::to_string(&config).expect("Failed to serialize config");
@@ -111,6 +115,7 @@ fn test_args_config_serialization() {
         default: vec!["--help".to_string()],
         prefix: vec!["--prefix".to_string()],
         suffix: vec!["--suffix".to_string()],
+        rewrite: vec![],
     };
 
     let toml_str = toml::to_string(&args_config).unwrap();
@@ -307,6 +312,7 @@ fn test_complete_config_serialization() {
                     is_primary: false,
                 },
             ],
+            ..Default::default()
         },
         args: ArgsConfig {
             template: Some(vec!["--global-config".to_string(), "{args}".to_string(), "--log-format".to_string(), "json".to_string()]),
@@ -315,6 +321,7 @@ fn test_complete_config_serialization() {
             default: vec![],
             prefix: vec!["--prefix".to_string()],
             suffix: vec!["--suffix".to_string()],
+            rewrite: vec![],
         },
         env: env_vars,
         metadata: ShimMetadata {
@@ -339,6 +346,7 @@ fn test_complete_config_serialization() {
             pre_update_command: Some("echo 'Preparing for update'".to_string()),
             post_update_command: Some("echo 'Update completed successfully'".to_string()),
         }),
+        ..Default::default()
     };
 
     // Test full serialization and deserialization
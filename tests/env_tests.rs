@@ -105,6 +105,7 @@ fn test_shim_config_env_expansion() {
             download_url: None,
             source_type: SourceType::File,
             extracted_executables: vec![],
+            ..Default::default()
         },
         args: Default::default(),
         env: {
@@ -118,6 +119,7 @@ fn test_shim_config_env_expansion() {
         },
         metadata: Default::default(),
         auto_update: None,
+        ..Default::default()
     };
 
     // Expand environment variables
@@ -199,6 +201,7 @@ fn test_shim_runner_env_vars() {
             download_url: None,
             source_type: SourceType::File,
             extracted_executables: vec![],
+            ..Default::default()
         },
         args: Default::default(),
         env: {
@@ -209,6 +212,7 @@ fn test_shim_runner_env_vars() {
         },
         metadata: Default::default(),
         auto_update: None,
+        ..Default::default()
     };
 
     // Create runner from config
@@ -237,11 +241,13 @@ fn test_empty_env_config() {
             download_url: None,
             source_type: SourceType::File,
             extracted_executables: vec![],
+            ..Default::default()
         },
         args: Default::default(),
         env: HashMap::new(), // Empty environment
         metadata: Default::default(),
         auto_update: None,
+        ..Default::default()
     };
 
     let runner = ShimRunner::from_config(config).unwrap();
@@ -261,6 +267,7 @@ fn test_env_vars_with_special_characters() {
             download_url: None,
             source_type: SourceType::File,
             extracted_executables: vec![],
+            ..Default::default()
         },
         args: Default::default(),
         env: {
@@ -278,6 +285,7 @@ fn test_env_vars_with_special_characters() {
         },
         metadata: Default::default(),
         auto_update: None,
+        ..Default::default()
     };
 
     // Expand environment variables
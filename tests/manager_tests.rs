@@ -20,6 +20,7 @@ fn create_test_config(name: &str) -> ShimConfig {
             download_url: None,
             source_type: SourceType::File,
             extracted_executables: vec![],
+            ..Default::default()
         },
         args: Default::default(),
         env: HashMap::new(),
@@ -30,6 +31,7 @@ fn create_test_config(name: &str) -> ShimConfig {
             tags: vec!["test".to_string()],
         },
         auto_update: None,
+        ..Default::default()
     }
 }
 
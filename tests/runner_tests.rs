@@ -14,11 +14,13 @@ fn test_runner_from_config() {
             download_url: None,
             source_type: SourceType::File,
             extracted_executables: vec![],
+            ..Default::default()
         },
         args: Default::default(),
         env: HashMap::new(),
         metadata: Default::default(),
         auto_update: None,
+        ..Default::default()
     };
 
     let runner = ShimRunner::from_config(config).unwrap();
@@ -257,6 +257,7 @@ fn test_args_config_serialization() {
         default: vec!["default".to_string()],
         prefix: vec!["prefix".to_string()],
         suffix: vec!["suffix".to_string()],
+        rewrite: vec![],
     };
 
     // Test TOML serialization
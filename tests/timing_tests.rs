@@ -0,0 +1,64 @@
+// Tests for the SHIMEXE_TIMING report written by a shim executable
+#![cfg(unix)]
+
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+/// Copy the shimexe binary next to a shim file so it runs as shim `name`
+fn install_shim(dir: &Path, name: &str, path: &str) -> PathBuf {
+    let exe = dir.join(name);
+    std::fs::copy(env!("CARGO_BIN_EXE_shimexe"), &exe).unwrap();
+    std::fs::write(
+        dir.join(format!("{}.shim.toml", name)),
+        format!("[shim]\nname = \"{}\"\npath = \"{}\"\n", name, path),
+    )
+    .unwrap();
+    exe
+}
+
+fn run_with_timing(exe: &Path, home: &Path, report: &Path) -> std::process::ExitStatus {
+    Command::new(exe)
+        .env("HOME", home)
+        .env("SHIMEXE_TIMING", report)
+        .env_remove("SHIMEXE_LOG")
+        .output()
+        .unwrap()
+        .status
+}
+
+#[test]
+fn test_timing_report_lists_stages() {
+    let dir = tempfile::tempdir().unwrap();
+    let exe = install_shim(dir.path(), "timed", "true");
+    let report = dir.path().join("timing.txt");
+
+    assert!(run_with_timing(&exe, dir.path(), &report).success());
+    let report = std::fs::read_to_string(report).unwrap();
+    for stage in [
+        "config discovery",
+        "toml parse",
+        "executable resolution",
+        "spawn",
+        "target",
+        "total",
+    ] {
+        assert!(
+            report.contains(stage),
+            "missing '{}' in:\n{}",
+            stage,
+            report
+        );
+    }
+}
+
+#[test]
+fn test_timing_report_written_when_shim_fails() {
+    let dir = tempfile::tempdir().unwrap();
+    let exe = install_shim(dir.path(), "broken", "shimexe-missing-target");
+    let report = dir.path().join("timing.txt");
+
+    assert!(!run_with_timing(&exe, dir.path(), &report).success());
+    let report = std::fs::read_to_string(report).unwrap();
+    assert!(report.contains("config discovery"), "{}", report);
+    assert!(!report.contains("spawn"), "{}", report);
+}
@@ -15,11 +15,13 @@ fn create_test_config() -> ShimConfig {
             download_url: None,
             source_type: SourceType::File,
             extracted_executables: vec![],
+            ..Default::default()
         },
         args: Default::default(),
         env: HashMap::new(),
         metadata: Default::default(),
         auto_update: None,
+        ..Default::default()
     }
 }
 